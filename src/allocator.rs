@@ -5,28 +5,217 @@ use std::fmt;
 use std::os;
 use std::mem;
 use std::ptr;
+use std::sync;
+use std::sync::atomic;
+
+/// A pluggable backing strategy for `Allocator`, mirroring the three operations V8's own
+/// `ArrayBuffer::Allocator` dispatches to (`Allocate`, `AllocateUninitialized`, `Free`).
+///
+/// Implement this to hand array buffers out of an arena, a pool, a pre-reserved `mmap` region, or
+/// an instrumented allocator, without forking the crate. `Allocator::with_backend` boxes the
+/// implementation and drives it from the `extern "C"` callbacks V8 calls into directly.
+pub trait ArrayBufferBackend: Send + Sync {
+    /// Allocates `len` zeroed bytes, or returns `None` on failure.
+    ///
+    /// Returning `None` rather than aborting lets V8 raise a catchable `RangeError` instead of
+    /// taking down the whole process.
+    fn allocate(&self, len: usize) -> Option<*mut u8>;
+
+    /// Allocates `len` bytes without zeroing them, or returns `None` on failure.
+    fn allocate_uninitialized(&self, len: usize) -> Option<*mut u8>;
+
+    /// Frees a block of `len` bytes previously returned by `allocate`/`allocate_uninitialized`.
+    fn free(&self, data: *mut u8, len: usize);
+}
+
+/// The number of header bytes `VecBackend` prepends to every allocation, holding the `Vec`'s
+/// true capacity so `free` can reconstruct it exactly rather than guessing `cap == len`.
+const HEADER_SIZE: usize = mem::size_of::<usize>();
+
+/// The default `ArrayBufferBackend`: backs every allocation with a `Vec<u8>`.
+///
+/// `try_reserve_exact` only asks the allocator not to over-allocate; it's free to hand back more
+/// than requested, so the true capacity can differ from `len`. Each allocation is prefixed with a
+/// `HEADER_SIZE`-byte header recording that true capacity (written/read via the `_unaligned`
+/// accessors, since a `Vec<u8>` allocation isn't guaranteed aligned for a `usize`), so `free` can
+/// reconstruct the original `Vec` exactly instead of risking a capacity mismatch.
+struct VecBackend;
+
+impl VecBackend {
+    fn allocate_with_header(len: usize, zero: bool) -> Option<*mut u8> {
+        let total = len.checked_add(HEADER_SIZE)?;
+
+        let mut data = Vec::<u8>::new();
+        if data.try_reserve_exact(total).is_err() {
+            return None;
+        }
+        if zero {
+            data.resize(total, 0u8);
+        } else {
+            unsafe {
+                data.set_len(total);
+            }
+        }
+
+        let cap = data.capacity();
+        let header_ptr = data.as_mut_ptr();
+        mem::forget(data);
+
+        unsafe {
+            ptr::write_unaligned(header_ptr as *mut usize, cap);
+            Some(header_ptr.add(HEADER_SIZE))
+        }
+    }
+}
+
+impl ArrayBufferBackend for VecBackend {
+    fn allocate(&self, len: usize) -> Option<*mut u8> {
+        VecBackend::allocate_with_header(len, true)
+    }
+
+    fn allocate_uninitialized(&self, len: usize) -> Option<*mut u8> {
+        VecBackend::allocate_with_header(len, false)
+    }
+
+    fn free(&self, data: *mut u8, _len: usize) {
+        unsafe {
+            let header_ptr = data.sub(HEADER_SIZE);
+            let cap = ptr::read_unaligned(header_ptr as *const usize);
+            drop(Vec::from_raw_parts(header_ptr, cap, cap));
+        }
+    }
+}
+
+/// Tracks how many bytes a `LimitedBackend` currently has outstanding, so allocation can be
+/// capped at a configured budget.
+struct Accounting {
+    max_bytes: usize,
+    allocated: atomic::AtomicUsize,
+    high_water_mark: atomic::AtomicUsize,
+}
+
+impl Accounting {
+    fn new(max_bytes: usize) -> Accounting {
+        Accounting {
+            max_bytes: max_bytes,
+            allocated: atomic::AtomicUsize::new(0),
+            high_water_mark: atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Atomically reserves `len` bytes against the budget, returning `false` without reserving
+    /// anything if that would exceed `max_bytes`.
+    fn try_add(&self, len: usize) -> bool {
+        loop {
+            let current = self.allocated.load(atomic::Ordering::SeqCst);
+            let next = match current.checked_add(len) {
+                Some(next) if next <= self.max_bytes => next,
+                _ => return false,
+            };
+            if self.allocated.compare_and_swap(current, next, atomic::Ordering::SeqCst) == current {
+                self.high_water_mark.fetch_max(next, atomic::Ordering::SeqCst);
+                return true;
+            }
+        }
+    }
+
+    fn sub(&self, len: usize) {
+        self.allocated.fetch_sub(len, atomic::Ordering::SeqCst);
+    }
+}
+
+/// An `ArrayBufferBackend` adapter that enforces a byte budget around another backend,
+/// rejecting allocations that would push the running total past `max_bytes`.
+struct LimitedBackend<B> {
+    inner: B,
+    accounting: sync::Arc<Accounting>,
+}
+
+impl<B: ArrayBufferBackend> ArrayBufferBackend for LimitedBackend<B> {
+    fn allocate(&self, len: usize) -> Option<*mut u8> {
+        if !self.accounting.try_add(len) {
+            return None;
+        }
+        let result = self.inner.allocate(len);
+        if result.is_none() {
+            self.accounting.sub(len);
+        }
+        result
+    }
+
+    fn allocate_uninitialized(&self, len: usize) -> Option<*mut u8> {
+        if !self.accounting.try_add(len) {
+            return None;
+        }
+        let result = self.inner.allocate_uninitialized(len);
+        if result.is_none() {
+            self.accounting.sub(len);
+        }
+        result
+    }
+
+    fn free(&self, data: *mut u8, len: usize) {
+        self.inner.free(data, len);
+        self.accounting.sub(len);
+    }
+}
 
 /// A simple array buffer allocator that guarantees that all allocated
 /// blocks are coercible to `Vec`s of `u8`.
-pub struct Allocator(ptr::Shared<v8_sys::ArrayBuffer_Allocator>);
+pub struct Allocator(ptr::Shared<v8_sys::ArrayBuffer_Allocator>, Option<sync::Arc<Accounting>>);
 
 impl Allocator {
-    /// Creates a new allocator.
+    /// Creates a new allocator, backed by `Vec<u8>`.
     pub fn new() -> Allocator {
-        let raw = unsafe {
+        Allocator::with_backend(VecBackend)
+    }
+
+    /// Creates a new allocator that dispatches every allocation to `backend`.
+    pub fn with_backend<B: ArrayBufferBackend + 'static>(backend: B) -> Allocator {
+        Allocator(Allocator::create_raw(backend), None)
+    }
+
+    /// Creates a new allocator backed by `Vec<u8>`, rejecting any allocation that would push the
+    /// running total of outstanding bytes past `max_bytes`.
+    ///
+    /// Use `bytes_allocated`/`high_water_mark` to monitor usage against the budget.
+    pub fn with_limit(max_bytes: usize) -> Allocator {
+        let accounting = sync::Arc::new(Accounting::new(max_bytes));
+        let backend = LimitedBackend { inner: VecBackend, accounting: accounting.clone() };
+
+        Allocator(Allocator::create_raw(backend), Some(accounting))
+    }
+
+    fn create_raw<B: ArrayBufferBackend + 'static>(
+        backend: B,
+    ) -> ptr::Shared<v8_sys::ArrayBuffer_Allocator> {
+        let boxed: Box<ArrayBufferBackend> = Box::new(backend);
+        let this = Box::into_raw(Box::new(boxed)) as *mut os::raw::c_void;
+
+        unsafe {
             ptr::Shared::new(v8_sys::impls::CreateArrayBufferAllocator(
                 ALLOCATOR_FUNCTIONS,
-                ptr::null_mut(),
+                this,
             ))
-        }.expect("could not create ArrayBuffer::Allocator");
-
-        Allocator(raw)
+        }.expect("could not create ArrayBuffer::Allocator")
     }
 
     /// Returns the underlying raw pointer behind this allocator.
     pub fn as_ptr(&self) -> *mut v8_sys::ArrayBuffer_Allocator {
         self.0.as_ptr()
     }
+
+    /// The number of bytes currently outstanding against the budget configured via
+    /// `with_limit`, or `0` for an allocator with no configured limit.
+    pub fn bytes_allocated(&self) -> usize {
+        self.1.as_ref().map_or(0, |a| a.allocated.load(atomic::Ordering::SeqCst))
+    }
+
+    /// The highest `bytes_allocated` has ever reached, or `0` for an allocator with no
+    /// configured limit.
+    pub fn high_water_mark(&self) -> usize {
+        self.1.as_ref().map_or(0, |a| a.high_water_mark.load(atomic::Ordering::SeqCst))
+    }
 }
 
 impl fmt::Debug for Allocator {
@@ -45,7 +234,7 @@ impl Drop for Allocator {
 
 const ALLOCATOR_FUNCTIONS: v8_sys::impls::ArrayBufferAllocatorFunctions =
     v8_sys::impls::ArrayBufferAllocatorFunctions {
-        Destroy: None,
+        Destroy: Some(destroy),
         Allocate: Some(allocate),
         AllocateUninitialized: Some(allocate_uninitialized),
         Reserve: None,
@@ -54,48 +243,46 @@ const ALLOCATOR_FUNCTIONS: v8_sys::impls::ArrayBufferAllocatorFunctions =
         SetProtection: None,
     };
 
+unsafe fn backend<'a>(this: *mut os::raw::c_void) -> &'a ArrayBufferBackend {
+    &**(this as *const Box<ArrayBufferBackend>)
+}
+
 unsafe extern "C" fn allocate(
-    _this: *mut os::raw::c_void,
+    this: *mut os::raw::c_void,
     _fallback_fn: Option<unsafe extern "C" fn(*mut os::raw::c_void, usize) -> *mut os::raw::c_void>,
     _fallback_arg: *mut os::raw::c_void,
     length: usize,
 ) -> *mut os::raw::c_void {
-    let mut data = Vec::with_capacity(length);
-    data.resize(length, 0u8);
-    let ptr = data.as_mut_ptr();
-    mem::forget(data);
-
-    ptr as *mut os::raw::c_void
+    match backend(this).allocate(length) {
+        Some(ptr) => ptr as *mut os::raw::c_void,
+        None => ptr::null_mut(),
+    }
 }
 
 unsafe extern "C" fn allocate_uninitialized(
-    _this: *mut os::raw::c_void,
+    this: *mut os::raw::c_void,
     _fallback_fn: Option<unsafe extern "C" fn(*mut os::raw::c_void, usize) -> *mut os::raw::c_void>,
     _fallback_arg: *mut os::raw::c_void,
     length: usize,
 ) -> *mut os::raw::c_void {
-    let mut data = Vec::with_capacity(length);
-    data.set_len(length);
-
-    let ptr = data.as_mut_ptr();
-    mem::forget(data);
-
-    ptr as *mut os::raw::c_void
+    match backend(this).allocate_uninitialized(length) {
+        Some(ptr) => ptr as *mut os::raw::c_void,
+        None => ptr::null_mut(),
+    }
 }
 
 unsafe extern "C" fn free(
-    _this: *mut os::raw::c_void,
+    this: *mut os::raw::c_void,
     _fallback_fn: Option<unsafe extern "C" fn(*mut os::raw::c_void, *mut os::raw::c_void, usize)>,
     _fallback_arg: *mut os::raw::c_void,
     data: *mut os::raw::c_void,
     length: usize,
 ) {
-    // TODO: restore `cap` here?  Can this possibly leak memory?
-    drop(Vec::from_raw_parts(data, length, length));
+    backend(this).free(data as *mut u8, length);
 }
 
 unsafe extern "C" fn free_mode(
-    _this: *mut os::raw::c_void,
+    this: *mut os::raw::c_void,
     fallback_fn: Option<
         unsafe extern "C" fn(*mut os::raw::c_void,
                              *mut os::raw::c_void,
@@ -108,9 +295,12 @@ unsafe extern "C" fn free_mode(
     mode: v8_sys::ArrayBuffer_Allocator_AllocationMode,
 ) {
     if mode == v8_sys::ArrayBuffer_Allocator_AllocationMode_kNormal {
-        // TODO: restore `cap` here?  Can this possibly leak memory?
-        drop(Vec::from_raw_parts(data, length, length));
+        backend(this).free(data as *mut u8, length);
     } else {
         fallback_fn.unwrap()(fallback_arg, data, length, mode);
     }
 }
+
+unsafe extern "C" fn destroy(this: *mut os::raw::c_void) {
+    drop(Box::from_raw(this as *mut Box<ArrayBufferBackend>));
+}
@@ -0,0 +1,92 @@
+//! Optional `arrow2` integration.
+//!
+//! Converts the concrete typed-array types in `value` into arrow2 `PrimitiveArray<T>`s and back.
+//! `to_arrow` shares the JS typed array's backing store directly (via an `Arc` that keeps the
+//! owning `value::ArrayBuffer` alive) instead of copying it, so a column of numbers crosses from
+//! the JS heap into an arrow pipeline in O(1). `from_arrow` copies the arrow values into a new
+//! buffer; that direction is O(n) and shares no memory with the arrow array.
+
+use arrow2::array::PrimitiveArray;
+use arrow2::buffer::Buffer;
+use arrow2::datatypes::DataType;
+use arrow2::types::NativeType;
+use std::any;
+use std::mem;
+use std::sync;
+use error;
+use isolate;
+use value;
+
+/// Keeps the `value::ArrayBuffer` a zero-copy arrow `Buffer` was built on top of alive for as
+/// long as that buffer is: nothing else pins the allocation once the original typed array handle
+/// this was sliced from is dropped.
+struct BufferOwner {
+    _buffer: value::ArrayBuffer,
+}
+
+/// Generates `to_arrow`/`from_arrow` for a concrete typed-array type, mapping it to arrow2's
+/// `$elem: NativeType`.
+macro_rules! arrow_accessors {
+    ($typ:ident, $elem:ty) => {
+        impl value::$typ {
+            /// A zero-copy view of this typed array as an arrow2 `PrimitiveArray<$elem>`.
+            ///
+            /// The returned array carries no validity bitmap: every slot of a JS typed array is
+            /// always present, so there's no room for a null to represent.
+            ///
+            /// Panics if this view's backing `ArrayBuffer` has been detached.
+            pub fn to_arrow(&self) -> PrimitiveArray<$elem> {
+                let buffer = self.buffer();
+                let offset = self.byte_offset();
+                let len = self.byte_length() / mem::size_of::<$elem>();
+                let store = buffer.get_backing_store().expect("backing ArrayBuffer was detached");
+
+                let data = unsafe { store.as_slice()[offset..].as_ptr() as *const $elem };
+                let owner: sync::Arc<any::Any> = sync::Arc::new(BufferOwner { _buffer: buffer });
+                let values = unsafe { Buffer::from_foreign(data, len, owner) };
+
+                PrimitiveArray::new(DataType::from(<$elem as NativeType>::PRIMITIVE), values, None)
+            }
+
+            /// Copies `array`'s values into a new `ArrayBuffer`-backed `$typ`. The result does not
+            /// share memory with `array`.
+            ///
+            /// Fails if `array` carries a non-empty validity bitmap: a JS typed array has no null
+            /// slot to represent one.
+            pub fn from_arrow(isolate: &isolate::Isolate, array: &PrimitiveArray<$elem>) -> error::Result<value::$typ> {
+                if array.validity().map_or(false, |bitmap| bitmap.unset_bits() > 0) {
+                    return Err(error::ErrorKind::Arrow(
+                        "arrow array has null values, which a JS typed array cannot represent".to_string()
+                    ).into());
+                }
+
+                let elements: Vec<$elem> = array.values().as_slice().to_vec();
+                let len = elements.len();
+                let byte_len = len * mem::size_of::<$elem>();
+                let bytes = unsafe {
+                    let mut elements = elements;
+                    let ptr = elements.as_mut_ptr() as *mut u8;
+                    let cap = elements.capacity() * mem::size_of::<$elem>();
+                    mem::forget(elements);
+                    Vec::from_raw_parts(ptr, byte_len, cap)
+                };
+
+                let buffer = value::ArrayBuffer::new_from_bytes(isolate, bytes);
+                Ok(value::$typ::new(isolate, &buffer, 0, len))
+            }
+        }
+    }
+}
+
+// `Uint8ClampedArray` is deliberately not covered here: its storage is plain `u8`, identical to
+// `Uint8Array`, but its write semantics (clamping rather than wrapping) have no arrow2
+// counterpart, so treating it as an interchangeable `NativeType::u8` column would be misleading.
+
+arrow_accessors!(Uint8Array, u8);
+arrow_accessors!(Int8Array, i8);
+arrow_accessors!(Uint16Array, u16);
+arrow_accessors!(Int16Array, i16);
+arrow_accessors!(Uint32Array, u32);
+arrow_accessors!(Int32Array, i32);
+arrow_accessors!(Float32Array, f32);
+arrow_accessors!(Float64Array, f64);
@@ -1,9 +1,19 @@
 //! Execution contexts and sandboxing.
 use v8_sys;
+use std::fmt;
+use std::os;
 use std::ptr;
+use error;
 use isolate;
+use isolate::GetIsolate;
 use handle;
 use value;
+use template;
+
+/// The embedder-data slot index `new_with_microtask_queue` stashes the context's dedicated
+/// `MicrotaskQueue` in, so `run_microtasks` can find it again without the context itself needing
+/// an extra field (which would break the size assumptions `handle::Local::new` relies on).
+const MICROTASK_QUEUE_EMBEDDER_DATA_INDEX: os::raw::c_int = 0;
 
 /// A sandboxed execution context with its own set of built-in objects and functions.
 #[derive(Debug)]
@@ -13,6 +23,72 @@ pub struct Context(v8_sys::Context);
 #[must_use]
 pub struct Scope<'c>(&'c mut Context);
 
+/// Controls when the jobs enqueued in a `MicrotaskQueue` are actually run.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MicrotasksPolicy {
+    /// Microtasks are run automatically when control returns to the message loop, or when
+    /// explicitly requested via `Scope::run_microtasks`.
+    Auto,
+    /// Microtasks are only run when explicitly requested via `Scope::run_microtasks`.  This is
+    /// the policy to use when several sandboxed contexts share one isolate and must not let their
+    /// promise jobs interleave implicitly.
+    Explicit,
+}
+
+impl MicrotasksPolicy {
+    pub fn as_raw(self) -> v8_sys::MicrotasksPolicy {
+        match self {
+            MicrotasksPolicy::Auto => v8_sys::MicrotasksPolicy::kAuto,
+            MicrotasksPolicy::Explicit => v8_sys::MicrotasksPolicy::kExplicit,
+        }
+    }
+
+    pub fn from_raw(raw: v8_sys::MicrotasksPolicy) -> MicrotasksPolicy {
+        match raw {
+            v8_sys::MicrotasksPolicy::kAuto => MicrotasksPolicy::Auto,
+            v8_sys::MicrotasksPolicy::kExplicit => MicrotasksPolicy::Explicit,
+        }
+    }
+}
+
+/// An independent queue of pending microtask (promise reaction) jobs.
+///
+/// Normally all contexts on an isolate share one implicit queue.  Giving a `Context` its own
+/// `MicrotaskQueue` instead keeps its promise jobs from interleaving with those of unrelated
+/// sandboxes running on the same isolate.
+pub struct MicrotaskQueue(ptr::Unique<v8_sys::MicrotaskQueue>);
+
+impl MicrotaskQueue {
+    /// Creates a new microtask queue with the given draining policy.
+    pub fn new(isolate: &isolate::Isolate, policy: MicrotasksPolicy) -> MicrotaskQueue {
+        let raw = unsafe {
+            ptr::Unique::new(v8_sys::MicrotaskQueue::New(isolate.as_ptr(), policy.as_raw()))
+        }.expect("could not create MicrotaskQueue");
+
+        MicrotaskQueue(raw)
+    }
+
+    /// Returns the underlying raw pointer behind this queue.
+    pub fn as_ptr(&self) -> *mut v8_sys::MicrotaskQueue {
+        self.0.as_ptr()
+    }
+
+    /// Synchronously runs every microtask currently enqueued on this queue, draining it.
+    ///
+    /// Unlike `Isolate::perform_microtask_checkpoint`, this only drains jobs enqueued against
+    /// this specific queue, leaving any other context sharing the isolate (with its own queue, or
+    /// the isolate's default one) untouched.
+    pub fn perform_checkpoint(&self, isolate: &isolate::Isolate) {
+        unsafe { self.0.as_ptr().as_mut().unwrap().PerformCheckpoint(isolate.as_ptr()) }
+    }
+}
+
+impl fmt::Debug for MicrotaskQueue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MicrotaskQueue({:?})", unsafe { self.0.as_ref() })
+    }
+}
+
 impl Context {
     /// Creates a new context and returns a handle to the newly allocated context.
     pub fn new<'i, 's>(
@@ -33,6 +109,65 @@ impl Context {
         }
     }
 
+    /// Creates a new context whose global object is constructed from `template`, and returns a
+    /// handle to the newly allocated context.
+    ///
+    /// This allows an embedder to pre-install accessors, interceptors and functions on the global
+    /// object before any script runs in the context, which `global()` cannot do: by the time
+    /// `global()` is reachable, the context (and its global object's prototype chain) already
+    /// exists.
+    pub fn new_from_template<'i, 's>(
+        scope: &'s handle::Scope,
+        isolate: &'i isolate::Isolate,
+        template: handle::Local<'i, 's, template::ObjectTemplate>,
+    ) -> handle::Local<'i, 's, Context> {
+        unsafe {
+            handle::Local::new(v8_sys::Context::New(
+                isolate.as_ptr(),
+                ptr::null_mut(),
+                handle::MaybeLocal::from(template).into_raw(),
+                handle::MaybeLocal::empty().into_raw(),
+                v8_sys::DeserializeInternalFieldsCallback {
+                    callback: None,
+                    data: ptr::null_mut(),
+                },
+            ))
+        }
+    }
+
+    /// Creates a new context that drains its promise jobs from `microtask_queue` instead of the
+    /// isolate's default queue, and returns a handle to the newly allocated context.
+    ///
+    /// This is the building block for correctly isolating async execution between multiple
+    /// sandboxed contexts running on one isolate: as long as each gets its own queue, their
+    /// microtask jobs cannot interleave.
+    pub fn new_with_microtask_queue<'i, 's>(
+        scope: &'s handle::Scope,
+        isolate: &'i isolate::Isolate,
+        microtask_queue: &MicrotaskQueue,
+    ) -> handle::Local<'i, 's, Context> {
+        unsafe {
+            let mut context: handle::Local<Context> = handle::Local::new(v8_sys::Context::New_WithMicrotaskQueue(
+                isolate.as_ptr(),
+                ptr::null_mut(),
+                handle::MaybeLocal::empty().into_raw(),
+                handle::MaybeLocal::empty().into_raw(),
+                v8_sys::DeserializeInternalFieldsCallback {
+                    callback: None,
+                    data: ptr::null_mut(),
+                },
+                microtask_queue.as_ptr(),
+            ));
+            // Stash `microtask_queue` so `run_microtasks` can find and drain this specific queue
+            // instead of the isolate-wide one.
+            (*context).0.SetAlignedPointerInEmbedderData(
+                MICROTASK_QUEUE_EMBEDDER_DATA_INDEX,
+                microtask_queue.as_ptr() as *mut os::raw::c_void,
+            );
+            context
+        }
+    }
+
     /// Binds the context to the current scope.
     ///
     /// Within this scope, functionality that relies on implicit contexts will work.
@@ -43,6 +178,40 @@ impl Context {
         Scope(self)
     }
 
+    /// Associates `token` with this context for the purposes of the same-origin policy: another
+    /// context may only reach into this one's globals/objects directly if its own token is
+    /// `===`-identical to this one (or both sides are still on their default token).
+    ///
+    /// Anything outside that is only reachable through an `access_check_callback` registered on
+    /// the relevant `ObjectTemplate`, the same way cross-origin `window` access works in a
+    /// browser.
+    pub fn set_security_token(&mut self, token: handle::Local<value::Value>) {
+        unsafe { self.0.SetSecurityToken(token.into_raw()) }
+    }
+
+    /// The security token last set via `set_security_token`, or this context's default token if
+    /// none has been set.
+    pub fn get_security_token(&self) -> handle::Local<value::Value> {
+        unsafe { handle::Local::new(self.0.GetSecurityToken()) }
+    }
+
+    /// Restores this context's default security token, undoing any prior `set_security_token`.
+    ///
+    /// Under the default token, this context is only directly accessible from other contexts that
+    /// are themselves still on their default token.
+    pub fn use_default_security_token(&mut self) {
+        unsafe { self.0.UseDefaultSecurityToken() }
+    }
+
+    /// Opens a `TryCatch` scope that intercepts any JS exception thrown by code run beneath it,
+    /// instead of letting it surface as the `error::ErrorKind::Javascript` that `util::invoke`'s
+    /// own implicit per-call catch would produce. Useful when a caller needs the raw thrown value
+    /// back (e.g. to check its prototype, or reject another promise with it) rather than just a
+    /// formatted message.
+    pub fn try_catch(&self) -> error::TryCatch {
+        error::TryCatch::new(&self.get_isolate())
+    }
+
     /// Returns the global proxy object.
     ///
     /// Global proxy object is a thin wrapper whose prototype points to actual context's global
@@ -57,6 +226,45 @@ impl Context {
             handle::Local::new(self.0.Global())
         }
     }
+
+    /// Returns the `MicrotaskQueue` this context was created with via `new_with_microtask_queue`,
+    /// or `None` if it shares the isolate's default queue.
+    fn microtask_queue_ptr(&self) -> Option<*mut v8_sys::MicrotaskQueue> {
+        let ptr = unsafe {
+            self.0.GetAlignedPointerFromEmbedderData(MICROTASK_QUEUE_EMBEDDER_DATA_INDEX)
+        } as *mut v8_sys::MicrotaskQueue;
+        if ptr.is_null() { None } else { Some(ptr) }
+    }
+
+    /// Synchronously runs every microtask currently enqueued for this context, draining the
+    /// queue.
+    ///
+    /// This is the `Context`-level counterpart to `Scope::run_microtasks`, usable without an
+    /// active `Scope`. If this context was created via `new_with_microtask_queue`, only its own
+    /// dedicated queue is drained; otherwise this drains the isolate's default queue, the same as
+    /// `Isolate::perform_microtask_checkpoint`. This is what makes a `Promise` settle: resolving
+    /// or rejecting a `PromiseResolver` only enqueues the promise's reactions, it does not run
+    /// them.
+    pub fn run_microtasks(&self) {
+        match self.microtask_queue_ptr() {
+            Some(queue) => unsafe {
+                queue.as_mut().unwrap().PerformCheckpoint(self.get_isolate().as_ptr())
+            },
+            None => self.get_isolate().perform_microtask_checkpoint(),
+        }
+    }
+}
+
+impl GetIsolate for Context {
+    fn get_isolate(&self) -> isolate::Isolate {
+        unsafe { isolate::Isolate::from_ptr(self.0.GetIsolate()) }
+    }
+}
+
+impl<'c> GetIsolate for Scope<'c> {
+    fn get_isolate(&self) -> isolate::Isolate {
+        self.0.get_isolate()
+    }
 }
 
 impl<'c> Scope<'c> {
@@ -67,6 +275,17 @@ impl<'c> Scope<'c> {
     pub fn context_mut(&mut self) -> &mut Context {
         &mut self.0
     }
+
+    /// Synchronously runs every microtask currently enqueued for this context, draining the queue.
+    ///
+    /// Under `MicrotasksPolicy::Explicit`, this is the only way pending promise reactions get a
+    /// chance to run; under `MicrotasksPolicy::Auto`, this lets the embedder force a drain ahead of
+    /// the implicit points where V8 would otherwise do it. If the context was created via
+    /// `Context::new_with_microtask_queue`, this drains only that dedicated queue, leaving other
+    /// sandboxed contexts sharing the isolate untouched; see `Context::run_microtasks`.
+    pub fn run_microtasks(&mut self) {
+        self.0.run_microtasks();
+    }
 }
 
 impl<'c> Drop for Scope<'c> {
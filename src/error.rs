@@ -1,6 +1,7 @@
 //! Error types and utilities.
 
 use std::fmt;
+use std::os;
 use std::ptr;
 use v8_sys;
 use context;
@@ -11,9 +12,85 @@ use value;
 
 error_chain! {
     errors {
-        Javascript(message: String, stack_trace: CapturedStackTrace) {
+        Javascript(exception: value::Value, name: Option<String>, message: String, stack_trace: CapturedStackTrace, source: Option<CapturedSourceContext>) {
             description("Javascript exception")
-            display("Javascript exception: {}\n{}", message, stack_trace)
+            display("Javascript exception: {}\n{}{}",
+                    message,
+                    source.as_ref().map(|s| s.to_string()).unwrap_or_default(),
+                    stack_trace)
+        }
+        Terminated {
+            description("script execution was terminated")
+            display("script execution was terminated before it could complete")
+        }
+        OutOfMemory(limit_bytes: usize) {
+            description("isolate exceeded its configured heap limit")
+            display("isolate exceeded its configured heap limit of {} bytes and was terminated",
+                    limit_bytes)
+        }
+        PromiseRejected(reason: String) {
+            description("a JavaScript promise was rejected")
+            display("a JavaScript promise was rejected with: {}", reason)
+        }
+        StringTooLong(len: usize) {
+            description("string data is too long to back a v8::String")
+            display("string data is {} elements long, which exceeds v8::String's maximum length",
+                    len)
+        }
+        DataCloneError(kind: &'static str) {
+            description("a value could not be structured-cloned")
+            display("a {} cannot be structured-cloned", kind)
+        }
+        TruncatedCloneData {
+            description("structured-clone data ended unexpectedly")
+            display("structured-clone data ended before a complete value could be read")
+        }
+        #[cfg(feature = "serde")]
+        Serde(message: String) {
+            description("serde (de)serialization error")
+            display("serde (de)serialization error: {}", message)
+        }
+        #[cfg(feature = "arrow2")]
+        Arrow(message: String) {
+            description("arrow2 conversion error")
+            display("arrow2 conversion error: {}", message)
+        }
+    }
+}
+
+/// The source line an exception was thrown from, together with the column span to underline,
+/// captured so it can be rendered without the isolate that produced it still being alive.
+#[derive(Clone, Debug)]
+pub struct CapturedSourceContext {
+    pub script_resource_name: Option<String>,
+    pub line_number: u32,
+    pub source_line: String,
+    pub start_column: u32,
+    pub end_column: u32,
+}
+
+/// Where a `Message` places the severity of whatever it describes, mirroring V8's
+/// `Isolate::MessageErrorLevel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorLevel {
+    Log,
+    Debug,
+    Info,
+    Error,
+    Warning,
+    All,
+}
+
+impl ErrorLevel {
+    fn from_raw(raw: os::raw::c_int) -> ErrorLevel {
+        match raw {
+            1 => ErrorLevel::Log,
+            2 => ErrorLevel::Debug,
+            4 => ErrorLevel::Info,
+            8 => ErrorLevel::Error,
+            16 => ErrorLevel::Warning,
+            31 => ErrorLevel::All,
+            l => panic!("unknown v8::Isolate::MessageErrorLevel {}", l),
         }
     }
 }
@@ -29,15 +106,133 @@ pub struct CapturedStackTrace {
 pub struct CapturedStackFrame {
     pub line: u32,
     pub column: u32,
+    pub script_id: i32,
     pub script_name: Option<String>,
     pub function_name: Option<String>,
     pub is_eval: bool,
     pub is_constructor: bool,
 }
 
+/// A thrown JavaScript value paired with the diagnostics `util::invoke`'s implicit per-call catch
+/// would otherwise have already flattened into a plain `message`/`stack_trace`, as returned by
+/// `TryCatch::to_value_error`.
+///
+/// This is what a call site reaches for when it needs the actual exception object back (e.g. to
+/// check its prototype, or to pass it on to `PromiseResolver::reject`), rather than
+/// `ErrorKind::Javascript`'s already-formatted strings.
+#[derive(Clone, Debug)]
+pub struct ValueError {
+    pub value: value::Value,
+    pub message: String,
+    pub stack_trace: CapturedStackTrace,
+    pub source: Option<CapturedSourceContext>,
+}
+
+/// An RAII guard over `v8::TryCatch`, obtained from `context::Context::try_catch`.
+///
+/// Exceptions thrown by code run while this is alive are intercepted here instead of propagating
+/// to an enclosing `TryCatch` (or, absent one, becoming the `ErrorKind::Javascript` that
+/// `util::invoke`'s own implicit catch would otherwise turn them into).
+pub struct TryCatch {
+    isolate: isolate::Isolate,
+    raw: ptr::Unique<v8_sys::TryCatch>,
+}
+
+impl TryCatch {
+    /// Opens a try/catch scope over `isolate`.
+    pub fn new(isolate: &isolate::Isolate) -> TryCatch {
+        let raw = unsafe {
+            ptr::Unique::new(v8_sys::TryCatch::New(isolate.as_ptr()))
+        }.expect("could not create TryCatch");
+
+        TryCatch {
+            isolate: isolate.clone(),
+            raw: raw,
+        }
+    }
+
+    /// Whether an exception was thrown (and caught here) since this scope was opened.
+    pub fn has_caught(&self) -> bool {
+        unsafe { self.raw.as_ref().HasCaught() }
+    }
+
+    /// The exception that was caught, if any.
+    pub fn exception(&self) -> Option<value::Value> {
+        if !self.has_caught() {
+            return None;
+        }
+        Some(unsafe { value::Value::from_raw(&self.isolate, self.raw.as_ref().Exception()) })
+    }
+
+    /// The message describing the caught exception (source location, stack trace, ...), if any.
+    pub fn message(&self) -> Option<Message> {
+        if !self.has_caught() {
+            return None;
+        }
+        let raw = unsafe { self.raw.as_ref().Message() };
+        if raw.is_null() {
+            None
+        } else {
+            Some(unsafe { Message::from_raw(&self.isolate, raw) })
+        }
+    }
+
+    /// The caught exception's stack trace, if the isolate was configured to capture one.
+    pub fn stack_trace(&self, context: &context::Context) -> Option<value::Value> {
+        if !self.has_caught() {
+            return None;
+        }
+        let raw = unsafe { self.raw.as_ref().StackTrace(context.as_raw()) };
+        if raw.is_null() {
+            None
+        } else {
+            Some(unsafe { value::Value::from_raw(&self.isolate, raw) })
+        }
+    }
+
+    /// Re-throws the caught exception into the enclosing scope (another `TryCatch`, or the
+    /// embedder if there isn't one) instead of letting it end here.
+    pub fn rethrow(&mut self) {
+        unsafe { self.raw.as_mut().ReThrow() };
+    }
+
+    /// Packages the caught exception, if any, as a `ValueError`.
+    pub fn to_value_error(&self, context: &context::Context) -> Option<ValueError> {
+        let exception = match self.exception() {
+            Some(exception) => exception,
+            None => return None,
+        };
+        let message = self.message();
+        let message_str = message.as_ref().map(|m| m.get().value()).unwrap_or_default();
+        let stack_trace = message.as_ref()
+            .map(|m| m.get_stack_trace().to_captured())
+            .unwrap_or_else(|| CapturedStackTrace { frames: Vec::new() });
+        let source = message.as_ref().and_then(|m| m.to_captured_source_context(context));
+
+        Some(ValueError {
+            value: exception,
+            message: message_str,
+            stack_trace: stack_trace,
+            source: source,
+        })
+    }
+}
+
+impl fmt::Debug for TryCatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TryCatch({:?})", unsafe { self.raw.as_ref() })
+    }
+}
+
+impl Drop for TryCatch {
+    fn drop(&mut self) {
+        unsafe { self.raw.as_mut().Delete() };
+    }
+}
+
 /// An error message.
 #[derive(Debug)]
-pub struct Message(v8_sys::Message);
+pub struct Message(isolate::Isolate, v8_sys::Message);
 
 /// A stack trace, that is bound to an isolate.
 #[derive(Debug)]
@@ -52,16 +247,98 @@ impl Message {
 
     /// The error message string.
     pub fn get(&self) -> handle::Local<value::String> {
-        unsafe { handle::Local::new(self.0.Get()) }
+        unsafe { handle::Local::new(self.1.Get()) }
     }
 
     /// The stack trace to the point where the error was generated.
     pub fn get_stack_trace(&self) -> handle::Local<StackTrace> {
-        unsafe { handle::Local::new(self.0.GetStackTrace()) }
+        unsafe { handle::Local::new(self.1.GetStackTrace()) }
+    }
+
+    /// The line of source code that the offending expression, identified by `get_start_column`
+    /// and `get_end_column`, appears on.
+    pub fn get_source_line(&self, context: &context::Context) -> Result<String> {
+        let raw = unsafe {
+            try!(util::invoke_ctx(&self.0, context, |c| {
+                v8_sys::v8_Message_GetSourceLine(c, self.1, context.as_raw())
+            }))
+        };
+        Ok(unsafe { handle::Local::<value::String>::new(raw) }.value())
+    }
+
+    /// The name of the resource (e.g. file name) that the script generating this message was
+    /// compiled from, or `None` if it wasn't compiled with one.
+    pub fn get_script_resource_name(&self) -> Option<handle::Local<value::Value>> {
+        let raw = unsafe { self.1.GetScriptResourceName() };
+        let value = unsafe { handle::Local::<value::Value>::new(raw) };
+        if value.is_undefined() { None } else { Some(value) }
+    }
+
+    /// The 1-based line number of the offending expression.
+    pub fn get_line_number(&self, context: &context::Context) -> Result<u32> {
+        Ok(unsafe {
+            try!(util::invoke_ctx(&self.0, context, |c| {
+                v8_sys::v8_Message_GetLineNumber(c, self.1, context.as_raw())
+            }))
+        } as u32)
+    }
+
+    /// The index within the resource of the first character of the offending expression.
+    pub fn get_start_position(&self) -> i32 {
+        unsafe { self.1.GetStartPosition() }
+    }
+
+    /// The index within the resource of the last character of the offending expression.
+    pub fn get_end_position(&self) -> i32 {
+        unsafe { self.1.GetEndPosition() }
     }
 
-    pub unsafe fn from_raw(raw: v8_sys::Message) -> Message {
-        Message(raw)
+    /// The column on `get_line_number`'s line at which the offending expression starts.
+    pub fn get_start_column(&self, context: &context::Context) -> Result<u32> {
+        Ok(unsafe {
+            try!(util::invoke_ctx(&self.0, context, |c| {
+                v8_sys::v8_Message_GetStartColumn(c, self.1, context.as_raw())
+            }))
+        } as u32)
+    }
+
+    /// The column on `get_line_number`'s line at which the offending expression ends.
+    pub fn get_end_column(&self, context: &context::Context) -> Result<u32> {
+        Ok(unsafe {
+            try!(util::invoke_ctx(&self.0, context, |c| {
+                v8_sys::v8_Message_GetEndColumn(c, self.1, context.as_raw())
+            }))
+        } as u32)
+    }
+
+    /// Whether the resource this message's script was compiled from is marked as shared
+    /// cross-origin (e.g. served with CORS), making it safe to expose to error handlers on other
+    /// origins.
+    pub fn is_shared_cross_origin(&self) -> bool {
+        unsafe { self.1.IsSharedCrossOrigin() }
+    }
+
+    /// How severe the isolate considers whatever this message describes.
+    pub fn error_level(&self) -> ErrorLevel {
+        ErrorLevel::from_raw(unsafe { self.1.ErrorLevel() })
+    }
+
+    /// Captures this message's source line and column span, if available, so it can be rendered
+    /// without the isolate that produced it still being alive.
+    pub fn to_captured_source_context(&self, context: &context::Context) -> Option<CapturedSourceContext> {
+        self.get_source_line(context).ok().map(|source_line| {
+            CapturedSourceContext {
+                script_resource_name: self.get_script_resource_name().map(|n| n.into_string().unwrap().value()),
+                line_number: self.get_line_number(context).unwrap_or(0),
+                source_line: source_line,
+                start_column: self.get_start_column(context).unwrap_or(0),
+                end_column: self.get_end_column(context).unwrap_or(0),
+            }
+        })
+    }
+
+    pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8_sys::Message) -> Message {
+        Message(isolate.clone(), raw)
     }
 }
 
@@ -103,6 +380,12 @@ impl StackFrame {
         unsafe { self.0.GetColumn() as u32 }
     }
 
+    /// The id of the script this stack frame was pushed in, as set via
+    /// `script::ScriptOrigin::script_id` at compile time (or auto-assigned by V8 if not).
+    pub fn get_script_id(&self) -> i32 {
+        unsafe { self.0.GetScriptId() }
+    }
+
     /// The script file name in which this stack frame was pushed.
     pub fn get_script_name(&self) -> Option<handle::Local<value::String>> {
         unsafe {
@@ -139,6 +422,7 @@ impl StackFrame {
         CapturedStackFrame {
             line: self.get_line_number(),
             column: self.get_column(),
+            script_id: self.get_script_id(),
             script_name: self.get_script_name().map(|ref s| s.value()),
             function_name: if function_name.is_empty() {
                 None
@@ -160,6 +444,28 @@ impl fmt::Display for CapturedStackTrace {
     }
 }
 
+impl fmt::Display for CapturedSourceContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f,
+                 "{}:{}:{}",
+                 self.script_resource_name.as_ref().map(|n| n.as_str()).unwrap_or("<anon>"),
+                 self.line_number,
+                 self.start_column)?;
+        writeln!(f, "   {}", self.source_line)?;
+
+        let width = if self.end_column > self.start_column {
+            (self.end_column - self.start_column) as usize
+        } else {
+            1
+        };
+        let indent: String = ::std::iter::repeat(' ').take(3 + self.start_column as usize).collect();
+        let carets: String = ::std::iter::repeat('^').take(width).collect();
+        writeln!(f, "{}{}", indent, carets)?;
+
+        Ok(())
+    }
+}
+
 impl fmt::Display for CapturedStackFrame {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "    at ")?;
@@ -0,0 +1,53 @@
+//! Optional integration with Rust's `Future` trait.
+//!
+//! Bridges a `value::Promise` into a `std::future::Future`, following rquickjs's `Async`
+//! function-wrapper idea: V8 has no way to push a wakeup to an external executor, so each poll
+//! simply drains the context's microtask queue (running any reactions that are now ready) and
+//! then re-checks the promise's state, re-arming its own waker until the promise settles.
+
+use std::future;
+use std::pin;
+use std::task;
+
+use context;
+use error;
+use value;
+
+/// Adapts a `value::Promise` into a `Future` that resolves once the promise settles, so JS async
+/// code can be consumed with `async fn`/`.await` instead of polling `Promise::state` by hand.
+pub struct PromiseFuture<'c> {
+    context: &'c context::Context,
+    promise: value::Promise,
+}
+
+impl<'c> PromiseFuture<'c> {
+    /// Wraps `promise`, which must belong to `context`, so it can be `await`ed from Rust.
+    pub fn new(context: &'c context::Context, promise: value::Promise) -> PromiseFuture<'c> {
+        PromiseFuture {
+            context: context,
+            promise: promise,
+        }
+    }
+}
+
+impl<'c> future::Future for PromiseFuture<'c> {
+    type Output = error::Result<value::Value>;
+
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut task::Context) -> task::Poll<Self::Output> {
+        self.context.run_microtasks();
+
+        match self.promise.state() {
+            value::PromiseState::Pending => {
+                // Nothing tells us when the next microtask checkpoint might settle this promise,
+                // so ask to be polled again rather than going to sleep forever.
+                cx.waker().wake_by_ref();
+                task::Poll::Pending
+            }
+            value::PromiseState::Fulfilled(value) => task::Poll::Ready(Ok(value)),
+            value::PromiseState::Rejected(reason) => {
+                let message = reason.to_string(self.context).value();
+                task::Poll::Ready(Err(error::ErrorKind::PromiseRejected(message).into()))
+            }
+        }
+    }
+}
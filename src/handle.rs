@@ -5,10 +5,22 @@ use std::mem;
 use std::ops;
 use std::ptr;
 use isolate;
+use isolate::GetIsolate;
 
 #[derive(Debug)]
 pub struct Scope<'i>(v8_sys::HandleScope, marker::PhantomData<&'i isolate::Isolate>);
 
+/// A handle scope that allows a single value created within it to escape into the enclosing
+/// (parent) scope.
+///
+/// This is the standard pattern for factory functions: build a value using temporary handles in
+/// an inner scope, then `escape` the one value that should survive into the caller's scope.  The
+/// lifetime `'p` identifies the parent scope that the escaped value will belong to.
+#[derive(Debug)]
+pub struct EscapableScope<'i, 'p>(v8_sys::EscapableHandleScope, marker::PhantomData<&'p Scope<'i>>)
+    where
+        'i: 'p;
+
 #[derive(Debug, Copy, Clone)]
 pub struct Local<'i, 's, A>(v8_sys::Local<A>, marker::PhantomData<&'s Scope<'i>>)
     where
@@ -25,6 +37,20 @@ pub struct Eternal<'i, A>(v8_sys::Eternal<A>, marker::PhantomData<&'i isolate::I
 #[derive(Debug, Copy, Clone)]
 pub struct Persistent<'i, A>(v8_sys::Persistent<A>, marker::PhantomData<&'i isolate::Isolate>);
 
+/// The data passed to a weak callback registered via `Persistent::set_weak`.
+///
+/// This mirrors V8's `WeakCallbackInfo<T>`: it gives the callback access to the isolate that owns
+/// the handle and to the `T` that was supplied when the handle was made weak.
+#[derive(Debug)]
+pub struct WeakCallbackInfo<'i, T>(v8_sys::WeakCallbackInfo<WeakBox<T>>, marker::PhantomData<&'i isolate::Isolate>);
+
+/// The combination of user data and callback stashed behind a weak `Persistent`'s parameter
+/// pointer, since V8 only gives us a single `void*` slot to smuggle both through.
+struct WeakBox<T> {
+    value: T,
+    callback: fn(WeakCallbackInfo<T>),
+}
+
 impl<'i, 's, A> Local<'i, 's, A> {
     pub unsafe fn new<B>(value: v8_sys::Local<B>) -> Local<'i, 's, A> {
         assert_eq!(mem::size_of::<B>(), mem::size_of::<A>());
@@ -36,6 +62,118 @@ impl<'i, 's, A> Local<'i, 's, A> {
     }
 }
 
+impl<'i, A> Persistent<'i, A> {
+    /// Creates a new persistent handle from a local handle.
+    ///
+    /// Unlike a `Local`, a `Persistent` is not tied to a handle scope and must be explicitly
+    /// `reset` (or dropped via the isolate's own finalization) to release the underlying value.
+    pub unsafe fn new<'s>(isolate: &'i isolate::Isolate, value: Local<'i, 's, A>) -> Persistent<'i, A> {
+        let mut raw = mem::zeroed();
+        v8_sys::Persistent::New(isolate.as_ptr(), &mut raw, value.into_raw());
+        Persistent(raw, marker::PhantomData)
+    }
+
+    /// Whether this handle has been turned into a weak handle via `set_weak`.
+    pub fn is_weak(&self) -> bool {
+        unsafe { self.0.IsWeak() }
+    }
+
+    /// Clears this handle, releasing the strong (or weak) reference that it holds.
+    pub fn reset(&mut self) {
+        unsafe { self.0.Reset() }
+    }
+
+    /// Turns this handle into a weak handle, allowing the garbage collector to reclaim the
+    /// underlying value.
+    ///
+    /// `data` is stashed alongside `callback` and handed back to it (via `WeakCallbackInfo`) once
+    /// V8 decides to collect the value.  This is V8's "first pass" weak callback: the callback may
+    /// call `WeakCallbackInfo::set_second_pass_callback` to defer further cleanup (for example,
+    /// dropping Rust state) until after V8 has finished finalizing every weak handle in this GC
+    /// cycle, since it isn't safe to allocate or otherwise touch the heap during the first pass.
+    ///
+    /// Whichever callback turns out to be the last one invoked for this handle (the first-pass
+    /// callback if it never defers, otherwise the second-pass callback) must call
+    /// `WeakCallbackInfo::into_data` to reclaim and drop `data`; otherwise it leaks.
+    pub fn set_weak<T>(&mut self, data: Box<T>, callback: fn(WeakCallbackInfo<T>)) {
+        let boxed = Box::new(WeakBox {
+            value: *data,
+            callback: callback,
+        });
+        unsafe {
+            self.0.SetWeak(
+                Box::into_raw(boxed),
+                trampoline::<T>,
+                v8_sys::WeakCallbackType::kParameter,
+            );
+        }
+    }
+}
+
+extern "C" fn trampoline<T>(info: &v8_sys::WeakCallbackInfo<WeakBox<T>>) {
+    let info = WeakCallbackInfo::<T>(*info, marker::PhantomData);
+    let callback = unsafe { (*info.0.GetParameter()).callback };
+    callback(info);
+}
+
+impl<'i, T> WeakCallbackInfo<'i, T> {
+    /// The isolate that the now-weak handle belonged to.
+    pub fn isolate(&self) -> isolate::Isolate {
+        unsafe { isolate::Isolate::from_ptr(self.0.GetIsolate()) }
+    }
+
+    /// The data that was passed to `Persistent::set_weak`.
+    pub fn data(&self) -> &T {
+        unsafe { &(*self.0.GetParameter()).value }
+    }
+
+    /// Takes ownership of the data that was passed to `Persistent::set_weak`, freeing the
+    /// `WeakBox` that V8 has been carrying around in its `void*` parameter slot.
+    ///
+    /// Call this from whichever callback is actually final for this handle: the first-pass
+    /// callback if it never calls `set_second_pass_callback`, or the second-pass callback
+    /// otherwise. Calling it more than once (or not at all) is a bug: the former double-frees,
+    /// the latter leaks.
+    pub fn into_data(self) -> T {
+        unsafe { Box::from_raw(self.0.GetParameter()).value }
+    }
+
+    /// Defers the rest of the cleanup (for example, dropping `data`) to a second callback that
+    /// runs once every weak callback in this GC cycle has completed its first pass.  It is only
+    /// safe to allocate Rust values (or otherwise touch the V8 heap) in the second pass.
+    pub fn set_second_pass_callback(&self, callback: fn(WeakCallbackInfo<T>)) {
+        unsafe {
+            self.0.SetSecondPassCallback(trampoline::<T>);
+        }
+        // Stash the second-pass callback in the same box so that `trampoline` picks it up again.
+        unsafe { (*self.0.GetParameter()).callback = callback };
+    }
+}
+
+impl<'i, 'p> EscapableScope<'i, 'p> {
+    /// Moves `value` into the reserved slot of the enclosing scope, returning a `Local` tied to
+    /// that parent scope's lifetime `'p` instead of this scope's lifetime.
+    ///
+    /// This is V8's `Escape` operation: it allocates one reserved slot in the parent handle scope
+    /// at construction time and copies the escaping value there, so the value survives this
+    /// scope's destruction. Because only one slot is reserved, calling this more than once per
+    /// scope is undefined behavior on the V8 side; the borrow checker helps by tying the result to
+    /// `'p`, but it cannot enforce the one-value invariant by itself.
+    pub unsafe fn escape<'s, A>(&mut self, value: Local<'i, 's, A>) -> Local<'i, 'p, A> {
+        Local::new(self.0.Escape(value.into_raw()))
+    }
+}
+
+impl<'i, 's, A> GetIsolate for Local<'i, 's, A> {
+    /// Recovers the isolate that this handle's value lives in.
+    ///
+    /// This relies on every `v8::Data` subclass reachable through a `Local` exposing its own
+    /// `GetIsolate()`, which is true of everything the bindings currently hand out as a `Local`.
+    fn get_isolate(&self) -> isolate::Isolate {
+        unsafe { isolate::Isolate::from_ptr((*self.0.val_).GetIsolate()) }
+    }
+}
+
 impl<'i, 's, A> convert::From<v8_sys::Local<A>> for Local<'i, 's, A> {
     fn from(other: v8_sys::Local<A>) -> Self {
         Local(other, marker::PhantomData)
@@ -71,3 +209,15 @@ impl<'i, 's, A> MaybeLocal<'i, 's, A> {
         self.0
     }
 }
+
+impl<'i, 's, A> convert::From<Local<'i, 's, A>> for MaybeLocal<'i, 's, A> {
+    fn from(other: Local<'i, 's, A>) -> Self {
+        MaybeLocal(
+            v8_sys::MaybeLocal {
+                val_: other.into_raw().val_,
+                _phantom_0: marker::PhantomData,
+            },
+            marker::PhantomData,
+        )
+    }
+}
@@ -24,20 +24,77 @@
 //! user wants to allow this to happen, an isolate should be constructed with
 //! `Isolate::builder().supports_idle_tasks(true).build()`.  The user should then regularly call
 //! `isolate.run_idle_tasks(deadline)` to run any pending idle tasks.
+//!
+//! # Microtasks
+//!
+//! By default, an isolate's implicit microtask queue is drained automatically whenever control
+//! returns to the message loop.  `Isolate::set_microtasks_policy` lets the embedder switch that
+//! queue to `context::MicrotasksPolicy::Explicit`, so that pending promise reactions only run when
+//! `isolate.perform_microtask_checkpoint()` is called.  Contexts with their own dedicated
+//! `context::MicrotaskQueue` are unaffected by this setting; see `context::Scope::run_microtasks`
+//! for draining those instead.
+//!
+//! # Driving an isolate from multiple threads
+//!
+//! An isolate can be entered by at most one thread at any given time.  By default this library
+//! does not enforce that, trusting the embedder to only ever touch an isolate from the thread that
+//! created it.  An isolate built with `Isolate::builder().supports_locking(true).build()` instead
+//! requires whichever thread wants to enter it (via `scope()`, or implicitly while running an
+//! enqueued or idle task) to first hold a `Locker` for it, acquired with `isolate.lock()`.
+//! `Isolate::send_handle()` packages such an isolate into a `SendIsolate` that can be moved to
+//! another thread and locked there.
+//!
+//! # Heap limits
+//!
+//! By default V8 picks heap resource constraints based on the system's available memory.
+//! `Isolate::builder().heap_limits(initial, maximum)` lets the embedder configure these explicitly
+//! instead, or `max_old_generation_size_in_bytes`/`max_young_generation_size_in_bytes` can be used
+//! to cap an individual generation, for example to bound how much memory a sandboxed, untrusted
+//! script may use. `isolate.add_near_heap_limit_callback(callback)` additionally registers a
+//! callback that gets one last chance to free memory or raise the limit before V8 gives up and
+//! crashes with an out-of-memory error.
+//!
+//! # Garbage collection
+//!
+//! `isolate.get_heap_statistics()` reports the isolate's current heap usage and limits, for
+//! embedders that want to do their own memory accounting or back-pressure around the manual task
+//! pump loop above. `isolate.add_gc_prologue_callback`/`add_gc_epilogue_callback` let the embedder
+//! observe every collection (optionally restricted to a `GCType` bitmask) as it starts or
+//! finishes, and `isolate.low_memory_notification()` lets the embedder forward an OS-level memory
+//! pressure signal on to V8. `isolate.request_garbage_collection_for_testing(gc_type)` forces a
+//! collection, but only does anything if V8 was built/run with the relevant flag to allow it.
+//!
+//! # Aborting runaway scripts
+//!
+//! `Script::run`/`Function::call` run on the calling thread for as long as the script lets them,
+//! so a malicious or buggy `while (true) {}` hangs that thread forever by default.
+//! `isolate.set_execution_timeout(duration)` arms a watchdog thread that cooperatively terminates
+//! whatever is running once `duration` elapses; `isolate.set_heap_limit(bytes)` does the same once
+//! a garbage collection observes heap usage has reached `bytes`. Either way, the aborted call
+//! returns `error::ErrorKind::Terminated` (or `error::ErrorKind::OutOfMemory` for the heap limit) instead
+//! of whatever it would otherwise have produced. `isolate.cancel()`, or a `CancelHandle` obtained
+//! from `isolate.cancel_handle()` and sent to another thread, terminates on demand the same way.
 
+use std::any;
 use std::cell;
 use std::collections;
 use std::fmt;
 use std::mem;
+use std::ops;
 use std::os;
 use std::ptr;
 use std::rc;
 use std::sync;
+use std::thread;
 use std::time;
 use v8_sys;
 use allocator;
+use context;
+use error;
+use num_cpus;
 use platform;
 use priority_queue;
+use snapshot;
 
 static INITIALIZE: sync::Once = sync::ONCE_INIT;
 
@@ -52,10 +109,18 @@ pub struct Isolate(ptr::Shared<v8_sys::Isolate>);
 /// A builder for isolates.  Can be converted into an isolate with the `build` method.
 pub struct Builder {
     supports_idle_tasks: bool,
+    supports_locking: bool,
+    heap_limits: Option<(usize, usize)>,
+    max_old_generation_size_in_bytes: Option<usize>,
+    max_young_generation_size_in_bytes: Option<usize>,
+    snapshot_blob: Option<snapshot::StartupData>,
 }
 
 #[must_use]
-pub struct Scope<'i>(&'i mut Isolate);
+pub struct Scope<'i> {
+    isolate: &'i mut Isolate,
+    _locker: Option<Locker>,
+}
 
 #[derive(Debug)]
 struct Data {
@@ -63,10 +128,57 @@ struct Data {
     _allocator: allocator::Allocator,
     task_queue: rc::Rc<cell::RefCell<priority_queue::PriorityQueue<platform::Task, time::Instant>>>,
     idle_task_queue: Option<rc::Rc<cell::RefCell<collections::VecDeque<platform::IdleTask>>>>,
+    worker_pool: WorkerPool,
+    supports_locking: bool,
+    execution_timeout: cell::Cell<Option<time::Duration>>,
+    termination: sync::Arc<sync::Mutex<Option<TerminationReason>>>,
+    externals: ExternalRegistry,
 }
 
 const DATA_PTR_SLOT: u32 = 0;
 
+/// Owns the boxed Rust values handed out through `value::External::new_typed`, so that they stay
+/// alive for exactly as long as the isolate they were registered against, and are dropped (rather
+/// than leaked) once that isolate is disposed.
+struct ExternalRegistry(cell::RefCell<Vec<*mut Box<any::Any>>>);
+
+impl ExternalRegistry {
+    fn new() -> ExternalRegistry {
+        ExternalRegistry(cell::RefCell::new(Vec::new()))
+    }
+
+    /// Takes ownership of `value`, returning a thin, stable pointer to it suitable for stashing
+    /// behind a `v8::External`. The pointee stays alive until this isolate is disposed.
+    fn insert(&self, value: Box<any::Any>) -> *mut Box<any::Any> {
+        let raw = Box::into_raw(Box::new(value));
+        self.0.borrow_mut().push(raw);
+        raw
+    }
+
+    /// Whether `ptr` was returned by a previous call to `insert` on this registry and hasn't been
+    /// dropped since. Used to reject `v8::External`s that weren't created by `new_typed` before
+    /// reinterpreting their payload as a `Box<dyn Any>`.
+    fn contains(&self, ptr: *mut Box<any::Any>) -> bool {
+        self.0.borrow().contains(&ptr)
+    }
+}
+
+impl fmt::Debug for ExternalRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ExternalRegistry({} entries)", self.0.borrow().len())
+    }
+}
+
+impl Drop for ExternalRegistry {
+    fn drop(&mut self) {
+        for raw in self.0.borrow_mut().drain(..) {
+            unsafe {
+                drop(Box::from_raw(raw));
+            }
+        }
+    }
+}
+
 impl Isolate {
     /// Creates a new isolate.
     pub fn new() -> Isolate {
@@ -75,7 +187,14 @@ impl Isolate {
 
     /// Creates a new isolate builder.
     pub fn builder() -> Builder {
-        Builder { supports_idle_tasks: false }
+        Builder {
+            supports_idle_tasks: false,
+            supports_locking: false,
+            heap_limits: None,
+            max_old_generation_size_in_bytes: None,
+            max_young_generation_size_in_bytes: None,
+            snapshot_blob: None,
+        }
     }
 
     /// Creates a data from a raw pointer.
@@ -93,9 +212,51 @@ impl Isolate {
         self.0.as_ptr()
     }
 
+    /// Binds the isolate to the current thread.
+    ///
+    /// If this isolate was built with `Builder::supports_locking(true)`, this also acquires a
+    /// `Locker` for the current thread first, and holds it for the lifetime of the returned
+    /// `Scope`, so that `Enter` can never race with another thread's `Enter`/`Exit`.
     pub fn scope(&mut self) -> Scope {
+        let locker = if self.supports_locking() {
+            Some(self.lock())
+        } else {
+            None
+        };
+
         unsafe { self.0.as_mut().Enter() };
-        Scope(self)
+        Scope {
+            isolate: self,
+            _locker: locker,
+        }
+    }
+
+    /// Acquires V8's lock for the current thread on this isolate, blocking if another thread
+    /// currently holds it.
+    ///
+    /// Required before entering an isolate that was built with `Builder::supports_locking(true)`
+    /// from any thread other than the one that created it.
+    pub fn lock(&self) -> Locker {
+        let raw = unsafe { ptr::Unique::new(v8_sys::Locker::New(self.as_ptr())) }
+            .expect("could not create Locker");
+        Locker(raw)
+    }
+
+    /// Whether this isolate was configured to require a `Locker` to be entered.
+    pub fn supports_locking(&self) -> bool {
+        self.data().supports_locking
+    }
+
+    /// Packages this isolate as a `SendIsolate` that can be moved to another thread and locked
+    /// there with `SendIsolate::lock`.
+    ///
+    /// Panics unless this isolate was built with `Builder::supports_locking(true)`.
+    pub fn send_handle(&self) -> SendIsolate {
+        assert!(
+            self.supports_locking(),
+            "isolate was not built with supports_locking(true)"
+        );
+        SendIsolate(self.as_ptr())
     }
 
     /*
@@ -129,6 +290,15 @@ impl Isolate {
             .unwrap_or(false)
         {
             let task = data.task_queue.borrow_mut().pop().unwrap().0;
+
+            // Hold the isolate's lock while running the task, mirroring gin's platform, so that a
+            // task posted from a background thread never runs concurrently with another thread
+            // that is inside the isolate.
+            let _locker = if data.supports_locking {
+                Some(self.lock())
+            } else {
+                None
+            };
             task.run();
             true
         } else {
@@ -163,6 +333,11 @@ impl Isolate {
             .map(|q| q.borrow_mut().pop_front())
             .unwrap_or(None)
         {
+            let _locker = if data.supports_locking {
+                Some(self.lock())
+            } else {
+                None
+            };
             idle_task.run(deadline);
             true
         } else {
@@ -201,6 +376,214 @@ impl Isolate {
         self.data().idle_task_queue.is_some()
     }
 
+    /// Sets the policy that governs when this isolate's implicit microtask queue is drained.
+    pub fn set_microtasks_policy(&self, policy: context::MicrotasksPolicy) {
+        unsafe { (*self.0.as_ptr()).SetMicrotasksPolicy(policy.as_raw()) };
+    }
+
+    /// Returns the policy currently governing this isolate's implicit microtask queue.
+    pub fn microtasks_policy(&self) -> context::MicrotasksPolicy {
+        unsafe { context::MicrotasksPolicy::from_raw((*self.0.as_ptr()).GetMicrotasksPolicy()) }
+    }
+
+    /// Synchronously runs every microtask currently enqueued on this isolate's implicit queue,
+    /// draining it.
+    ///
+    /// Under `context::MicrotasksPolicy::Explicit`, this is the only way pending promise reactions
+    /// on the implicit queue get a chance to run.
+    pub fn perform_microtask_checkpoint(&self) {
+        unsafe { (*self.0.as_ptr()).PerformMicrotaskCheckpoint() };
+    }
+
+    /// Schedules `task` to run on this isolate's worker-thread pool as soon as a worker is free.
+    pub fn call_on_worker_thread(&self, task: platform::Task) {
+        self.data().worker_pool.queue.push(task);
+    }
+
+    /// Schedules `task` to run on this isolate's worker-thread pool once `delay` has elapsed.
+    pub fn call_delayed_on_worker_thread(&self, delay: time::Duration, task: platform::Task) {
+        self.data().worker_pool.queue.push_delayed(time::Instant::now() + delay, task);
+    }
+
+    /// Registers a callback that V8 invokes just before it would otherwise give up and crash with
+    /// an out-of-memory error, giving the embedder a last chance to either free memory or raise the
+    /// heap limit.
+    ///
+    /// The callback is given the current and initial heap limits (in bytes) and must return the new
+    /// limit; returning the current limit unchanged tells V8 no more headroom could be made
+    /// available.
+    ///
+    /// Multiple callbacks may be registered, in which case V8 calls them in the reverse order they
+    /// were added until one of them raises the limit.
+    pub fn add_near_heap_limit_callback<F>(&self, callback: F)
+    where
+        F: Fn(usize, usize) -> usize + 'static,
+    {
+        let callback: Box<Box<NearHeapLimitCallback>> = Box::new(Box::new(callback));
+        unsafe {
+            (*self.0.as_ptr()).AddNearHeapLimitCallback(
+                Some(near_heap_limit_callback),
+                Box::into_raw(callback) as *mut os::raw::c_void,
+            );
+        }
+    }
+
+    /// Returns a snapshot of this isolate's current heap usage and limits.
+    pub fn get_heap_statistics(&self) -> HeapStatistics {
+        unsafe {
+            let mut raw: v8_sys::HeapStatistics = mem::zeroed();
+            (*self.0.as_ptr()).GetHeapStatistics(&mut raw);
+            HeapStatistics {
+                total_heap_size: raw.total_heap_size(),
+                total_heap_size_executable: raw.total_heap_size_executable(),
+                total_physical_size: raw.total_physical_size(),
+                total_available_size: raw.total_available_size(),
+                used_heap_size: raw.used_heap_size(),
+                heap_size_limit: raw.heap_size_limit(),
+                malloced_memory: raw.malloced_memory(),
+                external_memory: raw.external_memory(),
+                peak_malloced_memory: raw.peak_malloced_memory(),
+                number_of_native_contexts: raw.number_of_native_contexts(),
+                number_of_detached_contexts: raw.number_of_detached_contexts(),
+                does_zap_garbage: raw.does_zap_garbage(),
+            }
+        }
+    }
+
+    /// Registers a callback to run just before V8 starts a garbage collection matching
+    /// `gc_type_filter`.
+    ///
+    /// Multiple callbacks may be registered; V8 calls them in the order they were added.
+    pub fn add_gc_prologue_callback<F>(&self, gc_type_filter: GCType, callback: F)
+    where
+        F: Fn(&Isolate, GCType) + 'static,
+    {
+        let callback: Box<Box<GCCallback>> = Box::new(Box::new(callback));
+        unsafe {
+            (*self.0.as_ptr()).AddGCPrologueCallback(
+                Some(gc_callback_trampoline),
+                Box::into_raw(callback) as *mut os::raw::c_void,
+                gc_type_filter.as_raw(),
+            );
+        }
+    }
+
+    /// Registers a callback to run just after V8 finishes a garbage collection matching
+    /// `gc_type_filter`.
+    ///
+    /// Multiple callbacks may be registered; V8 calls them in the order they were added.
+    pub fn add_gc_epilogue_callback<F>(&self, gc_type_filter: GCType, callback: F)
+    where
+        F: Fn(&Isolate, GCType) + 'static,
+    {
+        let callback: Box<Box<GCCallback>> = Box::new(Box::new(callback));
+        unsafe {
+            (*self.0.as_ptr()).AddGCEpilogueCallback(
+                Some(gc_callback_trampoline),
+                Box::into_raw(callback) as *mut os::raw::c_void,
+                gc_type_filter.as_raw(),
+            );
+        }
+    }
+
+    /// Tells V8 that the embedder is under memory pressure, for example in response to an OS
+    /// low-memory signal, encouraging it to free as much memory as possible even at some
+    /// performance cost.
+    pub fn low_memory_notification(&self) {
+        unsafe { (*self.0.as_ptr()).LowMemoryNotification() };
+    }
+
+    /// Forces a `gc_type` garbage collection outside of V8's own scheduling.
+    ///
+    /// Only takes effect if V8 was initialized with a flag allowing it (for example
+    /// `--expose-gc`); intended for tests that want to assert on collection behavior, not for
+    /// production embedders.
+    pub fn request_garbage_collection_for_testing(&self, gc_type: GarbageCollectionType) {
+        unsafe {
+            (*self.0.as_ptr()).RequestGarbageCollectionForTesting(gc_type.as_raw());
+        }
+    }
+
+    /// Arms a watchdog that cooperatively terminates whatever `run_guarded`-wrapped call (i.e.
+    /// `Script::run`/`Function::call`) is in progress once `timeout` elapses, instead of letting a
+    /// malicious or buggy script hang the calling thread forever.
+    ///
+    /// Takes effect from the next guarded call onward; call again to change the timeout. The
+    /// watchdog only runs for the duration of a guarded call, so it never fires while the isolate
+    /// is idle between scripts.
+    pub fn set_execution_timeout(&self, timeout: time::Duration) {
+        self.data().execution_timeout.set(Some(timeout));
+    }
+
+    /// Caps this isolate's heap at approximately `bytes`, terminating execution the first time
+    /// usage is observed to reach it, instead of letting the isolate keep growing all the way to
+    /// its (much larger, system-memory-based) default `heap_size_limit` or eventually crash the
+    /// process with an out-of-memory error.
+    ///
+    /// This is implemented as a GC epilogue callback that compares `get_heap_statistics().
+    /// used_heap_size` against `bytes` after every collection, rather than V8's own
+    /// near-heap-limit signal (see `add_near_heap_limit_callback`): that signal only fires once
+    /// usage nears the isolate's *current* `heap_size_limit`, which for a small budget like a few
+    /// megabytes is nowhere close to where this callback needs to intervene. A single allocation
+    /// that overshoots `bytes` between two collections is still not caught until the next GC runs.
+    ///
+    /// A `run_guarded`-wrapped call aborted this way returns `error::ErrorKind::OutOfMemory`. This
+    /// only takes effect once a guarded call is in progress when a collection triggers the check;
+    /// call this before starting to run untrusted script.
+    pub fn set_heap_limit(&self, bytes: usize) {
+        let handle = self.cancel_handle();
+        self.add_gc_epilogue_callback(GCType::ALL, move |isolate, _gc_type| {
+            if isolate.get_heap_statistics().used_heap_size >= bytes {
+                handle.terminate(TerminationReason::OutOfMemory(bytes));
+            }
+        });
+    }
+
+    /// Terminates whatever `run_guarded`-wrapped call (i.e. `Script::run`/`Function::call`) is
+    /// currently executing on this isolate, if any. A no-op if nothing is currently executing.
+    ///
+    /// The call being aborted sees `error::ErrorKind::Terminated` instead of whatever it would
+    /// otherwise have returned. See `cancel_handle` to do this from another thread.
+    pub fn cancel(&self) {
+        self.cancel_handle().cancel();
+    }
+
+    /// Packages a handle that can `cancel` this isolate's currently-executing script from another
+    /// thread, unlike `Isolate` itself (which is tied to `Rc`-based task queues and is therefore
+    /// `!Send`), mirroring `send_handle`'s relationship to `SendIsolate` but without requiring
+    /// `Builder::supports_locking(true)`.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            isolate: self.as_ptr(),
+            termination: self.data().termination.clone(),
+        }
+    }
+
+    /// Runs `f`, arming this isolate's `set_execution_timeout` watchdog (if any) for the duration
+    /// of the call, and translating whatever `set_heap_limit`, `cancel`, or the watchdog itself
+    /// terminated execution for into the matching `error::ErrorKind`.
+    ///
+    /// Intended for `Script::run`/`Function::call`-style entry points that actually execute
+    /// JavaScript; wrapping every FFI call the way `util::invoke` does would arm (and disarm) a
+    /// watchdog thread far more often than a timeout is meant to bound.
+    pub fn run_guarded<F, T>(&self, f: F) -> error::Result<T>
+        where F: FnOnce() -> error::Result<T>
+    {
+        let _watchdog = self.data().execution_timeout.get().map(|timeout| {
+            ExecutionWatchdog::arm(self.cancel_handle(), timeout)
+        });
+
+        let result = f();
+
+        match self.data().termination.lock().unwrap().take() {
+            Some(TerminationReason::Guarded) => Err(error::ErrorKind::Terminated.into()),
+            Some(TerminationReason::OutOfMemory(limit_bytes)) => {
+                Err(error::ErrorKind::OutOfMemory(limit_bytes).into())
+            }
+            None => result,
+        }
+    }
+
     fn data_ptr(&self) -> *mut Data {
         unsafe { (*self.0.as_ptr()).GetData(DATA_PTR_SLOT) as *mut Data }
     }
@@ -212,6 +595,254 @@ impl Isolate {
     fn data_mut(&mut self) -> &mut Data {
         unsafe { self.data_ptr().as_mut().unwrap() }
     }
+
+    /// Takes ownership of `value`, returning a thin pointer to it that stays valid for the
+    /// lifetime of this isolate.
+    ///
+    /// Used by `value::External::new_typed` to anchor the boxed Rust value it stashes behind a
+    /// `v8::External`, so that it is dropped when this isolate is disposed instead of leaking.
+    pub fn register_external(&self, value: Box<any::Any>) -> *mut Box<any::Any> {
+        self.data().externals.insert(value)
+    }
+
+    /// Whether `ptr` is a pointer previously returned by `register_external` on this isolate.
+    ///
+    /// Used by `value::External::downcast` to check that an external was actually produced by
+    /// `new_typed` before reinterpreting its payload as a `Box<dyn Any>`.
+    pub(crate) fn owns_external(&self, ptr: *mut Box<any::Any>) -> bool {
+        self.data().externals.contains(ptr)
+    }
+}
+
+/// Callback type for `Isolate::add_near_heap_limit_callback`.
+pub type NearHeapLimitCallback = Fn(usize, usize) -> usize + 'static;
+
+/// A snapshot of an isolate's heap usage and limits, returned by `Isolate::get_heap_statistics`.
+///
+/// All sizes are in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapStatistics {
+    pub total_heap_size: usize,
+    pub total_heap_size_executable: usize,
+    pub total_physical_size: usize,
+    pub total_available_size: usize,
+    pub used_heap_size: usize,
+    pub heap_size_limit: usize,
+    pub malloced_memory: usize,
+    pub external_memory: usize,
+    pub peak_malloced_memory: usize,
+    pub number_of_native_contexts: usize,
+    pub number_of_detached_contexts: usize,
+    pub does_zap_garbage: bool,
+}
+
+/// A bitmask of garbage collection phases, matching V8's `GCType`.
+///
+/// Used to filter which collections invoke a callback registered with
+/// `Isolate::add_gc_prologue_callback`/`add_gc_epilogue_callback`; combine flags with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GCType(os::raw::c_int);
+
+impl GCType {
+    pub const SCAVENGE: GCType = GCType(1 << 0);
+    pub const MARK_SWEEP_COMPACT: GCType = GCType(1 << 1);
+    pub const INCREMENTAL_MARKING: GCType = GCType(1 << 2);
+    pub const PROCESS_WEAK_CALLBACKS: GCType = GCType(1 << 3);
+    pub const ALL: GCType = GCType(1 << 0 | 1 << 1 | 1 << 2 | 1 << 3);
+
+    fn as_raw(self) -> v8_sys::GCType {
+        self.0 as v8_sys::GCType
+    }
+
+    fn from_raw(raw: v8_sys::GCType) -> GCType {
+        GCType(raw as os::raw::c_int)
+    }
+}
+
+impl ops::BitOr for GCType {
+    type Output = GCType;
+
+    fn bitor(self, rhs: GCType) -> GCType {
+        GCType(self.0 | rhs.0)
+    }
+}
+
+/// Which generations `Isolate::request_garbage_collection_for_testing` should collect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GarbageCollectionType {
+    /// Collects both the young and old generation.
+    Full,
+    /// Collects only the young generation.
+    Minor,
+}
+
+impl GarbageCollectionType {
+    fn as_raw(self) -> v8_sys::GarbageCollectionType {
+        match self {
+            GarbageCollectionType::Full => v8_sys::GarbageCollectionType::kFullGarbageCollection,
+            GarbageCollectionType::Minor => {
+                v8_sys::GarbageCollectionType::kMinorGarbageCollection
+            }
+        }
+    }
+}
+
+/// Callback type for `Isolate::add_gc_prologue_callback`/`add_gc_epilogue_callback`.
+pub type GCCallback = Fn(&Isolate, GCType) + 'static;
+
+extern "C" fn gc_callback_trampoline(
+    isolate: *mut v8_sys::Isolate,
+    gc_type: v8_sys::GCType,
+    _flags: v8_sys::GCCallbackFlags,
+    data: *mut os::raw::c_void,
+) {
+    unsafe {
+        let isolate = Isolate::from_ptr(isolate);
+        let callback = &*(data as *mut Box<GCCallback>);
+        callback(&isolate, GCType::from_raw(gc_type));
+    }
+}
+
+extern "C" fn near_heap_limit_callback(
+    data: *mut os::raw::c_void,
+    current_heap_limit: usize,
+    initial_heap_limit: usize,
+) -> usize {
+    let callback = unsafe { &*(data as *mut Box<NearHeapLimitCallback>) };
+    callback(current_heap_limit, initial_heap_limit)
+}
+
+/// An RAII guard holding V8's lock for the current thread on a given isolate, acquired with
+/// `Isolate::lock`.  Dropping it releases the lock (the "Unlocker" half of V8's Locker/Unlocker
+/// API), letting another thread take a `Locker` of its own and safely enter the isolate.
+pub struct Locker(ptr::Unique<v8_sys::Locker>);
+
+impl Locker {
+    /// Whether `isolate` is currently locked by any thread.
+    pub fn is_locked(isolate: &Isolate) -> bool {
+        unsafe { v8_sys::Locker::IsLocked(isolate.as_ptr()) }
+    }
+}
+
+impl fmt::Debug for Locker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Locker({:?})", unsafe { self.0.as_ref() })
+    }
+}
+
+impl Drop for Locker {
+    fn drop(&mut self) {
+        unsafe {
+            v8_sys::Locker_Locker_destructor(self.0.as_ptr());
+        }
+    }
+}
+
+/// A handle to an isolate built with `Builder::supports_locking(true)` that can be sent to another
+/// thread and locked there, unlike `Isolate` itself (which is tied to `Rc`-based task queues and is
+/// therefore `!Send`).
+///
+/// Obtained from `Isolate::send_handle`.
+pub struct SendIsolate(*mut v8_sys::Isolate);
+
+unsafe impl Send for SendIsolate {}
+
+impl SendIsolate {
+    /// Locks the isolate on the calling thread and recovers an `Isolate` handle to it.
+    pub fn lock(self) -> (Locker, Isolate) {
+        let isolate = unsafe { Isolate::from_ptr(self.0) };
+        let locker = isolate.lock();
+        (locker, isolate)
+    }
+}
+
+/// Why `Isolate::run_guarded` saw its call terminated, recorded by whichever of `cancel`,
+/// `set_execution_timeout`'s watchdog, or `set_heap_limit`'s GC epilogue callback called
+/// `TerminateExecution`.
+#[derive(Debug, Clone, Copy)]
+enum TerminationReason {
+    /// Terminated via `Isolate::cancel`/`CancelHandle::cancel`, or `set_execution_timeout`'s
+    /// watchdog running out the clock.
+    Guarded,
+    /// Terminated because the isolate's heap usage reached the limit configured via
+    /// `Isolate::set_heap_limit`.
+    OutOfMemory(usize),
+}
+
+/// A `Send`/`Sync` handle that can terminate whatever script is currently executing on the
+/// isolate it was taken from, from any thread, without requiring
+/// `Builder::supports_locking(true)`.
+///
+/// Obtained from `Isolate::cancel_handle`.
+#[derive(Clone)]
+pub struct CancelHandle {
+    isolate: *mut v8_sys::Isolate,
+    termination: sync::Arc<sync::Mutex<Option<TerminationReason>>>,
+}
+
+unsafe impl Send for CancelHandle {}
+unsafe impl Sync for CancelHandle {}
+
+impl CancelHandle {
+    /// Terminates whatever `run_guarded`-wrapped call is currently executing on the isolate this
+    /// handle was taken from. A no-op if nothing is currently executing.
+    pub fn cancel(&self) {
+        self.terminate(TerminationReason::Guarded);
+    }
+
+    fn terminate(&self, reason: TerminationReason) {
+        *self.termination.lock().unwrap() = Some(reason);
+        unsafe { (*self.isolate).TerminateExecution() };
+    }
+}
+
+/// An RAII guard that spawns a background thread to terminate its isolate if `timeout` elapses
+/// before the guard is dropped, backing `Isolate::set_execution_timeout`. Dropping the guard
+/// before the timeout fires (i.e. the guarded call returned in time) stops the watchdog without
+/// it ever touching the isolate.
+struct ExecutionWatchdog {
+    cancelled: sync::mpsc::Sender<()>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl ExecutionWatchdog {
+    fn arm(handle: CancelHandle, timeout: time::Duration) -> ExecutionWatchdog {
+        let (cancelled, recv) = sync::mpsc::channel();
+
+        let thread_handle = thread::Builder::new()
+            .name("v8-execution-watchdog".to_owned())
+            .spawn(move || match recv.recv_timeout(timeout) {
+                Ok(()) | Err(sync::mpsc::RecvTimeoutError::Disconnected) => {}
+                Err(sync::mpsc::RecvTimeoutError::Timeout) => {
+                    handle.terminate(TerminationReason::Guarded);
+                }
+            })
+            .expect("could not spawn the V8 execution watchdog thread");
+
+        ExecutionWatchdog {
+            cancelled: cancelled,
+            handle: Some(thread_handle),
+        }
+    }
+}
+
+impl Drop for ExecutionWatchdog {
+    fn drop(&mut self) {
+        let _ = self.cancelled.send(());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Something that is owned by (and knows how to find its way back to) an `Isolate`.
+///
+/// Every handle, context and persistent reference is ultimately tied to one isolate, so rather
+/// than threading an `&Isolate` through every call that needs one, code that already holds one of
+/// these can recover it with `get_isolate`.
+pub trait GetIsolate {
+    /// Returns the isolate that owns this value.
+    fn get_isolate(&self) -> Isolate;
 }
 
 impl Clone for Isolate {
@@ -260,6 +891,44 @@ impl Builder {
         self
     }
 
+    /// Whether the isolate should require a `Locker` to be held by whichever thread enters it;
+    /// i.e. whether the embedder plans to drive this isolate from more than one thread.
+    pub fn supports_locking(mut self, value: bool) -> Builder {
+        self.supports_locking = value;
+        self
+    }
+
+    /// Configures sensible default heap resource constraints for a heap of around
+    /// `maximum_size_in_bytes`, starting out at `initial_size_in_bytes`.
+    ///
+    /// This is the convenience entry point most embedders want; see
+    /// `max_old_generation_size_in_bytes`/`max_young_generation_size_in_bytes` for finer-grained
+    /// control, for example to cap the memory a sandboxed, untrusted script may use.
+    pub fn heap_limits(mut self, initial_size_in_bytes: usize, maximum_size_in_bytes: usize) -> Builder {
+        self.heap_limits = Some((initial_size_in_bytes, maximum_size_in_bytes));
+        self
+    }
+
+    /// Caps the size of the old generation heap, in bytes.
+    pub fn max_old_generation_size_in_bytes(mut self, value: usize) -> Builder {
+        self.max_old_generation_size_in_bytes = Some(value);
+        self
+    }
+
+    /// Caps the size of the young generation heap, in bytes.
+    pub fn max_young_generation_size_in_bytes(mut self, value: usize) -> Builder {
+        self.max_young_generation_size_in_bytes = Some(value);
+        self
+    }
+
+    /// Deserializes `blob` (produced by `snapshot::SnapshotCreator::create_blob`) into the new
+    /// isolate's heap instead of starting from V8's own built-in snapshot, skipping the cost of
+    /// re-parsing and re-compiling whatever `blob` already warmed up.
+    pub fn snapshot_blob(mut self, blob: snapshot::StartupData) -> Builder {
+        self.snapshot_blob = Some(blob);
+        self
+    }
+
     /// Constructs a new `Isolate` based on this builder.
     pub fn build(self) -> Isolate {
         ensure_initialized();
@@ -270,6 +939,27 @@ impl Builder {
             let mut params: v8_sys::Isolate_CreateParams = mem::zeroed();
             params.allow_atomics_wait = true;
             params.array_buffer_allocator = allocator.as_ptr();
+            if let Some((initial_size_in_bytes, maximum_size_in_bytes)) = self.heap_limits {
+                params.constraints.ConfigureDefaultsFromHeapSize(
+                    initial_size_in_bytes,
+                    maximum_size_in_bytes,
+                );
+            }
+            if let Some(value) = self.max_old_generation_size_in_bytes {
+                params.constraints.set_max_old_generation_size_in_bytes(value);
+            }
+            if let Some(value) = self.max_young_generation_size_in_bytes {
+                params.constraints.set_max_young_generation_size_in_bytes(value);
+            }
+            let mut raw_snapshot_blob = self.snapshot_blob.as_ref().map(|blob| {
+                v8_sys::StartupData {
+                    data: blob.as_bytes().as_ptr() as *const os::raw::c_char,
+                    raw_size: blob.as_bytes().len() as os::raw::c_int,
+                }
+            });
+            if let Some(ref mut raw_snapshot_blob) = raw_snapshot_blob {
+                params.snapshot_blob = raw_snapshot_blob;
+            }
             ptr::Shared::new(v8_sys::Isolate::New(&params)).expect("Could not create Isolate")
         };
 
@@ -290,6 +980,11 @@ impl Builder {
             _allocator: allocator,
             task_queue: rc::Rc::new(cell::RefCell::new(priority_queue::PriorityQueue::new())),
             idle_task_queue: idle_task_queue,
+            worker_pool: WorkerPool::new(num_cpus::get()),
+            supports_locking: self.supports_locking,
+            execution_timeout: cell::Cell::new(None),
+            termination: sync::Arc::new(sync::Mutex::new(None)),
+            externals: ExternalRegistry::new(),
         };
         let data_ptr: *mut Data = Box::into_raw(Box::new(data));
 
@@ -311,17 +1006,18 @@ impl Builder {
 
 impl<'i> Scope<'i> {
     pub fn isolate(&self) -> &Isolate {
-        &self.0
+        self.isolate
     }
 
     pub fn isolate_mut(&mut self) -> &mut Isolate {
-        &mut self.0
+        self.isolate
     }
 }
 
 impl<'i> Drop for Scope<'i> {
     fn drop(&mut self) {
-        unsafe { (self.0).0.as_mut().Exit() }
+        // `_locker` is released after this returns, once the isolate has already exited.
+        unsafe { self.isolate.0.as_mut().Exit() }
     }
 }
 
@@ -339,3 +1035,173 @@ fn ensure_initialized() {
         }
     });
 }
+
+/// An isolate-owned pool of background worker threads, modeled on the queue design `node`'s V8
+/// platform embedding uses: a blocking `ready` queue that workers pop from, a side list of
+/// not-yet-ready delayed tasks that a timer thread promotes once they come due, and an
+/// `outstanding_tasks` count so callers can block until the pool has drained.
+struct WorkerPool {
+    queue: sync::Arc<WorkerQueue>,
+    workers: Vec<thread::JoinHandle<()>>,
+    timer: Option<thread::JoinHandle<()>>,
+}
+
+struct WorkerQueue {
+    state: sync::Mutex<WorkerQueueState>,
+    tasks_available: sync::Condvar,
+    tasks_drained: sync::Condvar,
+}
+
+struct WorkerQueueState {
+    ready: collections::VecDeque<platform::Task>,
+    delayed: Vec<(time::Instant, platform::Task)>,
+    outstanding_tasks: usize,
+    stopped: bool,
+}
+
+impl WorkerPool {
+    fn new(num_threads: usize) -> WorkerPool {
+        let queue = sync::Arc::new(WorkerQueue::new());
+
+        let workers = (0..num_threads)
+            .map(|index| {
+                let queue = queue.clone();
+                thread::Builder::new()
+                    .name(format!("v8-worker-{}", index))
+                    .spawn(move || while let Some(task) = queue.blocking_pop() {
+                        task.run();
+                        queue.task_done();
+                    })
+                    .expect("could not spawn a V8 worker thread")
+            })
+            .collect();
+
+        let timer = {
+            let queue = queue.clone();
+            thread::Builder::new()
+                .name("v8-worker-timer".to_owned())
+                .spawn(move || {
+                    while !queue.is_stopped() {
+                        thread::sleep(time::Duration::from_millis(10));
+                        queue.promote_ready_delayed_tasks();
+                    }
+                })
+                .expect("could not spawn the V8 worker timer thread")
+        };
+
+        WorkerPool {
+            queue: queue,
+            workers: workers,
+            timer: Some(timer),
+        }
+    }
+}
+
+impl fmt::Debug for WorkerPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WorkerPool({} workers)", self.workers.len())
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.queue.stop();
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+        if let Some(timer) = self.timer.take() {
+            let _ = timer.join();
+        }
+    }
+}
+
+impl WorkerQueue {
+    fn new() -> WorkerQueue {
+        WorkerQueue {
+            state: sync::Mutex::new(WorkerQueueState {
+                ready: collections::VecDeque::new(),
+                delayed: Vec::new(),
+                outstanding_tasks: 0,
+                stopped: false,
+            }),
+            tasks_available: sync::Condvar::new(),
+            tasks_drained: sync::Condvar::new(),
+        }
+    }
+
+    /// Enqueues `task` to run as soon as a worker is free.
+    fn push(&self, task: platform::Task) {
+        let mut state = self.state.lock().unwrap();
+        state.ready.push_back(task);
+        state.outstanding_tasks += 1;
+        self.tasks_available.notify_one();
+    }
+
+    /// Enqueues `task` to be promoted to the ready queue once `ready_at` has passed.
+    fn push_delayed(&self, ready_at: time::Instant, task: platform::Task) {
+        let mut state = self.state.lock().unwrap();
+        state.delayed.push((ready_at, task));
+        state.outstanding_tasks += 1;
+    }
+
+    /// Blocks until a ready task is available, then pops and returns it.  Returns `None` once the
+    /// queue has been stopped and drained.
+    fn blocking_pop(&self) -> Option<platform::Task> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(task) = state.ready.pop_front() {
+                return Some(task);
+            }
+            if state.stopped {
+                return None;
+            }
+            state = self.tasks_available.wait(state).unwrap();
+        }
+    }
+
+    /// Marks one previously popped task as finished, waking any `blocking_drain` callers once none
+    /// remain outstanding.
+    fn task_done(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.outstanding_tasks -= 1;
+        if state.outstanding_tasks == 0 {
+            self.tasks_drained.notify_all();
+        }
+    }
+
+    /// Blocks the calling thread until every task pushed so far (ready or delayed) has run.
+    #[allow(dead_code)]
+    fn blocking_drain(&self) {
+        let mut state = self.state.lock().unwrap();
+        while state.outstanding_tasks > 0 {
+            state = self.tasks_drained.wait(state).unwrap();
+        }
+    }
+
+    /// Moves any delayed tasks whose deadline has passed into the ready queue.
+    fn promote_ready_delayed_tasks(&self) {
+        let mut state = self.state.lock().unwrap();
+        let now = time::Instant::now();
+        let mut i = 0;
+        while i < state.delayed.len() {
+            if state.delayed[i].0 <= now {
+                let (_, task) = state.delayed.remove(i);
+                state.ready.push_back(task);
+                self.tasks_available.notify_one();
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.state.lock().unwrap().stopped
+    }
+
+    fn stop(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.stopped = true;
+        self.tasks_available.notify_all();
+    }
+}
@@ -47,6 +47,10 @@
 extern crate error_chain;
 #[macro_use]
 extern crate lazy_static;
+#[cfg(feature = "arrow2")]
+extern crate arrow2;
+#[cfg(feature = "serde")]
+extern crate serde;
 extern crate v8_sys;
 
 mod allocator;
@@ -54,17 +58,29 @@ mod platform;
 #[macro_use]
 mod util;
 
+#[cfg(feature = "arrow2")]
+pub mod arrow_value;
 pub mod context;
 pub mod error;
+#[cfg(feature = "futures")]
+pub mod future;
 pub mod isolate;
+pub mod module;
 pub mod script;
+#[cfg(feature = "serde")]
+pub mod serde_value;
+pub mod serialize;
+pub mod snapshot;
 pub mod template;
 pub mod value;
+pub mod vm;
 
 pub use context::Context;
 pub use isolate::Isolate;
 pub use script::Script;
 pub use value::Value;
+#[cfg(feature = "serde")]
+pub use serde_value::{from_value, to_value};
 
 #[cfg(test)]
 mod tests {
@@ -206,6 +222,30 @@ mod tests {
         assert_eq!(9007199254740992, v.integer_value(&c));
     }
 
+    #[test]
+    fn eval_big_int() {
+        let (_, _, v) = eval("123456789012345678901234567890n").unwrap();
+        assert!(v.is_big_int());
+        let v = v.into_big_int().unwrap();
+        let (_, lossless) = v.to_i64();
+        assert!(!lossless);
+        let (negative, words) = v.to_words();
+        assert!(!negative);
+        assert!(!words.is_empty());
+    }
+
+    #[test]
+    fn value_try_from() {
+        use std::convert::TryFrom;
+
+        let (_, _, v) = eval("42").unwrap();
+        let n = value::Number::try_from(v).unwrap();
+        assert_eq!(42f64, n.value());
+
+        let (_, _, v) = eval("\"not a number\"").unwrap();
+        assert!(value::Number::try_from(v).is_err());
+    }
+
     #[test]
     fn eval_function() {
         let (i, c, v) = eval("(function(a, b) { return a + b; })").unwrap();
@@ -430,7 +470,7 @@ mod tests {
 
         let error = result.unwrap_err();
         match error.kind() {
-            &error::ErrorKind::Javascript(ref msg, _) => {
+            &error::ErrorKind::Javascript(_, _, ref msg, _, _) => {
                 assert_eq!("Uncaught SyntaxError: Unexpected end of input", msg);
             }
             x => panic!("Unexpected error kind: {:?}", x),
@@ -443,8 +483,26 @@ mod tests {
 
         let error = result.unwrap_err();
         match error.kind() {
-            &error::ErrorKind::Javascript(ref msg, _) => {
+            &error::ErrorKind::Javascript(ref exception, ref name, ref msg, _, _) => {
                 assert_eq!("Uncaught x", msg);
+                assert!(name.is_none());
+                assert!(exception.is_string());
+                assert_eq!("x", exception.clone().into_string().unwrap().value());
+            }
+            x => panic!("Unexpected error kind: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn eval_exception_name_and_value() {
+        let result = eval("throw new RangeError('too big');");
+
+        let error = result.unwrap_err();
+        match error.kind() {
+            &error::ErrorKind::Javascript(ref exception, ref name, ref msg, _, _) => {
+                assert_eq!("Uncaught RangeError: too big", msg);
+                assert_eq!(&Some("RangeError".to_owned()), name);
+                assert!(exception.is_object());
             }
             x => panic!("Unexpected error kind: {:?}", x),
         }
@@ -472,7 +530,7 @@ mod tests {
 
         let error = result.unwrap_err();
         match error.kind() {
-            &error::ErrorKind::Javascript(ref msg, ref stack_trace) => {
+            &error::ErrorKind::Javascript(_, _, ref msg, ref stack_trace, _) => {
                 assert_eq!("Uncaught Error: x", msg);
                 assert_eq!("    at new w (test.js:13:11)\n    at z (test.js:10:5)\n    at eval \
                             <anon>:1:1\n    at y (test.js:7:5)\n    at x (test.js:4:5)\n    at \
@@ -483,6 +541,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn compile_and_cache_roundtrip() {
+        let isolate = Isolate::new();
+        let context = Context::new(&isolate);
+        let name = value::String::from_str(&isolate, "test.js");
+        let source = value::String::from_str(&isolate, "1 + 1");
+
+        let (script, cache) = Script::compile_and_cache(&isolate, &context, &name.clone().into(), &source).unwrap();
+        assert_eq!(2, script.run(&context).unwrap().into_integer().unwrap().value());
+
+        let other_isolate = Isolate::new();
+        let other_context = Context::new(&other_isolate);
+        let (cached_script, rejected) =
+            Script::compile_with_cache(&other_isolate, &other_context, &source, &cache).unwrap();
+        assert!(!rejected);
+        assert_eq!(2,
+                   cached_script.run(&other_context).unwrap().into_integer().unwrap().value());
+    }
+
     #[test]
     fn run_native_function_call() {
         let isolate = Isolate::new();
@@ -528,6 +605,150 @@ mod tests {
         assert_eq!(5, result.int32_value(&c));
     }
 
+    #[test]
+    fn execution_timeout_aborts_infinite_loop() {
+        use std::time;
+
+        let isolate = Isolate::new();
+        isolate.set_execution_timeout(time::Duration::from_millis(50));
+        let context = Context::new(&isolate);
+        let name = value::String::from_str(&isolate, "test.js");
+        let source = value::String::from_str(&isolate, "while (true) {}");
+        let script = Script::compile_with_name(&isolate, &context, &name, &source).unwrap();
+
+        let started = time::Instant::now();
+        let result = script.run(&context);
+        assert!(started.elapsed() < time::Duration::from_secs(10));
+
+        match result.unwrap_err().kind() {
+            &error::ErrorKind::Terminated => {}
+            x => panic!("Unexpected error kind: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn cancel_aborts_running_script() {
+        use std::thread;
+        use std::time;
+
+        let isolate = Isolate::new();
+        let cancel_handle = isolate.cancel_handle();
+        let context = Context::new(&isolate);
+        let name = value::String::from_str(&isolate, "test.js");
+        let source = value::String::from_str(&isolate, "while (true) {}");
+        let script = Script::compile_with_name(&isolate, &context, &name, &source).unwrap();
+
+        thread::spawn(move || {
+            thread::sleep(time::Duration::from_millis(50));
+            cancel_handle.cancel();
+        });
+
+        match script.run(&context).unwrap_err().kind() {
+            &error::ErrorKind::Terminated => {}
+            x => panic!("Unexpected error kind: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn heap_limit_aborts_runaway_allocation() {
+        let isolate = Isolate::new();
+        isolate.set_heap_limit(8 * 1024 * 1024);
+        let context = Context::new(&isolate);
+        let name = value::String::from_str(&isolate, "test.js");
+        let source = value::String::from_str(&isolate,
+                                             "var xs = []; while (true) { xs.push(new Array(1024).fill(0)); }");
+        let script = Script::compile_with_name(&isolate, &context, &name, &source).unwrap();
+
+        match script.run(&context).unwrap_err().kind() {
+            &error::ErrorKind::OutOfMemory(limit_bytes) => assert_eq!(8 * 1024 * 1024, limit_bytes),
+            x => panic!("Unexpected error kind: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn promise_resolver_resolve_settles_promise() {
+        let isolate = Isolate::new();
+        let context = Context::new(&isolate);
+        let resolver = value::PromiseResolver::new(&isolate, &context).unwrap();
+        let promise = resolver.get_promise();
+
+        match promise.state() {
+            value::PromiseState::Pending => {}
+            x => panic!("Unexpected promise state: {:?}", x),
+        }
+
+        let v = value::Integer::new(&isolate, 42);
+        assert!(resolver.resolve(&context, &v.clone().into()));
+        context.run_microtasks();
+
+        match promise.state() {
+            value::PromiseState::Fulfilled(value) => {
+                assert_eq!(42, value.into_integer().unwrap().value())
+            }
+            x => panic!("Unexpected promise state: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn promise_resolver_reject_settles_promise() {
+        let isolate = Isolate::new();
+        let context = Context::new(&isolate);
+        let resolver = value::PromiseResolver::new(&isolate, &context).unwrap();
+        let promise = resolver.get_promise();
+
+        let reason = value::String::from_str(&isolate, "nope");
+        assert!(resolver.reject(&context, &reason.clone().into()));
+        context.run_microtasks();
+
+        match promise.state() {
+            value::PromiseState::Rejected(value) => {
+                assert_eq!("nope", value.into_string().unwrap().value())
+            }
+            x => panic!("Unexpected promise state: {:?}", x),
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn promise_future_resolves_once_promise_settles() {
+        use std::pin;
+        use std::task;
+        use future::PromiseFuture;
+
+        let isolate = Isolate::new();
+        let context = Context::new(&isolate);
+        let resolver = value::PromiseResolver::new(&isolate, &context).unwrap();
+        let v = value::Integer::new(&isolate, 42);
+        resolver.resolve(&context, &v.into());
+
+        let mut fut = PromiseFuture::new(&context, resolver.get_promise());
+        let waker = futures_test_waker();
+        let mut cx = task::Context::from_waker(&waker);
+
+        let result = loop {
+            match unsafe { pin::Pin::new_unchecked(&mut fut) }.poll(&mut cx) {
+                task::Poll::Ready(result) => break result,
+                task::Poll::Pending => continue,
+            }
+        };
+
+        assert_eq!(42, result.unwrap().into_integer().unwrap().value());
+
+        fn futures_test_waker() -> task::Waker {
+            use std::task::{RawWaker, RawWakerVTable};
+
+            static NOOP: () = ();
+
+            unsafe fn clone(_data: *const ()) -> RawWaker {
+                RawWaker::new(&NOOP, &VTABLE)
+            }
+            unsafe fn noop(_data: *const ()) {}
+
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+            unsafe { task::Waker::from_raw(RawWaker::new(&NOOP, &VTABLE)) }
+        }
+    }
+
     fn test_function(info: value::FunctionCallbackInfo) -> value::Value {
         let i = info.isolate;
         let c = i.current_context().unwrap();
@@ -673,6 +894,50 @@ mod tests {
         assert_eq!("Goodbye, World!", bar.msg);
         assert!(result.is_undefined());
     }
+
+    #[test]
+    fn map_and_set_from_empty_iterator_do_not_panic() {
+        let isolate = Isolate::new();
+
+        let map = value::Map::from_entries(&isolate, Vec::new());
+        assert_eq!(0, map.size());
+
+        let set = value::Set::from_values(&isolate, Vec::new());
+        assert_eq!(0, set.size());
+    }
+
+    #[test]
+    fn serializer_round_trips_an_object_referenced_twice() {
+        use std::convert::TryFrom;
+
+        let isolate = Isolate::new();
+        let context = Context::new(&isolate);
+
+        let name = value::String::from_str(&isolate, "name");
+        let shared = value::Object::new(&isolate, &context);
+        shared.set(&context, &name, &value::Number::new(&isolate, 42.0));
+
+        let array = value::Array::new(&isolate, &context, 2);
+        array.set_index(&context, 0, &shared);
+        array.set_index(&context, 1, &shared);
+
+        let bytes = serialize::Serializer::new(&isolate, &context)
+            .write_value(&array.into())
+            .unwrap();
+
+        let result = serialize::Deserializer::new(&isolate, &context, &bytes)
+            .read_value()
+            .unwrap();
+        let result = value::Array::try_from(result).unwrap();
+
+        let first = result.get_index(&context, 0);
+        let second = result.get_index(&context, 1);
+        assert!(first.strict_equals(&second));
+
+        let first = value::Object::try_from(first).unwrap();
+        let property = first.get(&context, &name);
+        assert_eq!(42.0, property.into_number().unwrap().value());
+    }
 }
 
 #[cfg(all(feature="unstable", test))]
@@ -0,0 +1,183 @@
+//! ECMAScript modules: compiling `import`/`export` source, resolving its dependency graph, and
+//! evaluating it, mirroring V8's `v8::Module` (as used by Deno's `EsIsolate` to drive the
+//! `import` graph) and `ScriptCompiler::CompileModule`.
+use v8_sys as v8;
+use std::os;
+use std::ptr;
+
+use context;
+use error;
+use isolate;
+use util;
+use value;
+
+/// Where a `Module` is in its compile/instantiate/evaluate lifecycle, mirroring V8's
+/// `Module::Status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// Just returned by `Module::compile`; nothing has resolved its imports yet.
+    Uninstantiated,
+    /// `instantiate` is resolving this module's own imports and those of its dependencies.
+    Instantiating,
+    /// `instantiate` finished successfully; ready for `evaluate`.
+    Instantiated,
+    /// `evaluate` is running this module's (or one of its dependencies') top-level code.
+    Evaluating,
+    /// `evaluate` finished successfully.
+    Evaluated,
+    /// Instantiation or evaluation threw; see `get_exception`.
+    Errored,
+}
+
+impl Status {
+    fn from_raw(raw: os::raw::c_int) -> Status {
+        match raw {
+            0 => Status::Uninstantiated,
+            1 => Status::Instantiating,
+            2 => Status::Instantiated,
+            3 => Status::Evaluating,
+            4 => Status::Evaluated,
+            5 => Status::Errored,
+            s => panic!("unknown v8::Module::Status {}", s),
+        }
+    }
+}
+
+/// Resolves a module specifier (the string in `import ... from "specifier"`) encountered while
+/// instantiating a `Module`, analogous to Node's/Deno's loader. Returns the resolved `Module`, or
+/// `None` if the specifier could not be resolved, which fails instantiation with a `TypeError`.
+pub type ModuleResolveCallback = Fn(&context::Context, &value::String, &Module) -> Option<Module> +
+    'static;
+
+/// A parsed, not-yet-instantiated/evaluated ECMAScript module (`import`/`export` source), tied to
+/// the `Context` it was compiled in.
+#[derive(Debug)]
+pub struct Module(isolate::Isolate, v8::ModuleRef);
+
+impl Module {
+    /// Compiles `source` as an ECMAScript module rather than a classic script, so its top-level
+    /// `import`/`export` statements are recognized instead of being syntax errors. `origin` is
+    /// attributed to the module the same way it is for `Script::compile_with_name`.
+    pub fn compile(isolate: &isolate::Isolate,
+                   context: &context::Context,
+                   source: &value::String,
+                   origin: &value::Value)
+                   -> error::Result<Module> {
+        let raw = unsafe {
+            try!(util::invoke_ctx(isolate, context, |c| {
+                v8::v8_ScriptCompiler_CompileModule(c,
+                                                    context.as_raw(),
+                                                    source.as_raw(),
+                                                    origin.as_raw())
+            }))
+        };
+        Ok(Module(isolate.clone(), raw))
+    }
+
+    /// Resolves every module request (`import`/`export ... from`) transitively reachable from
+    /// this module via `resolver`, readying it for `evaluate`. Must be called exactly once, before
+    /// `evaluate`.
+    pub fn instantiate(&self,
+                       context: &context::Context,
+                       resolver: Box<ModuleResolveCallback>)
+                       -> error::Result<()> {
+        unsafe {
+            let resolver_ptr = Box::into_raw(Box::new(resolver));
+            try!(util::invoke_ctx(&self.0, context, |c| {
+                v8::v8_Module_InstantiateModule(c,
+                                                self.1,
+                                                context.as_raw(),
+                                                Some(resolve_trampoline),
+                                                resolver_ptr as *mut os::raw::c_void)
+            }));
+            drop(Box::from_raw(resolver_ptr));
+        }
+        Ok(())
+    }
+
+    /// Runs the module's top-level code, returning its completion value (usually `undefined`).
+    pub fn evaluate(&self, context: &context::Context) -> error::Result<value::Value> {
+        unsafe {
+            let raw = try!(util::invoke_ctx(&self.0, context, |c| {
+                v8::v8_Module_Evaluate(c, self.1, context.as_raw())
+            }));
+            Ok(value::Value::from_raw(&self.0, raw))
+        }
+    }
+
+    /// The specifiers (`"./foo.js"`, `"fs"`, ...) this module's `import`/`export ... from`
+    /// statements request, in source order.
+    pub fn get_module_requests(&self) -> error::Result<Vec<value::String>> {
+        unsafe {
+            let length = try!(util::invoke(&self.0, |c| {
+                v8::v8_Module_GetModuleRequestsLength(c, self.1)
+            }));
+            (0..length)
+                .map(|i| {
+                    let raw = try!(util::invoke(&self.0, |c| {
+                        v8::v8_Module_GetModuleRequest(c, self.1, i)
+                    }));
+                    Ok(value::String::from_raw(&self.0, raw))
+                })
+                .collect()
+        }
+    }
+
+    /// Where this module is in its compile/instantiate/evaluate lifecycle.
+    pub fn get_status(&self) -> error::Result<Status> {
+        unsafe {
+            let raw = try!(util::invoke(&self.0, |c| v8::v8_Module_GetStatus(c, self.1)));
+            Ok(Status::from_raw(raw))
+        }
+    }
+
+    /// The exception thrown during instantiation/evaluation, if `get_status()` is
+    /// `Status::Errored`.
+    pub fn get_exception(&self) -> error::Result<value::Value> {
+        unsafe {
+            let raw = try!(util::invoke(&self.0, |c| v8::v8_Module_GetException(c, self.1)));
+            Ok(value::Value::from_raw(&self.0, raw))
+        }
+    }
+
+    /// Creates a module from a set of raw pointers.
+    pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8::ModuleRef) -> Module {
+        Module(isolate.clone(), raw)
+    }
+
+    /// Returns the underlying raw pointer behind this module.
+    pub fn as_raw(&self) -> v8::ModuleRef {
+        self.1
+    }
+}
+
+/// The C trampoline `v8_Module_InstantiateModule`'s native glue calls back into for every module
+/// request, bridging its `specifier`/`referrer` pair back to the boxed `ModuleResolveCallback`
+/// threaded through as `data`, mirroring `util::callback`'s bridge for `FunctionTemplate`.
+extern "C" fn resolve_trampoline(c: v8::RustContext,
+                                 context: v8::ContextRef,
+                                 specifier: v8::StringRef,
+                                 referrer: v8::ModuleRef,
+                                 data: *mut os::raw::c_void)
+                                 -> v8::ModuleRef {
+    unsafe {
+        let isolate = isolate::Isolate::from_raw(c.isolate);
+        let context = context::Context::from_raw(&isolate, context);
+        let specifier = value::String::from_raw(&isolate, specifier);
+        let referrer = Module::from_raw(&isolate, referrer);
+
+        let resolver: *mut Box<ModuleResolveCallback> = data as *mut _;
+        let resolver = resolver.as_ref().unwrap();
+
+        match resolver(&context, &specifier, &referrer) {
+            Some(resolved) => {
+                let raw = resolved.as_raw();
+                ::std::mem::forget(resolved);
+                raw
+            }
+            None => ptr::null_mut(),
+        }
+    }
+}
+
+reference!(Module, v8::v8_Module_CloneRef, v8::v8_Module_DestroyRef);
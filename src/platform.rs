@@ -1,8 +1,10 @@
 use v8_sys;
+use std::collections;
 use std::fmt;
 use std::hash;
 use std::os;
 use std::ptr;
+use std::sync;
 use std::thread;
 use std::time;
 use num_cpus;
@@ -14,10 +16,7 @@ lazy_static! {
     };
 }
 
-/// A simple platform implementation that uses global OS threads for
-/// scheduling.
-// TODO: make this use some kind of main loop/work stealing queue
-// instead.
+/// A platform implementation backed by a pluggable `Scheduler`.
 pub struct Platform(ptr::Unique<v8_sys::Platform>);
 
 pub struct Task(ptr::Unique<v8_sys::Task>);
@@ -26,13 +25,159 @@ unsafe impl Send for Task {}
 
 pub struct IdleTask(ptr::Unique<v8_sys::IdleTask>);
 
+/// A pluggable strategy for where V8's foreground and background tasks actually run.
+///
+/// `Platform::new` uses a `WorkStealingPool` sized to `num_cpus::get()` for background tasks, and
+/// dispatches foreground tasks into the target isolate's own queue (see the `isolate` module docs
+/// for how those get pumped). Embedders that already run their own thread pool or event loop can
+/// implement this trait and build a `Platform` with `Platform::with_scheduler` instead.
+pub trait Scheduler: Send + Sync {
+    /// The number of background threads this scheduler is willing to run concurrently. V8 uses
+    /// this as a hint for how much work it should keep in flight.
+    fn num_background_threads(&self) -> usize;
+
+    /// Schedules `task` to run on a background thread as soon as one becomes available.
+    fn run_on_background_thread(&self, task: Task);
+
+    /// Hands `task` off to `isolate`'s own foreground queue.
+    fn run_on_foreground_thread(&self, isolate: &isolate::Isolate, task: Task) {
+        isolate.enqueue_task(task);
+    }
+
+    /// Hands a delayed `task` off to `isolate`'s own foreground queue.
+    fn run_delayed_on_foreground_thread(
+        &self,
+        isolate: &isolate::Isolate,
+        task: Task,
+        delay: time::Duration,
+    ) {
+        isolate.enqueue_delayed_task(delay, task);
+    }
+
+    /// Hands an idle `task` off to `isolate`'s own foreground queue.
+    fn run_idle_on_foreground_thread(&self, isolate: &isolate::Isolate, task: IdleTask) {
+        isolate.enqueue_idle_task(task);
+    }
+
+    /// Whether `isolate` should be given idle tasks at all.
+    fn idle_tasks_enabled(&self, isolate: &isolate::Isolate) -> bool {
+        isolate.supports_idle_tasks()
+    }
+}
+
+/// The default `Scheduler`: a fixed-size pool of background worker threads, each with its own
+/// queue of tasks, that steal work from a neighbour's queue once their own runs dry instead of
+/// sitting idle while another thread is backed up.
+pub struct WorkStealingPool {
+    queues: Vec<sync::Arc<TaskQueue>>,
+    next: sync::atomic::AtomicUsize,
+}
+
+struct TaskQueue {
+    tasks: sync::Mutex<collections::VecDeque<Task>>,
+    condvar: sync::Condvar,
+}
+
+impl TaskQueue {
+    fn new() -> TaskQueue {
+        TaskQueue {
+            tasks: sync::Mutex::new(collections::VecDeque::new()),
+            condvar: sync::Condvar::new(),
+        }
+    }
+
+    fn push(&self, task: Task) {
+        self.tasks.lock().unwrap().push_back(task);
+        self.condvar.notify_one();
+    }
+
+    fn steal(&self) -> Option<Task> {
+        self.tasks.lock().unwrap().pop_front()
+    }
+}
+
+impl WorkStealingPool {
+    /// Spawns `num_threads` background worker threads, each polling its own queue and stealing
+    /// from the others when idle.
+    pub fn new(num_threads: usize) -> WorkStealingPool {
+        let queues: Vec<_> = (0..num_threads).map(|_| sync::Arc::new(TaskQueue::new())).collect();
+
+        for (index, queue) in queues.iter().cloned().enumerate() {
+            let queues = queues.clone();
+            thread::Builder::new()
+                .name(format!("v8-background-{}", index))
+                .spawn(move || worker_loop(index, queue, queues))
+                .expect("could not spawn a V8 background worker thread");
+        }
+
+        WorkStealingPool {
+            queues: queues,
+            next: sync::atomic::AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Scheduler for WorkStealingPool {
+    fn num_background_threads(&self) -> usize {
+        self.queues.len()
+    }
+
+    fn run_on_background_thread(&self, task: Task) {
+        let index = self.next.fetch_add(1, sync::atomic::Ordering::Relaxed) % self.queues.len();
+        self.queues[index].push(task);
+    }
+}
+
+fn worker_loop(own_index: usize, own_queue: sync::Arc<TaskQueue>, queues: Vec<sync::Arc<TaskQueue>>) {
+    loop {
+        let task = {
+            let mut guard = own_queue.tasks.lock().unwrap();
+            loop {
+                if let Some(task) = guard.pop_front() {
+                    break Some(task);
+                }
+
+                let (next_guard, timeout) =
+                    own_queue.condvar.wait_timeout(guard, time::Duration::from_millis(10)).unwrap();
+                guard = next_guard;
+
+                if timeout.timed_out() {
+                    break None;
+                }
+            }
+        };
+
+        let task = task.or_else(|| {
+            for (index, queue) in queues.iter().enumerate() {
+                if index != own_index {
+                    if let Some(task) = queue.steal() {
+                        return Some(task);
+                    }
+                }
+            }
+            None
+        });
+
+        if let Some(task) = task {
+            task.run();
+        }
+    }
+}
+
 impl Platform {
+    /// Creates a platform whose background tasks run on a `WorkStealingPool` sized to the number
+    /// of available CPUs.
     pub fn new() -> Platform {
+        Platform::with_scheduler(WorkStealingPool::new(num_cpus::get()))
+    }
+
+    /// Creates a platform whose foreground and background tasks are dispatched by `scheduler`.
+    pub fn with_scheduler<S: Scheduler + 'static>(scheduler: S) -> Platform {
+        let scheduler: Box<Scheduler> = Box::new(scheduler);
+        let data = Box::into_raw(Box::new(scheduler)) as *mut os::raw::c_void;
+
         let raw = unsafe {
-            ptr::Unique::new(v8_sys::impls::CreatePlatform(
-                PLATFORM_FUNCTIONS,
-                ptr::null_mut(),
-            ))
+            ptr::Unique::new(v8_sys::impls::CreatePlatform(PLATFORM_FUNCTIONS, data))
         }.expect("could not create Platform");
 
         Platform(raw)
@@ -138,6 +283,8 @@ const PLATFORM_FUNCTIONS: v8_sys::impls::PlatformFunctions = v8_sys::impls::Plat
     Destroy: Some(destroy_platform),
     NumberOfAvailableBackgroundThreads: Some(number_of_available_background_threads),
     CallOnBackgroundThread: Some(call_on_background_thread),
+    CallOnWorkerThread: Some(call_on_worker_thread),
+    CallDelayedOnWorkerThread: Some(call_delayed_on_worker_thread),
     CallOnForegroundThread: Some(call_on_foreground_thread),
     CallDelayedOnForegroundThread: Some(call_delayed_on_foreground_thread),
     CallIdleOnForegroundThread: Some(call_idle_on_foreground_thread),
@@ -145,26 +292,30 @@ const PLATFORM_FUNCTIONS: v8_sys::impls::PlatformFunctions = v8_sys::impls::Plat
     MonotonicallyIncreasingTime: Some(monotonically_increasing_time),
 };
 
-extern "C" fn destroy_platform(_this: *mut os::raw::c_void) {
-    // No-op
+unsafe fn scheduler<'a>(this: *mut os::raw::c_void) -> &'a Scheduler {
+    &**(this as *mut Box<Scheduler>)
 }
 
-extern "C" fn number_of_available_background_threads(_this: *mut os::raw::c_void) -> usize {
-    num_cpus::get()
+extern "C" fn destroy_platform(this: *mut os::raw::c_void) {
+    unsafe {
+        drop(Box::from_raw(this as *mut Box<Scheduler>));
+    }
+}
+
+extern "C" fn number_of_available_background_threads(this: *mut os::raw::c_void) -> usize {
+    unsafe { scheduler(this).num_background_threads() }
 }
 
 extern "C" fn call_on_background_thread(
-    _this: *mut os::raw::c_void,
+    this: *mut os::raw::c_void,
     task: *mut v8_sys::Task,
     _expected_runtime: v8_sys::Platform_ExpectedRuntime,
 ) {
     let task = unsafe { Task::from_ptr(task) };
-    thread::spawn(move || unsafe {
-        v8_sys::Task_Run(task.0.as_ptr() as *mut os::raw::c_void);
-    });
+    unsafe { scheduler(this).run_on_background_thread(task) };
 }
 
-extern "C" fn call_on_foreground_thread(
+extern "C" fn call_on_worker_thread(
     _this: *mut os::raw::c_void,
     isolate: *mut v8_sys::Isolate,
     task: *mut v8_sys::Task,
@@ -172,10 +323,10 @@ extern "C" fn call_on_foreground_thread(
     let task = unsafe { Task::from_ptr(task) };
     let isolate = unsafe { isolate::Isolate::from_ptr(isolate) };
 
-    isolate.enqueue_task(task);
+    isolate.call_on_worker_thread(task);
 }
 
-extern "C" fn call_delayed_on_foreground_thread(
+extern "C" fn call_delayed_on_worker_thread(
     _this: *mut os::raw::c_void,
     isolate: *mut v8_sys::Isolate,
     task: *mut v8_sys::Task,
@@ -185,27 +336,51 @@ extern "C" fn call_delayed_on_foreground_thread(
     let isolate = unsafe { isolate::Isolate::from_ptr(isolate) };
     let duration = duration_from_seconds(delay_in_seconds);
 
-    isolate.enqueue_delayed_task(duration, task);
+    isolate.call_delayed_on_worker_thread(duration, task);
+}
+
+extern "C" fn call_on_foreground_thread(
+    this: *mut os::raw::c_void,
+    isolate: *mut v8_sys::Isolate,
+    task: *mut v8_sys::Task,
+) {
+    let task = unsafe { Task::from_ptr(task) };
+    let isolate = unsafe { isolate::Isolate::from_ptr(isolate) };
+
+    unsafe { scheduler(this).run_on_foreground_thread(&isolate, task) };
+}
+
+extern "C" fn call_delayed_on_foreground_thread(
+    this: *mut os::raw::c_void,
+    isolate: *mut v8_sys::Isolate,
+    task: *mut v8_sys::Task,
+    delay_in_seconds: f64,
+) {
+    let task = unsafe { Task::from_ptr(task) };
+    let isolate = unsafe { isolate::Isolate::from_ptr(isolate) };
+    let duration = duration_from_seconds(delay_in_seconds);
+
+    unsafe { scheduler(this).run_delayed_on_foreground_thread(&isolate, task, duration) };
 }
 
 extern "C" fn call_idle_on_foreground_thread(
-    _this: *mut os::raw::c_void,
+    this: *mut os::raw::c_void,
     isolate: *mut v8_sys::Isolate,
     idle_task: *mut v8_sys::IdleTask,
 ) {
     let idle_task = unsafe { IdleTask::from_ptr(idle_task) };
     let isolate = unsafe { isolate::Isolate::from_ptr(isolate) };
 
-    isolate.enqueue_idle_task(idle_task);
+    unsafe { scheduler(this).run_idle_on_foreground_thread(&isolate, idle_task) };
 }
 
 extern "C" fn idle_tasks_enabled(
-    _this: *mut os::raw::c_void,
+    this: *mut os::raw::c_void,
     isolate: *mut v8_sys::Isolate,
 ) -> bool {
     let isolate = unsafe { isolate::Isolate::from_ptr(isolate) };
 
-    isolate.supports_idle_tasks()
+    unsafe { scheduler(this).idle_tasks_enabled(&isolate) }
 }
 
 extern "C" fn monotonically_increasing_time(_this: *mut os::raw::c_void) -> f64 {
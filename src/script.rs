@@ -1,5 +1,7 @@
 //! Script and source code compilation, execution, origins and management.
 use v8_sys as v8;
+use std::ptr;
+use std::slice;
 
 use context;
 use error;
@@ -7,6 +9,113 @@ use isolate;
 use value;
 use util;
 
+/// An opaque, serializable cache of a script's compiled bytecode, produced by
+/// `Script::create_code_cache` and fed back in via `Script::compile_with_cache` on a later run to
+/// skip recompiling from source, mirroring V8's `ScriptCompiler::CachedData` (as consumed by e.g.
+/// rusty_v8's `script_compiler` module).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CachedData(Vec<u8>);
+
+impl CachedData {
+    /// Wraps a previously-saved cache blob, e.g. one read back from disk.
+    pub fn new(data: Vec<u8>) -> CachedData {
+        CachedData(data)
+    }
+
+    /// The raw bytes, suitable for writing to disk and feeding back into
+    /// `Script::compile_with_cache` on a later run.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Where a script's source came from: its resource name, its position within a larger file (e.g.
+/// a `<script>` block partway down an HTML document), a source map URL, and assorted V8
+/// bookkeeping flags, mirroring V8's `ScriptOrigin`.
+#[derive(Debug, Clone)]
+pub struct ScriptOrigin {
+    resource_name: value::Value,
+    resource_line_offset: i32,
+    resource_column_offset: i32,
+    script_id: i32,
+    source_map_url: Option<value::Value>,
+    is_shared_cross_origin: bool,
+    is_opaque: bool,
+    is_wasm: bool,
+    is_module: bool,
+}
+
+impl ScriptOrigin {
+    /// Creates an origin for `resource_name` with every other field at V8's defaults: zero
+    /// offsets, an auto-assigned script id, no source map, and every flag unset.
+    pub fn new(resource_name: value::Value) -> ScriptOrigin {
+        ScriptOrigin {
+            resource_name: resource_name,
+            resource_line_offset: 0,
+            resource_column_offset: 0,
+            script_id: -1,
+            source_map_url: None,
+            is_shared_cross_origin: false,
+            is_opaque: false,
+            is_wasm: false,
+            is_module: false,
+        }
+    }
+
+    /// The 0-based line within `resource_name` at which this script's source begins.
+    pub fn resource_line_offset(mut self, offset: i32) -> ScriptOrigin {
+        self.resource_line_offset = offset;
+        self
+    }
+
+    /// The 0-based column on `resource_line_offset`'s line at which this script's source begins.
+    pub fn resource_column_offset(mut self, offset: i32) -> ScriptOrigin {
+        self.resource_column_offset = offset;
+        self
+    }
+
+    /// An explicit script id to report from `StackFrame::get_script_id`, instead of letting V8
+    /// assign one.
+    pub fn script_id(mut self, script_id: i32) -> ScriptOrigin {
+        self.script_id = script_id;
+        self
+    }
+
+    /// The URL of a source map describing how this script's source maps back to the original,
+    /// pre-transpilation source.
+    pub fn source_map_url(mut self, url: value::Value) -> ScriptOrigin {
+        self.source_map_url = Some(url);
+        self
+    }
+
+    /// Marks the resource as shared cross-origin (e.g. served with CORS), making it safe to
+    /// expose thrown errors to handlers on other origins.
+    pub fn shared_cross_origin(mut self, value: bool) -> ScriptOrigin {
+        self.is_shared_cross_origin = value;
+        self
+    }
+
+    /// Marks the resource as opaque, hiding its source and stack frames from handlers on other
+    /// origins even when `shared_cross_origin` is set.
+    pub fn opaque(mut self, value: bool) -> ScriptOrigin {
+        self.is_opaque = value;
+        self
+    }
+
+    /// Marks the source as WebAssembly rather than JavaScript.
+    pub fn wasm(mut self, value: bool) -> ScriptOrigin {
+        self.is_wasm = value;
+        self
+    }
+
+    /// Marks the source as an ECMAScript module, so its top-level `import`/`export` statements
+    /// are recognized instead of being syntax errors.
+    pub fn module(mut self, value: bool) -> ScriptOrigin {
+        self.is_module = value;
+        self
+    }
+}
+
 /// A compiled JavaScript script, tied to a Context which was active when the script was compiled.
 #[derive(Debug)]
 pub struct Script(isolate::Isolate, v8::ScriptRef);
@@ -34,37 +143,102 @@ impl Script {
                              name: &value::Value,
                              source: &value::String)
                              -> error::Result<Script> {
-        use std::ptr::null_mut as n;
+        Script::compile_with_origin(isolate, context, source, &ScriptOrigin::new(name.clone()))
+    }
+
+    /// Compiles the specified source code into a compiled script, attributing it to `origin`'s
+    /// resource name, position and bookkeeping flags.
+    pub fn compile_with_origin(isolate: &isolate::Isolate,
+                               context: &context::Context,
+                               source: &value::String,
+                               origin: &ScriptOrigin)
+                               -> error::Result<Script> {
         let raw = unsafe {
             try!(util::invoke_ctx(isolate, context, |c| {
                 v8::v8_Script_Compile_Origin(c,
                                           context.as_raw(),
                                           source.as_raw(),
-                                          name.as_raw(),
-                                          n(),
-                                          n(),
-                                          n(),
-                                          n(),
-                                          n(),
-                                          n(),
-                                          n())
+                                          origin.resource_name.as_raw(),
+                                          origin.resource_line_offset,
+                                          origin.resource_column_offset,
+                                          origin.is_shared_cross_origin,
+                                          origin.script_id,
+                                          origin.source_map_url
+                                              .as_ref()
+                                              .map(|u| u.as_raw())
+                                              .unwrap_or(ptr::null_mut()),
+                                          origin.is_opaque,
+                                          origin.is_wasm,
+                                          origin.is_module)
             }))
         };
         Ok(Script(isolate.clone(), raw))
     }
 
+    /// Compiles the specified source code, consuming a `CachedData` blob previously produced by
+    /// `create_code_cache` to skip re-parsing/re-compiling `source` where possible.
+    ///
+    /// Returns the compiled script together with whether `cache` was rejected (e.g. because
+    /// `source` has changed since the cache was produced, or it came from an incompatible V8
+    /// build). A rejected cache is not an error: the script is compiled from source as normal.
+    pub fn compile_with_cache(isolate: &isolate::Isolate,
+                              context: &context::Context,
+                              source: &value::String,
+                              cache: &CachedData)
+                              -> error::Result<(Script, bool)> {
+        let result = unsafe {
+            try!(util::invoke_ctx(isolate, context, |c| {
+                v8::v8_Script_Compile_WithCachedData(c,
+                                                     context.as_raw(),
+                                                     source.as_raw(),
+                                                     cache.0.as_ptr(),
+                                                     cache.0.len())
+            }))
+        };
+        Ok((Script(isolate.clone(), result.script), result.cached_data_rejected != 0))
+    }
+
+    /// Compiles `source`, attributing it to `name`, and immediately produces a `CachedData` blob
+    /// capturing the result, so later runs (in this isolate or another one entirely) can skip
+    /// recompiling from source via `compile_with_cache`.
+    pub fn compile_and_cache(isolate: &isolate::Isolate,
+                             context: &context::Context,
+                             name: &value::Value,
+                             source: &value::String)
+                             -> error::Result<(Script, CachedData)> {
+        let script = try!(Script::compile_with_name(isolate, context, name, source));
+        let cache = try!(script.create_code_cache());
+        Ok((script, cache))
+    }
+
+    /// Produces a `CachedData` blob capturing this script's compiled bytecode, which a later
+    /// process can pass to `compile_with_cache` alongside the same `source` to skip recompiling.
+    pub fn create_code_cache(&self) -> error::Result<CachedData> {
+        unsafe {
+            let bytes = try!(util::invoke(&self.0,
+                                          |c| v8::v8_Script_CreateCodeCache(c, self.1)));
+            let data = slice::from_raw_parts(bytes.data as *const u8, bytes.length).to_vec();
+            v8::v8_Bytes_Free(bytes);
+            Ok(CachedData(data))
+        }
+    }
+
     /// Runs this script in the specified context.
     ///
     /// If the script returns a value, meaning that the last line of the script evaluates to an
     /// expression or there is an explicit return, that value will be returned from this method.  If
     /// the script throws an exception, that will reslt in this method also throwing an exception.
+    ///
+    /// If the isolate was configured with `Isolate::set_execution_timeout`/`set_heap_limit`, or
+    /// `Isolate::cancel` is called from another thread while this is running, this returns
+    /// `error::ErrorKind::Terminated`/`error::ErrorKind::OutOfMemory` instead.
     pub fn run(&self, context: &context::Context) -> error::Result<value::Value> {
-        unsafe {
+        self.0.run_guarded(|| unsafe {
             let raw = try!(util::invoke_ctx(&self.0,
                                             context,
                                             |c| v8::v8_Script_Run(c, self.1, context.as_raw())));
             Ok(value::Value::from_raw(&self.0, raw))
-        }
+        })
     }
 }
 
@@ -0,0 +1,543 @@
+//! Optional `serde` integration.
+//!
+//! Converts arbitrary `Serialize` values directly into V8 `value::Object`/`value::Array` trees
+//! (rather than through an intermediate Rust value, the way `serde_json::Value` would), and the
+//! reverse, so embedders can do `let obj = v8::serde_value::to_value(&isolate, &context,
+//! &my_struct)?;` and `let cfg: Config = v8::serde_value::from_value(&context, &result)?;` instead
+//! of hand-walking object keys the way the tests in `lib.rs` do.
+
+use serde;
+use context;
+use error;
+use isolate;
+use value;
+use value::{FromValue, ToValue};
+
+/// Serializes `v` into a new value living in `context`.
+pub fn to_value<T>(isolate: &isolate::Isolate, context: &context::Context, v: &T) -> error::Result<value::Value>
+    where T: serde::Serialize
+{
+    v.serialize(Serializer {
+        isolate: isolate,
+        context: context,
+    })
+}
+
+/// Deserializes `value` into a `T`.
+pub fn from_value<T>(context: &context::Context, value: &value::Value) -> error::Result<T>
+    where T: serde::Deserialize
+{
+    T::deserialize(Deserializer {
+        context: context,
+        value: value.clone(),
+    })
+}
+
+impl serde::ser::Error for error::Error {
+    fn custom<T: ::std::fmt::Display>(msg: T) -> error::Error {
+        error::ErrorKind::Serde(msg.to_string()).into()
+    }
+}
+
+impl serde::de::Error for error::Error {
+    fn custom<T: ::std::fmt::Display>(msg: T) -> error::Error {
+        error::ErrorKind::Serde(msg.to_string()).into()
+    }
+}
+
+/// Drives `Serialize::serialize`, turning a Rust value into a V8 value.
+struct Serializer<'i, 'c> {
+    isolate: &'i isolate::Isolate,
+    context: &'c context::Context,
+}
+
+impl<'i, 'c> Copy for Serializer<'i, 'c> {}
+impl<'i, 'c> Clone for Serializer<'i, 'c> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'i, 'c> serde::Serializer for Serializer<'i, 'c> {
+    type Ok = value::Value;
+    type Error = error::Error;
+
+    type SerializeSeq = SerializeArray<'i, 'c>;
+    type SerializeTuple = SerializeArray<'i, 'c>;
+    type SerializeTupleStruct = SerializeArray<'i, 'c>;
+    type SerializeTupleVariant = SerializeVariantArray<'i, 'c>;
+    type SerializeMap = SerializeMapState<'i, 'c>;
+    type SerializeStruct = SerializeStructState<'i, 'c>;
+    type SerializeStructVariant = SerializeVariantStruct<'i, 'c>;
+
+    fn serialize_bool(self, v: bool) -> error::Result<value::Value> {
+        Ok(v.to_value(self.isolate))
+    }
+
+    fn serialize_i8(self, v: i8) -> error::Result<value::Value> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_i16(self, v: i16) -> error::Result<value::Value> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_i32(self, v: i32) -> error::Result<value::Value> {
+        Ok(v.to_value(self.isolate))
+    }
+
+    fn serialize_i64(self, v: i64) -> error::Result<value::Value> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u8(self, v: u8) -> error::Result<value::Value> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u16(self, v: u16) -> error::Result<value::Value> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u32(self, v: u32) -> error::Result<value::Value> {
+        Ok(v.to_value(self.isolate))
+    }
+
+    fn serialize_u64(self, v: u64) -> error::Result<value::Value> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f32(self, v: f32) -> error::Result<value::Value> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> error::Result<value::Value> {
+        Ok(v.to_value(self.isolate))
+    }
+
+    fn serialize_char(self, v: char) -> error::Result<value::Value> {
+        self.serialize_str(&v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> error::Result<value::Value> {
+        Ok(v.to_value(self.isolate))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> error::Result<value::Value> {
+        let array = value::Array::new(self.isolate, self.context, v.len() as u32);
+        for (i, byte) in v.iter().enumerate() {
+            array.set_index(self.context, i as u32, &(*byte as u32).to_value(self.isolate));
+        }
+        Ok(array.into())
+    }
+
+    fn serialize_none(self) -> error::Result<value::Value> {
+        Ok(value::undefined(self.isolate).into())
+    }
+
+    fn serialize_some<T: ?Sized>(self, value: &T) -> error::Result<value::Value>
+        where T: serde::Serialize
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> error::Result<value::Value> {
+        Ok(value::undefined(self.isolate).into())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> error::Result<value::Value> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self,
+                               _name: &'static str,
+                               _variant_index: usize,
+                               variant: &'static str)
+                               -> error::Result<value::Value> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized>(self, _name: &'static str, value: &T) -> error::Result<value::Value>
+        where T: serde::Serialize
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized>(self,
+                                             _name: &'static str,
+                                             _variant_index: usize,
+                                             variant: &'static str,
+                                             value: &T)
+                                             -> error::Result<value::Value>
+        where T: serde::Serialize
+    {
+        let object = value::Object::new(self.isolate, self.context);
+        let inner = try!(value.serialize(self));
+        object.set(self.context, &variant.to_value(self.isolate), &inner);
+        Ok(object.into())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> error::Result<SerializeArray<'i, 'c>> {
+        let array = value::Array::new(self.isolate, self.context, len.unwrap_or(0) as u32);
+        Ok(SerializeArray {
+            serializer: self,
+            array: array,
+            next_index: 0,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> error::Result<SerializeArray<'i, 'c>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self,
+                               _name: &'static str,
+                               len: usize)
+                               -> error::Result<SerializeArray<'i, 'c>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(self,
+                                _name: &'static str,
+                                _variant_index: usize,
+                                variant: &'static str,
+                                len: usize)
+                                -> error::Result<SerializeVariantArray<'i, 'c>> {
+        let array = value::Array::new(self.isolate, self.context, len as u32);
+        Ok(SerializeVariantArray {
+            serializer: self,
+            variant: variant,
+            array: array,
+            next_index: 0,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> error::Result<SerializeMapState<'i, 'c>> {
+        Ok(SerializeMapState {
+            serializer: self,
+            object: value::Object::new(self.isolate, self.context),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self,
+                         _name: &'static str,
+                         _len: usize)
+                         -> error::Result<SerializeStructState<'i, 'c>> {
+        Ok(SerializeStructState {
+            serializer: self,
+            object: value::Object::new(self.isolate, self.context),
+        })
+    }
+
+    fn serialize_struct_variant(self,
+                                 _name: &'static str,
+                                 _variant_index: usize,
+                                 variant: &'static str,
+                                 _len: usize)
+                                 -> error::Result<SerializeVariantStruct<'i, 'c>> {
+        Ok(SerializeVariantStruct {
+            serializer: self,
+            variant: variant,
+            object: value::Object::new(self.isolate, self.context),
+        })
+    }
+}
+
+struct SerializeArray<'i, 'c> {
+    serializer: Serializer<'i, 'c>,
+    array: value::Array,
+    next_index: u32,
+}
+
+impl<'i, 'c> SerializeArray<'i, 'c> {
+    fn push<T: ?Sized>(&mut self, value: &T) -> error::Result<()>
+        where T: serde::Serialize
+    {
+        let element = try!(value.serialize(self.serializer));
+        self.array.set_index(self.serializer.context, self.next_index, &element);
+        self.next_index += 1;
+        Ok(())
+    }
+}
+
+impl<'i, 'c> serde::ser::SerializeSeq for SerializeArray<'i, 'c> {
+    type Ok = value::Value;
+    type Error = error::Error;
+
+    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> error::Result<()>
+        where T: serde::Serialize
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> error::Result<value::Value> {
+        Ok(self.array.into())
+    }
+}
+
+impl<'i, 'c> serde::ser::SerializeTuple for SerializeArray<'i, 'c> {
+    type Ok = value::Value;
+    type Error = error::Error;
+
+    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> error::Result<()>
+        where T: serde::Serialize
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> error::Result<value::Value> {
+        Ok(self.array.into())
+    }
+}
+
+impl<'i, 'c> serde::ser::SerializeTupleStruct for SerializeArray<'i, 'c> {
+    type Ok = value::Value;
+    type Error = error::Error;
+
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> error::Result<()>
+        where T: serde::Serialize
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> error::Result<value::Value> {
+        Ok(self.array.into())
+    }
+}
+
+struct SerializeVariantArray<'i, 'c> {
+    serializer: Serializer<'i, 'c>,
+    variant: &'static str,
+    array: value::Array,
+    next_index: u32,
+}
+
+impl<'i, 'c> serde::ser::SerializeTupleVariant for SerializeVariantArray<'i, 'c> {
+    type Ok = value::Value;
+    type Error = error::Error;
+
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> error::Result<()>
+        where T: serde::Serialize
+    {
+        let element = try!(value.serialize(self.serializer));
+        self.array.set_index(self.serializer.context, self.next_index, &element);
+        self.next_index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> error::Result<value::Value> {
+        let object = value::Object::new(self.serializer.isolate, self.serializer.context);
+        object.set(self.serializer.context,
+                   &self.variant.to_value(self.serializer.isolate),
+                   &self.array.into());
+        Ok(object.into())
+    }
+}
+
+struct SerializeMapState<'i, 'c> {
+    serializer: Serializer<'i, 'c>,
+    object: value::Object,
+    next_key: Option<value::Value>,
+}
+
+impl<'i, 'c> serde::ser::SerializeMap for SerializeMapState<'i, 'c> {
+    type Ok = value::Value;
+    type Error = error::Error;
+
+    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> error::Result<()>
+        where T: serde::Serialize
+    {
+        self.next_key = Some(try!(key.serialize(self.serializer)));
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> error::Result<()>
+        where T: serde::Serialize
+    {
+        let key = self.next_key.take().expect("serialize_value called before serialize_key");
+        let value = try!(value.serialize(self.serializer));
+        self.object.set(self.serializer.context, &key, &value);
+        Ok(())
+    }
+
+    fn end(self) -> error::Result<value::Value> {
+        Ok(self.object.into())
+    }
+}
+
+struct SerializeStructState<'i, 'c> {
+    serializer: Serializer<'i, 'c>,
+    object: value::Object,
+}
+
+impl<'i, 'c> serde::ser::SerializeStruct for SerializeStructState<'i, 'c> {
+    type Ok = value::Value;
+    type Error = error::Error;
+
+    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> error::Result<()>
+        where T: serde::Serialize
+    {
+        let value = try!(value.serialize(self.serializer));
+        self.object.set(self.serializer.context, &key.to_value(self.serializer.isolate), &value);
+        Ok(())
+    }
+
+    fn end(self) -> error::Result<value::Value> {
+        Ok(self.object.into())
+    }
+}
+
+struct SerializeVariantStruct<'i, 'c> {
+    serializer: Serializer<'i, 'c>,
+    variant: &'static str,
+    object: value::Object,
+}
+
+impl<'i, 'c> serde::ser::SerializeStructVariant for SerializeVariantStruct<'i, 'c> {
+    type Ok = value::Value;
+    type Error = error::Error;
+
+    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> error::Result<()>
+        where T: serde::Serialize
+    {
+        let value = try!(value.serialize(self.serializer));
+        self.object.set(self.serializer.context, &key.to_value(self.serializer.isolate), &value);
+        Ok(())
+    }
+
+    fn end(self) -> error::Result<value::Value> {
+        let outer = value::Object::new(self.serializer.isolate, self.serializer.context);
+        outer.set(self.serializer.context,
+                  &self.variant.to_value(self.serializer.isolate),
+                  &self.object.into());
+        Ok(outer.into())
+    }
+}
+
+/// Drives `Deserialize::deserialize`, turning a V8 value into a Rust value.
+struct Deserializer<'c> {
+    context: &'c context::Context,
+    value: value::Value,
+}
+
+impl<'c> serde::Deserializer for Deserializer<'c> {
+    type Error = error::Error;
+
+    fn deserialize<V>(self, visitor: V) -> error::Result<V::Value>
+        where V: serde::de::Visitor
+    {
+        if self.value.is_undefined() || self.value.is_null() {
+            visitor.visit_unit()
+        } else if self.value.is_true() {
+            visitor.visit_bool(true)
+        } else if self.value.is_false() {
+            visitor.visit_bool(false)
+        } else if self.value.is_number() {
+            visitor.visit_f64(self.value.number_value(self.context))
+        } else if self.value.is_string() {
+            let s = try!(::std::string::String::from_value(self.context, &self.value)
+                .map_err(|_| error::ErrorKind::Serde("expected a string".to_string())));
+            visitor.visit_string(s)
+        } else if self.value.is_array() {
+            let array = self.value
+                .clone()
+                .into_array()
+                .expect("is_array returned true");
+            let len = array.length();
+            let mut elements = Vec::with_capacity(len as usize);
+            for i in 0..len {
+                elements.push(array.get_index(self.context, i));
+            }
+            visitor.visit_seq(SeqAccess {
+                context: self.context,
+                iter: elements.into_iter(),
+            })
+        } else if self.value.is_object() {
+            let object = self.value
+                .clone()
+                .into_object()
+                .expect("is_object returned true");
+            let keys = object.get_own_property_names(self.context);
+            let len = keys.length();
+            let mut entries = Vec::with_capacity(len as usize);
+            for i in 0..len {
+                let key = keys.get_index(self.context, i);
+                let value = object.get(self.context, &key);
+                entries.push((key, value));
+            }
+            visitor.visit_map(MapAccess {
+                context: self.context,
+                iter: entries.into_iter(),
+                value: None,
+            })
+        } else {
+            Err(error::ErrorKind::Serde("value of unsupported type".to_string()).into())
+        }
+    }
+
+    forward_to_deserialize! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit option
+        seq seq_fixed_size bytes map unit_struct newtype_struct tuple_struct
+        struct struct_field tuple ignored_any enum
+    }
+}
+
+struct SeqAccess<'c> {
+    context: &'c context::Context,
+    iter: ::std::vec::IntoIter<value::Value>,
+}
+
+impl<'c> serde::de::SeqAccess for SeqAccess<'c> {
+    type Error = error::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> error::Result<Option<T::Value>>
+        where T: serde::de::DeserializeSeed
+    {
+        match self.iter.next() {
+            Some(value) => {
+                seed.deserialize(Deserializer {
+                        context: self.context,
+                        value: value,
+                    })
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+struct MapAccess<'c> {
+    context: &'c context::Context,
+    iter: ::std::vec::IntoIter<(value::Value, value::Value)>,
+    value: Option<value::Value>,
+}
+
+impl<'c> serde::de::MapAccess for MapAccess<'c> {
+    type Error = error::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> error::Result<Option<K::Value>>
+        where K: serde::de::DeserializeSeed
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(Deserializer {
+                        context: self.context,
+                        value: key,
+                    })
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> error::Result<V::Value>
+        where V: serde::de::DeserializeSeed
+    {
+        let value = self.value.take().expect("next_value_seed called before next_key_seed");
+        seed.deserialize(Deserializer {
+            context: self.context,
+            value: value,
+        })
+    }
+}
@@ -0,0 +1,349 @@
+//! Structured-clone-style (de)serialization of `Value` graphs to/from a flat byte buffer, loosely
+//! mirroring V8's own `ValueSerializer`/`ValueDeserializer`.
+//!
+//! Unlike `serde_value`, which bridges to/from Rust's `Serialize`/`Deserialize` traits, this
+//! module round-trips a `Value` graph on its own terms: an `Object` or `Array` referenced more
+//! than once (including one that cycles back to itself) is written once and every later
+//! occurrence becomes a back-reference, the way `postMessage`/`structuredClone` behave in a
+//! browser.
+
+use std::collections::HashMap;
+use context;
+use error;
+use isolate;
+use value;
+use value::ToValue;
+
+const TAG_UNDEFINED: u8 = 0;
+const TAG_NULL: u8 = 1;
+const TAG_FALSE: u8 = 2;
+const TAG_TRUE: u8 = 3;
+const TAG_NUMBER: u8 = 4;
+const TAG_STRING: u8 = 5;
+const TAG_OBJECT: u8 = 6;
+const TAG_ARRAY: u8 = 7;
+const TAG_REF: u8 = 8;
+const TAG_HOST_OBJECT: u8 = 9;
+
+/// A hook for values that `Serializer`/`Deserializer` don't know how to handle on their own, e.g.
+/// a native wrapper object exposing internal fields.  Mirrors V8's own
+/// `ValueSerializer::Delegate`/`ValueDeserializer::Delegate` pair, minus the parts (Wasm modules,
+/// `SharedArrayBuffer` transfer) this chunk doesn't support yet.
+///
+/// The default implementation refuses every host object, matching `write_value`/`read_value`'s
+/// behavior when no delegate is supplied at all.
+pub trait Delegate {
+    /// Writes `object` (an object with at least one internal field) into `out`.  Returns
+    /// an error to fail the whole `write_value` call, the same as V8 raising a
+    /// `DataCloneError` would.
+    fn write_host_object(&mut self,
+                         _isolate: &isolate::Isolate,
+                         _object: &value::Object,
+                         _out: &mut Vec<u8>)
+                         -> error::Result<()> {
+        Err(error::ErrorKind::DataCloneError("host object").into())
+    }
+
+    /// Reads back an object previously written by `write_host_object`.
+    fn read_host_object(&mut self,
+                        _isolate: &isolate::Isolate,
+                        _context: &context::Context,
+                        _data: &mut Reader)
+                        -> error::Result<value::Object> {
+        Err(error::ErrorKind::DataCloneError("host object").into())
+    }
+}
+
+/// Encodes a `Value` graph into a flat `Vec<u8>`.
+///
+/// Every `Object`/`Array` is tracked by identity: `seen` is keyed by `value::Object` itself, whose
+/// `Eq` is `strict_equals` and whose `Hash` is `get_identity_hash` (a small, collidable hash that
+/// only picks the bucket, so a collision never conflates two distinct objects). A value
+/// referenced more than once (directly, or by way of a cycle) is written out only the first
+/// time; later occurrences become a cheap back-reference instead of being duplicated or causing
+/// infinite recursion.
+pub struct Serializer<'i, 'c, 'd> {
+    isolate: &'i isolate::Isolate,
+    context: &'c context::Context,
+    delegate: Option<&'d mut Delegate>,
+    seen: HashMap<value::Object, u32>,
+}
+
+impl<'i, 'c> Serializer<'i, 'c, 'static> {
+    /// Creates a serializer that refuses any host object it encounters, the same as V8 does when a
+    /// `ValueSerializer` is constructed without a `Delegate`.
+    pub fn new(isolate: &'i isolate::Isolate, context: &'c context::Context) -> Serializer<'i, 'c, 'static> {
+        Serializer {
+            isolate: isolate,
+            context: context,
+            delegate: None,
+            seen: HashMap::new(),
+        }
+    }
+}
+
+impl<'i, 'c, 'd> Serializer<'i, 'c, 'd> {
+    /// Creates a serializer that hands every object with internal fields off to `delegate`.
+    pub fn with_delegate(isolate: &'i isolate::Isolate,
+                         context: &'c context::Context,
+                         delegate: &'d mut Delegate)
+                         -> Serializer<'i, 'c, 'd> {
+        Serializer {
+            isolate: isolate,
+            context: context,
+            delegate: Some(delegate),
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Serializes `value`, returning the encoded buffer.
+    pub fn write_value(&mut self, value: &value::Value) -> error::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.write(&mut out, value)?;
+        Ok(out)
+    }
+
+    fn write(&mut self, out: &mut Vec<u8>, value: &value::Value) -> error::Result<()> {
+        match value.classify() {
+            value::ValueKind::Undefined => out.push(TAG_UNDEFINED),
+            value::ValueKind::Null => out.push(TAG_NULL),
+            value::ValueKind::Boolean => {
+                let b = value.clone().into_boolean().unwrap().value();
+                out.push(if b { TAG_TRUE } else { TAG_FALSE });
+            }
+            value::ValueKind::Number => {
+                let n = value.clone().into_number().unwrap().value();
+                out.push(TAG_NUMBER);
+                out.extend_from_slice(&n.to_le_bytes());
+            }
+            value::ValueKind::String => {
+                let s = value.clone().into_string().unwrap().value();
+                out.push(TAG_STRING);
+                write_bytes(out, s.as_bytes());
+            }
+            value::ValueKind::Symbol => {
+                return Err(error::ErrorKind::DataCloneError("symbol").into());
+            }
+            value::ValueKind::Array => {
+                let array = value.clone().into_array().unwrap();
+                if let Some(id) = self.back_reference(&array) {
+                    out.push(TAG_REF);
+                    out.extend_from_slice(&id.to_le_bytes());
+                    return Ok(());
+                }
+                out.push(TAG_ARRAY);
+                let length = array.length();
+                out.extend_from_slice(&length.to_le_bytes());
+                for i in 0..length {
+                    let element = array.get_index(self.context, i);
+                    self.write(out, &element)?;
+                }
+            }
+            value::ValueKind::Object => {
+                let object = value.clone().into_object().unwrap();
+                if let Some(id) = self.back_reference(&object) {
+                    out.push(TAG_REF);
+                    out.extend_from_slice(&id.to_le_bytes());
+                    return Ok(());
+                }
+                if object.internal_field_count() > 0 {
+                    out.push(TAG_HOST_OBJECT);
+                    match self.delegate {
+                        Some(ref mut delegate) => delegate.write_host_object(self.isolate, &object, out)?,
+                        None => return Err(error::ErrorKind::DataCloneError("host object").into()),
+                    }
+                    return Ok(());
+                }
+                out.push(TAG_OBJECT);
+                let keys = object.get_own_property_names(self.context);
+                let count = keys.length();
+                out.extend_from_slice(&count.to_le_bytes());
+                for i in 0..count {
+                    let key = keys.get_index(self.context, i);
+                    let property = object.get(self.context, &key);
+                    self.write(out, &key)?;
+                    self.write(out, &property)?;
+                }
+            }
+            value::ValueKind::Other => {
+                let object = value.clone().into_object()
+                    .ok_or_else(|| error::Error::from(error::ErrorKind::DataCloneError("non-object value")))?;
+                if let Some(id) = self.back_reference(&object) {
+                    out.push(TAG_REF);
+                    out.extend_from_slice(&id.to_le_bytes());
+                    return Ok(());
+                }
+                out.push(TAG_HOST_OBJECT);
+                match self.delegate {
+                    Some(ref mut delegate) => delegate.write_host_object(self.isolate, &object, out)?,
+                    None => return Err(error::ErrorKind::DataCloneError("host object").into()),
+                }
+            }
+            _ => {
+                return Err(error::ErrorKind::DataCloneError("unsupported value").into());
+            }
+        }
+        Ok(())
+    }
+
+    /// If `object` has already been written, returns the id its first occurrence was assigned;
+    /// otherwise reserves a fresh id for it (so a cycle back to `object` resolves correctly even
+    /// before this call returns) and returns `None`.
+    fn back_reference(&mut self, object: &value::Object) -> Option<u32> {
+        if let Some(&id) = self.seen.get(object) {
+            return Some(id);
+        }
+        let id = self.seen.len() as u32;
+        self.seen.insert(object.clone(), id);
+        None
+    }
+}
+
+/// Decodes a `Value` graph previously produced by `Serializer::write_value`.
+pub struct Deserializer<'i, 'c, 'd, 'b> {
+    isolate: &'i isolate::Isolate,
+    context: &'c context::Context,
+    delegate: Option<&'d mut Delegate>,
+    reader: Reader<'b>,
+    seen: Vec<value::Value>,
+}
+
+impl<'i, 'c, 'b> Deserializer<'i, 'c, 'static, 'b> {
+    /// Creates a deserializer that errors out on any host object, the same as `Serializer::new`.
+    pub fn new(isolate: &'i isolate::Isolate,
+              context: &'c context::Context,
+              data: &'b [u8])
+              -> Deserializer<'i, 'c, 'static, 'b> {
+        Deserializer {
+            isolate: isolate,
+            context: context,
+            delegate: None,
+            reader: Reader::new(data),
+            seen: Vec::new(),
+        }
+    }
+}
+
+impl<'i, 'c, 'd, 'b> Deserializer<'i, 'c, 'd, 'b> {
+    pub fn with_delegate(isolate: &'i isolate::Isolate,
+                         context: &'c context::Context,
+                         data: &'b [u8],
+                         delegate: &'d mut Delegate)
+                         -> Deserializer<'i, 'c, 'd, 'b> {
+        Deserializer {
+            isolate: isolate,
+            context: context,
+            delegate: Some(delegate),
+            reader: Reader::new(data),
+            seen: Vec::new(),
+        }
+    }
+
+    /// Reads a single value out of the buffer.
+    pub fn read_value(&mut self) -> error::Result<value::Value> {
+        let tag = self.reader.read_u8()?;
+        match tag {
+            TAG_UNDEFINED => Ok(value::undefined(self.isolate).into()),
+            TAG_NULL => Ok(value::null(self.isolate).into()),
+            TAG_FALSE => Ok(value::false_(self.isolate).into()),
+            TAG_TRUE => Ok(value::true_(self.isolate).into()),
+            TAG_NUMBER => {
+                let n = self.reader.read_f64()?;
+                Ok(value::Number::new(self.isolate, n).into())
+            }
+            TAG_STRING => {
+                let bytes = self.reader.read_bytes()?;
+                let s = String::from_utf8_lossy(bytes).into_owned();
+                Ok(s.to_value(self.isolate))
+            }
+            TAG_ARRAY => {
+                let length = self.reader.read_u32()?;
+                let array = value::Array::new(self.isolate, self.context, length);
+                self.seen.push(array.clone().into());
+                for i in 0..length {
+                    let element = self.read_value()?;
+                    array.set_index(self.context, i, &element);
+                }
+                Ok(array.into())
+            }
+            TAG_OBJECT => {
+                let count = self.reader.read_u32()?;
+                let object = value::Object::new(self.isolate, self.context);
+                self.seen.push(object.clone().into());
+                for _ in 0..count {
+                    let key = self.read_value()?;
+                    let property = self.read_value()?;
+                    object.set(self.context, &key, &property);
+                }
+                Ok(object.into())
+            }
+            TAG_REF => {
+                let id = self.reader.read_u32()?;
+                self.seen
+                    .get(id as usize)
+                    .cloned()
+                    .ok_or_else(|| error::ErrorKind::TruncatedCloneData.into())
+            }
+            TAG_HOST_OBJECT => {
+                let isolate = self.isolate;
+                let context = self.context;
+                let reader = &mut self.reader;
+                let object = match self.delegate {
+                    Some(ref mut delegate) => delegate.read_host_object(isolate, context, reader)?,
+                    None => return Err(error::ErrorKind::DataCloneError("host object").into()),
+                };
+                self.seen.push(object.clone().into());
+                Ok(object.into())
+            }
+            _ => Err(error::ErrorKind::TruncatedCloneData.into()),
+        }
+    }
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// A cursor over a `Deserializer`'s input buffer, also handed to `Delegate::read_host_object` so
+/// it can read back whatever raw data its matching `write_host_object` wrote.
+pub struct Reader<'b> {
+    data: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn new(data: &'b [u8]) -> Reader<'b> {
+        Reader { data: data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> error::Result<&'b [u8]> {
+        if self.pos + len > self.data.len() {
+            return Err(error::ErrorKind::TruncatedCloneData.into());
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    pub fn read_u8(&mut self) -> error::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> error::Result<u32> {
+        let mut array = [0u8; 4];
+        array.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(array))
+    }
+
+    pub fn read_f64(&mut self) -> error::Result<f64> {
+        let mut array = [0u8; 8];
+        array.copy_from_slice(self.take(8)?);
+        Ok(f64::from_le_bytes(array))
+    }
+
+    pub fn read_bytes(&mut self) -> error::Result<&'b [u8]> {
+        let len = self.read_u32()? as usize;
+        self.take(len)
+    }
+}
@@ -0,0 +1,140 @@
+//! Startup snapshots: warming up an isolate's heap once, serializing it to a blob, and
+//! deserializing that blob on every later `Isolate` creation instead of re-parsing and
+//! re-compiling V8's built-ins from scratch, mirroring V8's `v8::SnapshotCreator`.
+use v8_sys;
+
+use std::fmt;
+use std::os;
+use std::ptr;
+use std::slice;
+
+use context;
+use handle;
+use isolate;
+
+/// Whether `SnapshotCreator::create_blob` should keep the bytecode it already compiled for the
+/// contexts added to the snapshot, or discard it, mirroring V8's
+/// `SnapshotCreator::FunctionCodeHandling`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FunctionCodeHandling {
+    /// Discard compiled bytecode; functions are re-compiled lazily the first time they run again,
+    /// keeping the blob smaller.
+    Clear,
+    /// Keep compiled bytecode in the blob, trading a larger blob for a faster first run.
+    Keep,
+}
+
+impl FunctionCodeHandling {
+    fn as_raw(self) -> v8_sys::SnapshotCreator_FunctionCodeHandling {
+        match self {
+            FunctionCodeHandling::Clear => v8_sys::SnapshotCreator_FunctionCodeHandling::kClear,
+            FunctionCodeHandling::Keep => v8_sys::SnapshotCreator_FunctionCodeHandling::kKeep,
+        }
+    }
+}
+
+/// A serialized V8 heap snapshot, produced by `SnapshotCreator::create_blob` and consumed by
+/// `isolate::Builder::snapshot_blob` to give a freshly built isolate a pre-warmed heap.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StartupData(Vec<u8>);
+
+impl StartupData {
+    /// Wraps a previously-saved blob, e.g. one read back from disk.
+    pub fn new(data: Vec<u8>) -> StartupData {
+        StartupData(data)
+    }
+
+    /// The raw bytes, suitable for writing to disk and feeding back into
+    /// `isolate::Builder::snapshot_blob` on a later run.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Builds a throwaway isolate, lets the embedder warm it up by creating one or more contexts and
+/// running setup scripts in them, then serializes the resulting heap into a `StartupData` blob
+/// that a later process can feed to `isolate::Builder::snapshot_blob` for near-instant isolate
+/// spin-up, which matters for a per-request-isolate embedding pattern.
+///
+/// `external_references` must list every native function pointer that a context added to this
+/// snapshot may reference from a `template::FunctionTemplate` callback (terminated by a trailing
+/// `0`, as V8 requires), so those callbacks can be serialized as stable indices into that list
+/// instead of raw addresses that would be meaningless once deserialized into a different process.
+pub struct SnapshotCreator(ptr::Unique<v8_sys::SnapshotCreator>);
+
+impl SnapshotCreator {
+    /// Creates a new snapshot creator, along with the isolate it should be warmed up on (see
+    /// `isolate()`).
+    pub fn new(external_references: &'static [isize]) -> SnapshotCreator {
+        let raw = unsafe {
+            ptr::Unique::new(v8_sys::SnapshotCreator::New(external_references.as_ptr()))
+        }.expect("could not create SnapshotCreator");
+
+        SnapshotCreator(raw)
+    }
+
+    /// The isolate that this snapshot creator built for the embedder to warm up.
+    ///
+    /// Enter it (`isolate.scope()`) to create contexts and run setup scripts in them, the same way
+    /// an ordinary isolate would be used, before calling `set_default_context`/`add_context` and
+    /// `create_blob`.
+    pub fn isolate(&self) -> isolate::Isolate {
+        unsafe { isolate::Isolate::from_ptr(self.0.as_ref().GetIsolate()) }
+    }
+
+    /// Sets `context` as the context that a later `Isolate::builder().snapshot_blob(blob).build()`
+    /// will deserialize by default.
+    pub fn set_default_context<'i, 's>(&mut self, context: handle::Local<'i, 's, context::Context>) {
+        unsafe {
+            self.0.as_mut().SetDefaultContext(
+                context.into_raw(),
+                v8_sys::SerializeInternalFieldsCallback {
+                    callback: None,
+                    data: ptr::null_mut(),
+                },
+            );
+        }
+    }
+
+    /// Adds `context` as an additional context to the snapshot, returning the index it can later be
+    /// recovered at.
+    pub fn add_context<'i, 's>(&mut self, context: handle::Local<'i, 's, context::Context>) -> usize {
+        unsafe {
+            self.0.as_mut().AddContext(
+                context.into_raw(),
+                v8_sys::SerializeInternalFieldsCallback {
+                    callback: None,
+                    data: ptr::null_mut(),
+                },
+            )
+        }
+    }
+
+    /// Serializes every context added so far (and the default context, if one was set) into a
+    /// `StartupData` blob, consuming this snapshot creator and its isolate.
+    pub fn create_blob(self, function_code_handling: FunctionCodeHandling) -> StartupData {
+        unsafe {
+            let bytes = v8_sys::v8_SnapshotCreator_CreateBlob(
+                self.0.as_ptr(),
+                function_code_handling.as_raw(),
+            );
+            let data = slice::from_raw_parts(bytes.data as *const u8, bytes.length).to_vec();
+            v8_sys::v8_Bytes_Free(bytes);
+            StartupData(data)
+        }
+    }
+}
+
+impl fmt::Debug for SnapshotCreator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SnapshotCreator({:?})", unsafe { self.0.as_ref() })
+    }
+}
+
+impl Drop for SnapshotCreator {
+    fn drop(&mut self) {
+        unsafe {
+            v8_sys::SnapshotCreator_SnapshotCreator_destructor(self.0.as_ptr());
+        }
+    }
+}
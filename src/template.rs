@@ -30,6 +30,96 @@ pub struct FunctionTemplate(v8_sys::FunctionTemplate);
 #[derive(Debug)]
 pub struct ObjectTemplate(v8_sys::ObjectTemplate);
 
+/// The set of boxed closures `ObjectTemplate::set_named_property_handler` registers to intercept
+/// `obj.foo`/`obj["foo"]`-style access on objects instantiated from a template. Each field left
+/// `None` falls through to V8's normal property storage for that operation.
+#[derive(Default)]
+pub struct NamedPropertyHandler {
+    pub getter: Option<Box<value::NamedPropertyGetter>>,
+    pub setter: Option<Box<value::NamedPropertySetter>>,
+    pub query: Option<Box<value::NamedPropertyQuery>>,
+    pub deleter: Option<Box<value::NamedPropertyDeleter>>,
+    pub enumerator: Option<Box<value::NamedPropertyEnumerator>>,
+}
+
+impl NamedPropertyHandler {
+    /// Creates a handler that intercepts none of the named-property operations.
+    pub fn new() -> NamedPropertyHandler {
+        NamedPropertyHandler::default()
+    }
+
+    pub fn getter(mut self, getter: Box<value::NamedPropertyGetter>) -> NamedPropertyHandler {
+        self.getter = Some(getter);
+        self
+    }
+
+    pub fn setter(mut self, setter: Box<value::NamedPropertySetter>) -> NamedPropertyHandler {
+        self.setter = Some(setter);
+        self
+    }
+
+    pub fn query(mut self, query: Box<value::NamedPropertyQuery>) -> NamedPropertyHandler {
+        self.query = Some(query);
+        self
+    }
+
+    pub fn deleter(mut self, deleter: Box<value::NamedPropertyDeleter>) -> NamedPropertyHandler {
+        self.deleter = Some(deleter);
+        self
+    }
+
+    pub fn enumerator(mut self, enumerator: Box<value::NamedPropertyEnumerator>) -> NamedPropertyHandler {
+        self.enumerator = Some(enumerator);
+        self
+    }
+}
+
+/// The set of boxed closures `ObjectTemplate::set_indexed_property_handler` registers to
+/// intercept `obj[0]`-style access on objects instantiated from a template. Each field left
+/// `None` falls through to V8's normal property storage for that operation.
+#[derive(Default)]
+pub struct IndexedPropertyHandler {
+    pub getter: Option<Box<value::IndexedPropertyGetter>>,
+    pub setter: Option<Box<value::IndexedPropertySetter>>,
+    pub query: Option<Box<value::IndexedPropertyQuery>>,
+    pub deleter: Option<Box<value::IndexedPropertyDeleter>>,
+    pub enumerator: Option<Box<value::IndexedPropertyEnumerator>>,
+}
+
+impl IndexedPropertyHandler {
+    /// Creates a handler that intercepts none of the indexed-property operations.
+    pub fn new() -> IndexedPropertyHandler {
+        IndexedPropertyHandler::default()
+    }
+
+    pub fn getter(mut self, getter: Box<value::IndexedPropertyGetter>) -> IndexedPropertyHandler {
+        self.getter = Some(getter);
+        self
+    }
+
+    pub fn setter(mut self, setter: Box<value::IndexedPropertySetter>) -> IndexedPropertyHandler {
+        self.setter = Some(setter);
+        self
+    }
+
+    pub fn query(mut self, query: Box<value::IndexedPropertyQuery>) -> IndexedPropertyHandler {
+        self.query = Some(query);
+        self
+    }
+
+    pub fn deleter(mut self, deleter: Box<value::IndexedPropertyDeleter>) -> IndexedPropertyHandler {
+        self.deleter = Some(deleter);
+        self
+    }
+
+    pub fn enumerator(mut self,
+                      enumerator: Box<value::IndexedPropertyEnumerator>)
+                      -> IndexedPropertyHandler {
+        self.enumerator = Some(enumerator);
+        self
+    }
+}
+
 /// A Signature specifies which receiver is valid for a function.
 #[derive(Debug)]
 pub struct Signature(v8_sys::Signature);
@@ -124,6 +214,112 @@ impl ObjectTemplate {
         };
     }
 
+    /// Registers a computed property named `name` on every object instantiated from this
+    /// template, backed by `getter` and (if given) `setter`, following the same
+    /// boxed-callback-in-an-`External` pattern `FunctionTemplate::new` uses to smuggle its
+    /// callback through V8.
+    pub fn set_accessor(&self,
+                        isolate: &isolate::Isolate,
+                        name: &value::String,
+                        getter: Box<value::AccessorGetter>,
+                        setter: Option<Box<value::AccessorSetter>>) {
+        unsafe {
+            let has_setter = setter.is_some();
+            let accessor_ptr = Box::into_raw(Box::new(util::Accessor {
+                getter: getter,
+                setter: setter,
+            }));
+            let data = value::External::new::<util::Accessor>(isolate, accessor_ptr);
+            let setter_trampoline = if has_setter {
+                Some(util::accessor_setter_callback as _)
+            } else {
+                None
+            };
+
+            util::invoke(&self.0, |c| {
+                    v8_sys::v8_ObjectTemplate_SetAccessor(c,
+                                                          self.1,
+                                                          name.as_raw(),
+                                                          Some(util::accessor_getter_callback),
+                                                          setter_trampoline,
+                                                          data.as_raw())
+                })
+                .unwrap()
+        };
+    }
+
+    /// Intercepts property access by name on every object instantiated from this template (e.g.
+    /// `obj.foo` or `obj["foo"]`), letting `handler`'s closures lazily compute or virtualize
+    /// properties instead of only serving pre-set values.
+    pub fn set_named_property_handler(&self, isolate: &isolate::Isolate, handler: NamedPropertyHandler) {
+        unsafe {
+            let handler_ptr = Box::into_raw(Box::new(handler));
+            let data = value::External::new::<NamedPropertyHandler>(isolate, handler_ptr);
+            let handler = handler_ptr.as_ref().unwrap();
+
+            util::invoke(&self.0, |c| {
+                    v8_sys::v8_ObjectTemplate_SetNamedPropertyHandler(
+                        c,
+                        self.1,
+                        handler.getter.as_ref().map(|_| util::named_property_getter_callback as _),
+                        handler.setter.as_ref().map(|_| util::named_property_setter_callback as _),
+                        handler.query.as_ref().map(|_| util::named_property_query_callback as _),
+                        handler.deleter.as_ref().map(|_| util::named_property_deleter_callback as _),
+                        handler.enumerator.as_ref().map(|_| util::named_property_enumerator_callback as _),
+                        data.as_raw())
+                })
+                .unwrap()
+        };
+    }
+
+    /// Intercepts property access by index on every object instantiated from this template (e.g.
+    /// `obj[0]`), letting `handler`'s closures lazily compute or virtualize properties instead of
+    /// only serving pre-set values.
+    pub fn set_indexed_property_handler(&self, isolate: &isolate::Isolate, handler: IndexedPropertyHandler) {
+        unsafe {
+            let handler_ptr = Box::into_raw(Box::new(handler));
+            let data = value::External::new::<IndexedPropertyHandler>(isolate, handler_ptr);
+            let handler = handler_ptr.as_ref().unwrap();
+
+            util::invoke(&self.0, |c| {
+                    v8_sys::v8_ObjectTemplate_SetIndexedPropertyHandler(
+                        c,
+                        self.1,
+                        handler.getter.as_ref().map(|_| util::indexed_property_getter_callback as _),
+                        handler.setter.as_ref().map(|_| util::indexed_property_setter_callback as _),
+                        handler.query.as_ref().map(|_| util::indexed_property_query_callback as _),
+                        handler.deleter.as_ref().map(|_| util::indexed_property_deleter_callback as _),
+                        handler.enumerator.as_ref().map(|_| util::indexed_property_enumerator_callback as _),
+                        data.as_raw())
+                })
+                .unwrap()
+        };
+    }
+
+    /// Registers `callback` to arbitrate named/indexed property access to objects instantiated
+    /// from this template whenever it's reached from a context with a mismatched security token
+    /// (see `context::Context::set_security_token`), instead of such cross-context access being
+    /// denied outright. Lets a multi-tenant embedding enforce an `allowDomain`-style policy
+    /// between sandboxed contexts sharing one isolate.
+    pub fn set_access_check_callback(&self,
+                                     isolate: &isolate::Isolate,
+                                     callback: Box<value::AccessCheckCallback>) {
+        unsafe {
+            let callback_ptr = Box::into_raw(Box::new(callback));
+            let data = value::External::new::<Box<value::AccessCheckCallback>>(isolate, callback_ptr);
+
+            util::invoke(&self.0, |c| {
+                    v8_sys::v8_ObjectTemplate_SetAccessCheckCallback(
+                        c,
+                        self.1,
+                        Some(util::named_access_check_callback),
+                        Some(util::indexed_access_check_callback),
+                        data.as_raw())
+                })
+                .unwrap()
+        };
+    }
+
     /// Creates a new object instance based off of this template.
     pub fn new_instance(&self, context: &context::Context) -> value::Object {
         unsafe {
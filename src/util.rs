@@ -50,12 +50,13 @@ fn invoke_inner<F, B>(isolate: &isolate::Isolate,
             .or_else(|| isolate.current_context())
             .unwrap_or_else(|| context::Context::new(&isolate));
 
-        if exception.is_object() {
-            let exception = exception.into_object().unwrap();
+        // For a thrown `Error` object, pull out its `name` (e.g. "RangeError") separately from
+        // the concatenated `message`, so callers can branch on it without string-matching.
+        let name = if let Some(exception_obj) = exception.clone().into_object() {
             let panic_info_key = value::String::from_str(isolate, "panicInfo");
 
-            if exception.has(&context, &panic_info_key) {
-                match exception.get(&context, &panic_info_key).into_external() {
+            if exception_obj.has(&context, &panic_info_key) {
+                match exception_obj.get(&context, &panic_info_key).into_external() {
                     Some(panic_info) => {
                         let panic_info =
                             unsafe {
@@ -68,11 +69,21 @@ fn invoke_inner<F, B>(isolate: &isolate::Isolate,
                     }
                 }
             }
-        }
 
-        let message_str = message.get(&context).value();
+            let name_key = value::String::from_str(isolate, "name");
+            if exception_obj.has(&context, &name_key) {
+                exception_obj.get(&context, &name_key).into_string().map(|s| s.value())
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        let message_str = message.get().value();
         let stack_trace = message.get_stack_trace().to_captured();
-        Err(error::ErrorKind::Javascript(message_str, stack_trace).into())
+        let source = message.to_captured_source_context(&context);
+        Err(error::ErrorKind::Javascript(exception, name, message_str, stack_trace, source).into())
     }
 }
 
@@ -94,6 +105,7 @@ pub extern "C" fn callback(callback_info: v8::FunctionCallbackInfoPtr_Value) {
             holder: value::Object::from_raw(&isolate, callback_info.Holder),
             new_target: value::Value::from_raw(&isolate, callback_info.NewTarget),
             is_construct_call: 0 != callback_info.IsConstructCall,
+            data: data.get_internal_field(1).into_external(),
         };
 
         let result = panic::catch_unwind(|| {
@@ -118,6 +130,307 @@ pub extern "C" fn callback(callback_info: v8::FunctionCallbackInfoPtr_Value) {
     }
 }
 
+/// Pulls the boxed callback and shared `value::PropertyCallbackInfo` out of a raw property
+/// callback's `info`, the same way `callback` does for `FunctionCallbackInfo`.
+unsafe fn property_callback_info(info: &v8::PropertyCallbackInfo_Value)
+                                 -> (isolate::Isolate, value::External, value::PropertyCallbackInfo) {
+    let isolate = isolate::Isolate::from_raw(info.GetIsolate);
+    let data = value::External::from_raw(&isolate, info.Data as v8::ExternalRef);
+    let callback_info = value::PropertyCallbackInfo {
+        isolate: isolate.clone(),
+        this: value::Object::from_raw(&isolate, info.This),
+        holder: value::Object::from_raw(&isolate, info.Holder),
+    };
+    (isolate, data, callback_info)
+}
+
+/// Writes a property callback's result back into `info`, the same way `callback` does for
+/// `FunctionCallbackInfo`: `Some(Ok(value))` sets the return value, `Some(Err(exception))` throws
+/// it, and `None` leaves `info` untouched so V8 falls through to the object's own property.
+unsafe fn resolve_property_callback(isolate: &isolate::Isolate,
+                                    info: &mut v8::PropertyCallbackInfo_Value,
+                                    result: ::std::thread::Result<Option<Result<value::Value, value::Value>>>) {
+    match result {
+        Ok(Some(Ok(value))) => {
+            info.ReturnValue = value.as_raw();
+            mem::forget(value);
+        }
+        Ok(Some(Err(exception))) => {
+            let thrown = throw_exception(isolate, &exception);
+            info.ThrownValue = thrown.as_raw();
+            mem::forget(thrown);
+        }
+        Ok(None) => {}
+        Err(panic) => {
+            let error = create_panic_error(isolate, panic);
+            info.ThrownValue = error.as_raw();
+            mem::forget(error);
+        }
+    }
+}
+
+pub extern "C" fn named_property_getter_callback(property: v8::StringRef,
+                                                  info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+        let property = value::String::from_raw(&isolate, property).value();
+
+        let result = panic::catch_unwind(|| {
+            let callback_ptr: *mut Box<value::NamedPropertyGetter> = data.value();
+            let callback = callback_ptr.as_ref().unwrap();
+            callback(&property, callback_info)
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+pub extern "C" fn named_property_setter_callback(property: v8::StringRef,
+                                                  new_value: v8::ValueRef,
+                                                  info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+        let property = value::String::from_raw(&isolate, property).value();
+        let new_value = value::Value::from_raw(&isolate, new_value);
+
+        let result = panic::catch_unwind(|| {
+            let callback_ptr: *mut Box<value::NamedPropertySetter> = data.value();
+            let callback = callback_ptr.as_ref().unwrap();
+            callback(&property, new_value, callback_info)
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+pub extern "C" fn named_property_query_callback(property: v8::StringRef,
+                                                 info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+        let property = value::String::from_raw(&isolate, property).value();
+
+        let result = panic::catch_unwind(|| {
+            let callback_ptr: *mut Box<value::NamedPropertyQuery> = data.value();
+            let callback = callback_ptr.as_ref().unwrap();
+            callback(&property, callback_info).map(|r| r.map(|attributes| attributes.into()))
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+pub extern "C" fn named_property_deleter_callback(property: v8::StringRef,
+                                                   info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+        let property = value::String::from_raw(&isolate, property).value();
+
+        let result = panic::catch_unwind(|| {
+            let callback_ptr: *mut Box<value::NamedPropertyDeleter> = data.value();
+            let callback = callback_ptr.as_ref().unwrap();
+            callback(&property, callback_info)
+                .map(|r| r.map(|deleted| value::Boolean::new(&isolate, deleted).into()))
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+pub extern "C" fn named_property_enumerator_callback(info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+        let context = isolate.current_context().unwrap_or_else(|| context::Context::new(&isolate));
+
+        let result = panic::catch_unwind(|| {
+            let callback_ptr: *mut Box<value::NamedPropertyEnumerator> = data.value();
+            let callback = callback_ptr.as_ref().unwrap();
+            callback(callback_info).map(|names| {
+                let array = value::Array::new(&isolate, &context, names.len() as u32);
+                for (i, name) in names.into_iter().enumerate() {
+                    array.set_index(&context, i as u32, &value::String::from_str(&isolate, &name));
+                }
+                Some(array.into())
+            })
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+pub extern "C" fn indexed_property_getter_callback(index: u32, info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+
+        let result = panic::catch_unwind(|| {
+            let callback_ptr: *mut Box<value::IndexedPropertyGetter> = data.value();
+            let callback = callback_ptr.as_ref().unwrap();
+            callback(index, callback_info)
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+pub extern "C" fn indexed_property_setter_callback(index: u32,
+                                                    new_value: v8::ValueRef,
+                                                    info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+        let new_value = value::Value::from_raw(&isolate, new_value);
+
+        let result = panic::catch_unwind(|| {
+            let callback_ptr: *mut Box<value::IndexedPropertySetter> = data.value();
+            let callback = callback_ptr.as_ref().unwrap();
+            callback(index, new_value, callback_info)
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+pub extern "C" fn indexed_property_query_callback(index: u32, info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+
+        let result = panic::catch_unwind(|| {
+            let callback_ptr: *mut Box<value::IndexedPropertyQuery> = data.value();
+            let callback = callback_ptr.as_ref().unwrap();
+            callback(index, callback_info).map(|r| r.map(|attributes| attributes.into()))
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+pub extern "C" fn indexed_property_deleter_callback(index: u32, info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+
+        let result = panic::catch_unwind(|| {
+            let callback_ptr: *mut Box<value::IndexedPropertyDeleter> = data.value();
+            let callback = callback_ptr.as_ref().unwrap();
+            callback(index, callback_info)
+                .map(|r| r.map(|deleted| value::Boolean::new(&isolate, deleted).into()))
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+pub extern "C" fn indexed_property_enumerator_callback(info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+        let context = isolate.current_context().unwrap_or_else(|| context::Context::new(&isolate));
+
+        let result = panic::catch_unwind(|| {
+            let callback_ptr: *mut Box<value::IndexedPropertyEnumerator> = data.value();
+            let callback = callback_ptr.as_ref().unwrap();
+            callback(callback_info).map(|indices| {
+                let array = value::Array::new(&isolate, &context, indices.len() as u32);
+                for (i, index) in indices.into_iter().enumerate() {
+                    array.set_index(&context, i as u32, &value::Integer::new(&isolate, index as i32));
+                }
+                Some(array.into())
+            })
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+/// The pair of boxed closures behind a `template::ObjectTemplate::set_accessor` registration,
+/// stored together behind a single `External` since V8 only gives an accessor pair one shared
+/// `data` slot.
+pub struct Accessor {
+    pub getter: Box<value::AccessorGetter>,
+    pub setter: Option<Box<value::AccessorSetter>>,
+}
+
+pub extern "C" fn accessor_getter_callback(property: v8::StringRef, info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+        let property = value::String::from_raw(&isolate, property).value();
+
+        let result = panic::catch_unwind(|| {
+            let accessor_ptr: *mut Accessor = data.value();
+            let accessor = accessor_ptr.as_ref().unwrap();
+            Some((accessor.getter)(&property, callback_info))
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+pub extern "C" fn accessor_setter_callback(property: v8::StringRef,
+                                           new_value: v8::ValueRef,
+                                           info: v8::PropertyCallbackInfoPtr_Value) {
+    unsafe {
+        let info = info.as_mut().unwrap();
+        let (isolate, data, callback_info) = property_callback_info(info);
+        let property = value::String::from_raw(&isolate, property).value();
+        let new_value = value::Value::from_raw(&isolate, new_value);
+
+        let result = panic::catch_unwind(|| {
+            let accessor_ptr: *mut Accessor = data.value();
+            let accessor = accessor_ptr.as_ref().unwrap();
+            accessor.setter.as_ref().map(|setter| {
+                setter(&property, new_value, callback_info).map(|()| value::undefined(&isolate).into())
+            })
+        });
+        resolve_property_callback(&isolate, info, result);
+    }
+}
+
+/// Invoked in place of a `NamedSecurityCallback` by `template::ObjectTemplate::
+/// set_access_check_callback`, for a named-property access across a security token boundary.
+///
+/// Unlike the other callback trampolines, a panic here is treated as a denial rather than
+/// propagated into JS as a thrown exception: there is no sensible way to "throw" out of a bare
+/// `bool`-returning security check, and failing closed is the safe default.
+pub extern "C" fn named_access_check_callback(isolate: v8::IsolateRef,
+                                              host: v8::ObjectRef,
+                                              key: v8::StringRef,
+                                              data: v8::ExternalRef)
+                                              -> bool {
+    unsafe {
+        let isolate = isolate::Isolate::from_raw(isolate);
+        let context = isolate.current_context().unwrap_or_else(|| context::Context::new(&isolate));
+        let host = value::Object::from_raw(&isolate, host);
+        let key = value::String::from_raw(&isolate, key).value();
+        let data = value::External::from_raw(&isolate, data);
+
+        panic::catch_unwind(|| {
+                let callback_ptr: *mut Box<value::AccessCheckCallback> = data.value();
+                let callback = callback_ptr.as_ref().unwrap();
+                callback(&context, &host, value::PropertyKey::Named(key))
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Invoked in place of an `IndexedSecurityCallback` by `template::ObjectTemplate::
+/// set_access_check_callback`, for an indexed-property access across a security token boundary.
+/// See `named_access_check_callback` for why a panic is treated as a denial.
+pub extern "C" fn indexed_access_check_callback(isolate: v8::IsolateRef,
+                                                host: v8::ObjectRef,
+                                                index: u32,
+                                                data: v8::ExternalRef)
+                                                -> bool {
+    unsafe {
+        let isolate = isolate::Isolate::from_raw(isolate);
+        let context = isolate.current_context().unwrap_or_else(|| context::Context::new(&isolate));
+        let host = value::Object::from_raw(&isolate, host);
+        let data = value::External::from_raw(&isolate, data);
+
+        panic::catch_unwind(|| {
+                let callback_ptr: *mut Box<value::AccessCheckCallback> = data.value();
+                let callback = callback_ptr.as_ref().unwrap();
+                callback(&context, &host, value::PropertyKey::Indexed(index))
+            })
+            .unwrap_or(false)
+    }
+}
+
 fn throw_exception(isolate: &isolate::Isolate, exception: &value::Value) -> value::Value {
     unsafe {
         let raw = v8::Isolate_ThrowException(isolate.as_raw(), exception.as_raw()).as_mut().unwrap();
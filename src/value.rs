@@ -2,12 +2,24 @@
 use v8_sys;
 use context;
 use error;
+use future;
 use isolate;
+use isolate::GetIsolate;
 use util;
+use std::any;
+use std::collections::HashMap;
+use std::convert;
+use std::fmt;
+use std::hash;
+use std::iter;
+use std::marker;
 use std::mem;
 use std::ops;
 use std::os;
 use std::ptr;
+use std::slice;
+use std::string;
+use std::sync;
 use template;
 
 /// The superclass of values and API object templates.
@@ -18,6 +30,45 @@ pub struct Data(v8_sys::Data);
 #[derive(Debug)]
 pub struct Value(v8_sys::Value);
 
+/// A single-value classification of a `Value`, as returned by `Value::classify`.
+///
+/// Collapses the dozens of overlapping `is_*` predicates on `Value` (every `Array` is also an
+/// `Object`, every typed array is also an `ArrayBufferView`, ...) into one discriminant suitable
+/// for an exhaustive `match`, instead of an if/else ladder re-deriving the same precedence order
+/// at every call site.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Undefined,
+    Null,
+    Boolean,
+    Number,
+    BigInt,
+    String,
+    Symbol,
+    Array,
+    Function,
+    Promise,
+    Map,
+    Set,
+    ArrayBuffer,
+    SharedArrayBuffer,
+    Uint8Array,
+    Uint8ClampedArray,
+    Int8Array,
+    Uint16Array,
+    Int16Array,
+    Uint32Array,
+    Int32Array,
+    Float32Array,
+    Float64Array,
+    DataView,
+    Object,
+    /// Anything else: a non-object value that none of the above predicates match (e.g. an
+    /// `External`). An object `classify` doesn't give its own variant (e.g. a `Date`, `RegExp` or
+    /// `Proxy`) is classified as `Object`, not `Other`.
+    Other,
+}
+
 /// The superclass of primitive values.  See ECMA-262 4.3.2.
 #[derive(Debug)]
 pub struct Primitive(v8_sys::Primitive);
@@ -62,6 +113,11 @@ pub struct Int32(v8_sys::Int32);
 #[derive(Debug)]
 pub struct Uint32(v8_sys::Uint32);
 
+/// A JavaScript value representing an arbitrary-precision signed integer (ECMA-262 2020,
+/// 6.1.6.2), e.g. the literal `123n`.
+#[derive(Debug)]
+pub struct BigInt(v8_sys::BigInt);
+
 /// A JavaScript object (ECMA-262, 4.3.3)
 #[derive(Debug)]
 pub struct Object(v8_sys::Object);
@@ -88,6 +144,23 @@ pub struct Function(v8_sys::Function);
 #[derive(Debug)]
 pub struct Promise(v8_sys::Promise);
 
+/// Lets Rust hand JS an externally-controlled `Promise`: `get_promise()` returns the promise to
+/// expose, while `resolve`/`reject` are kept back to settle it once the result is ready, mirroring
+/// V8's `Promise::Resolver`.
+#[derive(Debug)]
+pub struct PromiseResolver(v8_sys::PromiseResolver);
+
+/// Where a `Promise` is in its lifecycle, as returned by `Promise::state`.
+#[derive(Debug, Clone)]
+pub enum PromiseState {
+    /// Neither `resolve` nor `reject` has been called on the promise's resolver yet.
+    Pending,
+    /// The promise was fulfilled with the contained value.
+    Fulfilled(Value),
+    /// The promise was rejected with the contained reason.
+    Rejected(Value),
+}
+
 /// An instance of the built-in Proxy constructor (ECMA-262, 6th Edition, 26.2.1).
 #[derive(Debug)]
 pub struct Proxy(v8_sys::Proxy);
@@ -189,6 +262,10 @@ pub struct NumberObject(v8_sys::NumberObject);
 #[derive(Debug)]
 pub struct BooleanObject(v8_sys::BooleanObject);
 
+/// A BigInt object.
+#[derive(Debug)]
+pub struct BigIntObject(v8_sys::BigIntObject);
+
 /// A String object (ECMA-262, 4.3.18).
 #[derive(Debug)]
 pub struct StringObject(v8_sys::StringObject);
@@ -211,10 +288,89 @@ pub struct External(v8_sys::External);
 pub struct Exception(v8_sys::Exception);
 
 pub struct PropertyCallbackInfo {
+    pub isolate: isolate::Isolate,
     pub this: Object,
     pub holder: Object,
 }
 
+/// Computes the value of an accessor property registered with
+/// `template::ObjectTemplate::set_accessor`.
+pub type AccessorGetter = Fn(&str, PropertyCallbackInfo) -> Result<Value, Value> + 'static;
+
+/// Stores the value of an accessor property registered with
+/// `template::ObjectTemplate::set_accessor`.
+pub type AccessorSetter = Fn(&str, Value, PropertyCallbackInfo) -> Result<(), Value> + 'static;
+
+/// Reads a named property intercepted by `template::ObjectTemplate::set_named_property_handler`.
+/// Returning `None` falls through to the object's own properties.
+pub type NamedPropertyGetter = Fn(&str, PropertyCallbackInfo) -> Option<Result<Value, Value>> +
+    'static;
+
+/// Writes a named property intercepted by `template::ObjectTemplate::set_named_property_handler`.
+/// Returning `None` falls through to the object's own properties.
+pub type NamedPropertySetter = Fn(&str, Value, PropertyCallbackInfo) -> Option<Result<Value, Value>> +
+    'static;
+
+/// Reports the attributes of a named property intercepted by
+/// `template::ObjectTemplate::set_named_property_handler`, or whether it exists at all.
+/// Returning `None` falls through to the object's own properties.
+pub type NamedPropertyQuery = Fn(&str, PropertyCallbackInfo) -> Option<Result<Integer, Value>> +
+    'static;
+
+/// Deletes a named property intercepted by `template::ObjectTemplate::set_named_property_handler`.
+/// Returning `None` falls through to the object's own properties.
+pub type NamedPropertyDeleter = Fn(&str, PropertyCallbackInfo) -> Option<Result<bool, Value>> +
+    'static;
+
+/// Lists the named properties intercepted by
+/// `template::ObjectTemplate::set_named_property_handler`, e.g. for `Object.keys`.
+pub type NamedPropertyEnumerator = Fn(PropertyCallbackInfo) -> Result<Vec<String>, Value> +
+    'static;
+
+/// Reads an indexed property intercepted by
+/// `template::ObjectTemplate::set_indexed_property_handler`. Returning `None` falls through to
+/// the object's own properties.
+pub type IndexedPropertyGetter = Fn(u32, PropertyCallbackInfo) -> Option<Result<Value, Value>> +
+    'static;
+
+/// Writes an indexed property intercepted by
+/// `template::ObjectTemplate::set_indexed_property_handler`. Returning `None` falls through to
+/// the object's own properties.
+pub type IndexedPropertySetter = Fn(u32, Value, PropertyCallbackInfo)
+    -> Option<Result<Value, Value>> + 'static;
+
+/// Reports the attributes of an indexed property intercepted by
+/// `template::ObjectTemplate::set_indexed_property_handler`, or whether it exists at all.
+/// Returning `None` falls through to the object's own properties.
+pub type IndexedPropertyQuery = Fn(u32, PropertyCallbackInfo) -> Option<Result<Integer, Value>> +
+    'static;
+
+/// Deletes an indexed property intercepted by
+/// `template::ObjectTemplate::set_indexed_property_handler`. Returning `None` falls through to
+/// the object's own properties.
+pub type IndexedPropertyDeleter = Fn(u32, PropertyCallbackInfo) -> Option<Result<bool, Value>> +
+    'static;
+
+/// Lists the indexed properties intercepted by
+/// `template::ObjectTemplate::set_indexed_property_handler`, e.g. for `Object.keys`.
+pub type IndexedPropertyEnumerator = Fn(PropertyCallbackInfo) -> Result<Vec<u32>, Value> + 'static;
+
+/// The property an `AccessCheckCallback` is being asked to allow or deny access to.
+pub enum PropertyKey {
+    Named(String),
+    Indexed(u32),
+}
+
+/// Decides whether code running in `accessing_context` may reach `key` on `accessed_object`,
+/// registered via `template::ObjectTemplate::set_access_check_callback`.
+///
+/// This is only consulted when `accessing_context`'s security token (see
+/// `context::Context::set_security_token`) doesn't match the token of the context
+/// `accessed_object` was created in; same-token access is always allowed without a check.
+/// Returning `false` makes the access fail the way touching a cross-origin `window` does in a
+/// browser, instead of throwing or silently returning `undefined`.
+pub type AccessCheckCallback = Fn(&context::Context, &Object, PropertyKey) -> bool + 'static;
+
 pub struct FunctionCallbackInfo {
     pub isolate: isolate::Isolate,
     pub length: isize,
@@ -223,10 +379,330 @@ pub struct FunctionCallbackInfo {
     pub holder: Object,
     pub new_target: Value,
     pub is_construct_call: bool,
+    /// The user data passed to `Function::new_with_data`, if any.  Lets a native host object
+    /// share state across several functions without stashing it in each closure.
+    pub data: Option<External>,
 }
 
 pub type FunctionCallback = Fn(FunctionCallbackInfo) -> Result<Value, Value> + 'static;
 
+/// Converts a JavaScript `Value` into a native Rust type, throwing a `TypeError` back into the
+/// script on mismatch rather than panicking.
+///
+/// Used by `Function::wrap` to decode a typed callback's arguments out of `info.args`, the way
+/// `run_defined_function` and friends currently do by hand with `is_int32`/`int32_value`. See
+/// `ToValue` for the reverse direction.
+pub trait FromValue: Sized {
+    /// Converts `value`, or returns a `TypeError` (as a plain `Value`, ready to be thrown) if it
+    /// isn't of the expected JavaScript type.
+    fn from_value(context: &context::Context, value: &Value) -> Result<Self, Value>;
+}
+
+/// Converts a native Rust type into a JavaScript `Value`, the reverse of `FromValue`.
+///
+/// Used by `Function::wrap` to convert a typed callback's return value back into something that
+/// can be written into `FunctionCallbackInfo::ReturnValue`.
+pub trait ToValue {
+    /// Converts this value into a `Value` living on `isolate`.
+    fn to_value(self, isolate: &isolate::Isolate) -> Value;
+}
+
+impl FromValue for Value {
+    fn from_value(_context: &context::Context, value: &Value) -> Result<Value, Value> {
+        Ok(value.clone())
+    }
+}
+
+impl ToValue for Value {
+    fn to_value(self, _isolate: &isolate::Isolate) -> Value {
+        self
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(context: &context::Context, value: &Value) -> Result<bool, Value> {
+        Ok(value.boolean_value(context))
+    }
+}
+
+impl ToValue for bool {
+    fn to_value(self, isolate: &isolate::Isolate) -> Value {
+        Boolean::new(isolate, self).into()
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(context: &context::Context, value: &Value) -> Result<f64, Value> {
+        if value.is_number() {
+            Ok(value.number_value(context))
+        } else {
+            Err(from_value_type_error(context, "a number"))
+        }
+    }
+}
+
+impl ToValue for f64 {
+    fn to_value(self, isolate: &isolate::Isolate) -> Value {
+        Number::new(isolate, self).into()
+    }
+}
+
+impl FromValue for i32 {
+    fn from_value(context: &context::Context, value: &Value) -> Result<i32, Value> {
+        if value.is_int32() {
+            Ok(value.int32_value(context))
+        } else {
+            Err(from_value_type_error(context, "a 32-bit integer"))
+        }
+    }
+}
+
+impl ToValue for i32 {
+    fn to_value(self, isolate: &isolate::Isolate) -> Value {
+        Integer::new(isolate, self).into()
+    }
+}
+
+impl FromValue for u32 {
+    fn from_value(context: &context::Context, value: &Value) -> Result<u32, Value> {
+        if value.is_uint32() {
+            Ok(value.uint32_value(context))
+        } else {
+            Err(from_value_type_error(context, "a 32-bit unsigned integer"))
+        }
+    }
+}
+
+impl ToValue for u32 {
+    fn to_value(self, isolate: &isolate::Isolate) -> Value {
+        Integer::new_from_unsigned(isolate, self).into()
+    }
+}
+
+impl FromValue for ::std::string::String {
+    fn from_value(context: &context::Context, value: &Value) -> Result<::std::string::String, Value> {
+        match value.clone().into_string() {
+            Some(s) => Ok(s.value()),
+            None => Err(from_value_type_error(context, "a string")),
+        }
+    }
+}
+
+impl ToValue for ::std::string::String {
+    fn to_value(self, isolate: &isolate::Isolate) -> Value {
+        String::from_str(isolate, &self).into()
+    }
+}
+
+impl<'a> ToValue for &'a str {
+    fn to_value(self, isolate: &isolate::Isolate) -> Value {
+        String::from_str(isolate, self).into()
+    }
+}
+
+impl ToValue for () {
+    fn to_value(self, isolate: &isolate::Isolate) -> Value {
+        undefined(isolate).into()
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(context: &context::Context, value: &Value) -> Result<Option<T>, Value> {
+        if value.is_undefined() || value.is_null() {
+            Ok(None)
+        } else {
+            T::from_value(context, value).map(Some)
+        }
+    }
+}
+
+impl<T: ToValue> ToValue for Option<T> {
+    fn to_value(self, isolate: &isolate::Isolate) -> Value {
+        match self {
+            Some(value) => value.to_value(isolate),
+            None => undefined(isolate).into(),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(context: &context::Context, value: &Value) -> Result<Vec<T>, Value> {
+        let array = match value.clone().into_array() {
+            Some(array) => array,
+            None => return Err(from_value_type_error(context, "an array")),
+        };
+        let len = array.length();
+        let mut result = Vec::with_capacity(len as usize);
+        for i in 0..len {
+            result.push(try!(T::from_value(context, &array.get_index(context, i))));
+        }
+        Ok(result)
+    }
+}
+
+impl<T: ToValue> ToValue for Vec<T> {
+    fn to_value(self, isolate: &isolate::Isolate) -> Value {
+        let context = isolate.current_context().unwrap_or_else(|| context::Context::new(isolate));
+        let array = Array::new(isolate, &context, self.len() as u32);
+        for (i, item) in self.into_iter().enumerate() {
+            array.set_index(&context, i as u32, &item.to_value(isolate));
+        }
+        array.into()
+    }
+}
+
+impl<T: FromValue> FromValue for HashMap<::std::string::String, T> {
+    fn from_value(context: &context::Context, value: &Value) -> Result<HashMap<::std::string::String, T>, Value> {
+        let object = match value.clone().into_object() {
+            Some(object) => object,
+            None => return Err(from_value_type_error(context, "an object")),
+        };
+        let keys = object.get_own_property_names(context);
+        let mut result = HashMap::with_capacity(keys.length() as usize);
+        for i in 0..keys.length() {
+            let key_value: &Value = &keys.get_index(context, i);
+            let key = try!(::std::string::String::from_value(context, key_value));
+            let value = try!(T::from_value(context, &object.get(context, key_value)));
+            result.insert(key, value);
+        }
+        Ok(result)
+    }
+}
+
+impl<T: ToValue> ToValue for HashMap<::std::string::String, T> {
+    fn to_value(self, isolate: &isolate::Isolate) -> Value {
+        let context = isolate.current_context().unwrap_or_else(|| context::Context::new(isolate));
+        let object = Object::new(isolate, &context);
+        for (key, item) in self.into_iter() {
+            let key = String::from_str(isolate, &key);
+            object.set(&context, &key, &item.to_value(isolate));
+        }
+        object.into()
+    }
+}
+
+fn from_value_type_error(context: &context::Context, expected: &str) -> Value {
+    let isolate = context.get_isolate();
+    let message = String::from_str(&isolate, &format!("expected {}", expected));
+    Exception::type_error(&isolate, &message)
+}
+
+fn arity_error(context: &context::Context, expected: usize, actual: usize) -> Value {
+    let isolate = context.get_isolate();
+    let message = String::from_str(&isolate,
+                                    &format!("expected {} argument(s), got {}", expected, actual));
+    Exception::type_error(&isolate, &message)
+}
+
+/// Captures every argument of a `Function::wrap`-ped callback beyond its declared fixed arity,
+/// mirroring JavaScript's `...rest` parameters; see `AsCallback`.
+///
+/// Deliberately does not implement `FromValue`: that keeps it from overlapping with the plain
+/// `AsCallback` tuple impls below, each of whose slots requires `FromValue`.
+pub struct Rest(pub Vec<Value>);
+
+/// Implemented for native closures whose argument and return types `FromValue`/`ToValue` can
+/// marshal automatically, so `Function::wrap` can skip hand-decoding `FunctionCallbackInfo`.
+///
+/// Implemented for `Fn(A1, .., An) -> R` for every arity from 0 to 12 inclusive, where each `Ai:
+/// FromValue` and `R: ToValue`, and again for each such arity with a trailing `Rest` parameter
+/// capturing any further arguments.
+pub trait AsCallback: 'static {
+    /// The number of leading arguments this callback requires; mirrors `Function.length`, and
+    /// does not include a trailing `Rest` parameter, if any.
+    fn arity() -> usize;
+
+    /// Validates `args` against `arity`, converts each leading argument with `FromValue`, invokes
+    /// `self`, and converts the result back with `ToValue`.
+    fn call(&self, context: &context::Context, args: &[Value]) -> Result<Value, Value>;
+}
+
+macro_rules! as_callback_impl {
+    ($arity:expr; $($arg:ident : $idx:expr),*) => {
+        impl<$($arg,)* R, F> AsCallback for F
+            where $($arg: FromValue,)*
+                  R: ToValue,
+                  F: Fn($($arg),*) -> R + 'static
+        {
+            fn arity() -> usize {
+                $arity
+            }
+
+            #[allow(unused_variables)]
+            fn call(&self, context: &context::Context, args: &[Value]) -> Result<Value, Value> {
+                if args.len() < $arity {
+                    return Err(arity_error(context, $arity, args.len()));
+                }
+                $(
+                    let $arg = try!($arg::from_value(context, &args[$idx]));
+                )*
+                Ok(self($($arg),*).to_value(&context.get_isolate()))
+            }
+        }
+    }
+}
+
+macro_rules! as_callback_rest_impl {
+    ($arity:expr; $($arg:ident : $idx:expr),*) => {
+        impl<$($arg,)* R, F> AsCallback for F
+            where $($arg: FromValue,)*
+                  R: ToValue,
+                  F: Fn($($arg,)* Rest) -> R + 'static
+        {
+            fn arity() -> usize {
+                $arity
+            }
+
+            #[allow(unused_variables)]
+            fn call(&self, context: &context::Context, args: &[Value]) -> Result<Value, Value> {
+                if args.len() < $arity {
+                    return Err(arity_error(context, $arity, args.len()));
+                }
+                $(
+                    let $arg = try!($arg::from_value(context, &args[$idx]));
+                )*
+                let rest = Rest(args[$arity..].to_vec());
+                Ok(self($($arg,)* rest).to_value(&context.get_isolate()))
+            }
+        }
+    }
+}
+
+as_callback_impl!(0;);
+as_callback_impl!(1; A0: 0);
+as_callback_impl!(2; A0: 0, A1: 1);
+as_callback_impl!(3; A0: 0, A1: 1, A2: 2);
+as_callback_impl!(4; A0: 0, A1: 1, A2: 2, A3: 3);
+as_callback_impl!(5; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4);
+as_callback_impl!(6; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5);
+as_callback_impl!(7; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6);
+as_callback_impl!(8; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6, A7: 7);
+as_callback_impl!(9; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6, A7: 7, A8: 8);
+as_callback_impl!(10; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6, A7: 7, A8: 8, A9: 9);
+as_callback_impl!(11; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6, A7: 7, A8: 8, A9: 9, A10: 10);
+as_callback_impl!(12;
+                  A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6, A7: 7, A8: 8, A9: 9, A10: 10,
+                  A11: 11);
+
+as_callback_rest_impl!(0;);
+as_callback_rest_impl!(1; A0: 0);
+as_callback_rest_impl!(2; A0: 0, A1: 1);
+as_callback_rest_impl!(3; A0: 0, A1: 1, A2: 2);
+as_callback_rest_impl!(4; A0: 0, A1: 1, A2: 2, A3: 3);
+as_callback_rest_impl!(5; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4);
+as_callback_rest_impl!(6; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5);
+as_callback_rest_impl!(7; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6);
+as_callback_rest_impl!(8; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6, A7: 7);
+as_callback_rest_impl!(9; A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6, A7: 7, A8: 8);
+as_callback_rest_impl!(10;
+                       A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6, A7: 7, A8: 8, A9: 9);
+as_callback_rest_impl!(11;
+                       A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6, A7: 7, A8: 8, A9: 9,
+                       A10: 10);
+as_callback_rest_impl!(12;
+                       A0: 0, A1: 1, A2: 2, A3: 3, A4: 4, A5: 5, A6: 6, A7: 7, A8: 8, A9: 9,
+                       A10: 10, A11: 11);
+
 pub fn undefined(isolate: &isolate::Isolate) -> Primitive {
     let raw = unsafe { util::invoke(isolate, |c| v8_sys::v8_Undefined(c)).unwrap() };
     Primitive(isolate.clone(), raw)
@@ -247,6 +723,49 @@ pub fn false_(isolate: &isolate::Isolate) -> Boolean {
     Boolean(isolate.clone(), raw)
 }
 
+/// The error returned by a `TryFrom<Value>` conversion when the value is not an instance of the
+/// target type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrongType {
+    expected: &'static str,
+}
+
+impl WrongType {
+    fn new(expected: &'static str) -> WrongType {
+        WrongType { expected: expected }
+    }
+}
+
+impl fmt::Display for WrongType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "value is not a {}", self.expected)
+    }
+}
+
+impl ::std::error::Error for WrongType {
+    fn description(&self) -> &str {
+        "value is not an instance of the expected type"
+    }
+}
+
+/// Generates a fallible `TryFrom<Value>` for `$result`, alongside the infallible `into_*` that
+/// `downcast!` already produces, so the hierarchy also composes with `.try_into()`/`?`.
+macro_rules! try_from_value {
+    ($result:ident, $predicate:ident) => {
+        impl convert::TryFrom<Value> for $result {
+            type Error = WrongType;
+
+            fn try_from(value: Value) -> Result<$result, WrongType> {
+                if value.$predicate() {
+                    Ok(unsafe { mem::transmute(value) })
+                } else {
+                    Err(WrongType::new(stringify!($result)))
+                }
+            }
+        }
+    }
+}
+
 macro_rules! downcast {
     ($predicate:ident, $predicate_doc:expr, $wrapped:expr) => {
         #[doc=$predicate_doc]
@@ -388,6 +907,12 @@ impl Value {
               "",
               v8_sys::v8_Value_IsUint32,
               Uint32);
+    downcast!(is_big_int,
+              "Returns true if this value is a BigInt.",
+              into_big_int,
+              "",
+              v8_sys::v8_Value_IsBigInt,
+              BigInt);
     downcast!(is_date,
               "Returns true if this value is a Date.",
               into_date,
@@ -409,6 +934,12 @@ impl Value {
               "",
               v8_sys::v8_Value_IsNumberObject,
               NumberObject);
+    downcast!(is_big_int_object,
+              "Returns true if this value is a BigInt object.",
+              into_big_int_object,
+              "",
+              v8_sys::v8_Value_IsBigIntObject,
+              BigIntObject);
     downcast!(is_string_object,
               "Returns true if this value is a String object.",
               into_string_object,
@@ -563,6 +1094,73 @@ impl Value {
               v8_sys::v8_Value_IsProxy,
               Proxy);
 
+    /// Returns true if this value is JavaScript's `null` or `undefined`.
+    pub fn is_null_or_undefined(&self) -> bool {
+        self.is_null() || self.is_undefined()
+    }
+
+    /// Classifies this value by its most specific JavaScript type.
+    ///
+    /// Checks the underlying `is_*` predicates in priority order (most specific first, since
+    /// e.g. every `Uint8Array` also answers `true` to `is_array_buffer_view`/`is_object`) and
+    /// returns the first match, so callers get a single `ValueKind` to `match` on instead of
+    /// re-deriving that precedence themselves.
+    pub fn classify(&self) -> ValueKind {
+        if self.is_undefined() {
+            ValueKind::Undefined
+        } else if self.is_null() {
+            ValueKind::Null
+        } else if self.is_boolean() {
+            ValueKind::Boolean
+        } else if self.is_number() {
+            ValueKind::Number
+        } else if self.is_big_int() {
+            ValueKind::BigInt
+        } else if self.is_string() {
+            ValueKind::String
+        } else if self.is_symbol() {
+            ValueKind::Symbol
+        } else if self.is_uint8_array() {
+            ValueKind::Uint8Array
+        } else if self.is_uint8_clamped_array() {
+            ValueKind::Uint8ClampedArray
+        } else if self.is_int8_array() {
+            ValueKind::Int8Array
+        } else if self.is_uint16_array() {
+            ValueKind::Uint16Array
+        } else if self.is_int16_array() {
+            ValueKind::Int16Array
+        } else if self.is_uint32_array() {
+            ValueKind::Uint32Array
+        } else if self.is_int32_array() {
+            ValueKind::Int32Array
+        } else if self.is_float32_array() {
+            ValueKind::Float32Array
+        } else if self.is_float64_array() {
+            ValueKind::Float64Array
+        } else if self.is_data_view() {
+            ValueKind::DataView
+        } else if self.is_array_buffer() {
+            ValueKind::ArrayBuffer
+        } else if self.is_shared_array_buffer() {
+            ValueKind::SharedArrayBuffer
+        } else if self.is_array() {
+            ValueKind::Array
+        } else if self.is_function() {
+            ValueKind::Function
+        } else if self.is_promise() {
+            ValueKind::Promise
+        } else if self.is_map() {
+            ValueKind::Map
+        } else if self.is_set() {
+            ValueKind::Set
+        } else if self.is_object() {
+            ValueKind::Object
+        } else {
+            ValueKind::Other
+        }
+    }
+
     partial_conversion!(to_boolean, v8_sys::v8_Value_ToBoolean, Boolean);
     partial_conversion!(to_number, v8_sys::v8_Value_ToNumber, Number);
     partial_conversion!(to_string, v8_sys::v8_Value_ToString, String);
@@ -718,6 +1316,51 @@ impl String {
         }
     }
 
+    /// Creates a new string backed directly by `data`, without V8 copying it.
+    ///
+    /// `data` is assumed to be Latin-1 (every byte value is a valid Latin-1 code point, so there's
+    /// nothing to validate there); it's kept alive by leaking an `Arc` clone into V8, which is
+    /// dropped again by a dispose callback once the string is garbage collected.
+    pub fn new_external_one_byte(isolate: &isolate::Isolate, data: sync::Arc<[u8]>) -> error::Result<String> {
+        if data.len() > i32::max_value() as usize {
+            return Err(error::ErrorKind::StringTooLong(data.len()).into());
+        }
+
+        let resource = Box::into_raw(Box::new(OneByteResource { data: data }));
+        let raw = unsafe {
+            util::invoke(&isolate, |c| {
+                    v8_sys::v8_String_NewExternalOneByte(c,
+                                                         isolate.as_raw(),
+                                                         ONE_BYTE_RESOURCE_FUNCTIONS,
+                                                         resource as *mut os::raw::c_void)
+                })
+                .unwrap()
+        };
+        Ok(String(isolate.clone(), raw))
+    }
+
+    /// Creates a new string backed directly by `data`, without V8 copying it.
+    ///
+    /// `data` is assumed to already be UTF-16; it's kept alive by leaking an `Arc` clone into V8,
+    /// which is dropped again by a dispose callback once the string is garbage collected.
+    pub fn new_external_two_byte(isolate: &isolate::Isolate, data: sync::Arc<[u16]>) -> error::Result<String> {
+        if data.len() > i32::max_value() as usize {
+            return Err(error::ErrorKind::StringTooLong(data.len()).into());
+        }
+
+        let resource = Box::into_raw(Box::new(TwoByteResource { data: data }));
+        let raw = unsafe {
+            util::invoke(&isolate, |c| {
+                    v8_sys::v8_String_NewExternalTwoByte(c,
+                                                         isolate.as_raw(),
+                                                         TWO_BYTE_RESOURCE_FUNCTIONS,
+                                                         resource as *mut os::raw::c_void)
+                })
+                .unwrap()
+        };
+        Ok(String(isolate.clone(), raw))
+    }
+
     /// Returns the number of characters in this string.
     pub fn length(&self) -> u32 {
         unsafe { util::invoke(&self.0, |c| v8_sys::v8_String_Length(c, self.1)).unwrap() as u32 }
@@ -762,6 +1405,62 @@ impl String {
         }
     }
 
+    /// Writes this string's UTF-8 encoding into `buf`, stopping early if `buf` is too small to
+    /// hold the whole string.  Returns the number of bytes written.
+    ///
+    /// Lets callers reuse one buffer across many reads instead of allocating a fresh `Vec` every
+    /// time, the way `value` does.
+    pub fn write_utf8_into(&self, buf: &mut [u8]) -> usize {
+        unsafe {
+            let ptr = mem::transmute(buf.as_mut_ptr());
+            util::invoke(&self.0,
+                         |c| v8_sys::v8_String_WriteUtf8(c, self.1, ptr, buf.len() as i32))
+                .unwrap() as usize
+        }
+    }
+
+    /// Writes this string's UTF-16 code units into `buf`, stopping early if `buf` is too small to
+    /// hold the whole string.  Returns the number of code units written.
+    pub fn write_two_byte_into(&self, buf: &mut [u16]) -> usize {
+        unsafe {
+            util::invoke(&self.0, |c| {
+                    v8_sys::v8_String_Write(c, self.1, buf.as_mut_ptr(), 0, buf.len() as i32)
+                })
+                .unwrap() as usize
+        }
+    }
+
+    /// Returns this string's contents as UTF-16 code units.
+    ///
+    /// Unlike `value`/`value_lossless`, this can represent lone surrogates, since it never
+    /// round-trips through UTF-8.
+    pub fn value_utf16(&self) -> Vec<u16> {
+        let len = self.length() as usize;
+        let mut buf = vec![0u16; len];
+        let written = self.write_two_byte_into(&mut buf);
+        buf.truncate(written);
+        buf
+    }
+
+    /// Like `value`, but checks the bytes V8 wrote instead of assuming they're well-formed UTF-8,
+    /// so a string holding a lone surrogate yields an error instead of undefined behavior.
+    ///
+    /// `is_one_byte`/`contains_only_one_byte` are cheap upfront checks V8 already has the answer
+    /// to; when they report a string is plain Latin-1, it can't contain a lone surrogate in the
+    /// first place, so this skips straight to the UTF-8 write instead of inspecting the result.
+    pub fn value_lossless(&self) -> Result<::std::string::String, string::FromUtf8Error> {
+        let len = self.utf8_length() as usize;
+        let mut buf = vec![0u8; len];
+        let written = self.write_utf8_into(&mut buf);
+        buf.truncate(written);
+
+        if self.is_one_byte() || self.contains_only_one_byte() {
+            Ok(unsafe { ::std::string::String::from_utf8_unchecked(buf) })
+        } else {
+            ::std::string::String::from_utf8(buf)
+        }
+    }
+
     /// Creates a string from a set of raw pointers.
     pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8_sys::StringRef) -> String {
         String(isolate.clone(), raw)
@@ -773,6 +1472,68 @@ impl String {
     }
 }
 
+impl fmt::Display for String {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+impl<'a> From<(&'a isolate::Isolate, &'a str)> for String {
+    fn from((isolate, str): (&'a isolate::Isolate, &'a str)) -> String {
+        String::from_str(isolate, str)
+    }
+}
+
+/// Generates the `data`/`length`/`dispose` vtable V8's `ExternalOneByteStringResource`/
+/// `ExternalTwoByteStringResource` expect, boxing an `Arc<[$elem]>` behind it so `new_external_*`
+/// doesn't need to hand-write this per encoding.
+///
+/// `dispose` runs on V8's own GC thread once the string it backs is collected, so it only ever
+/// drops the box; it never touches the heap in a way that could race with the isolate that made
+/// it.
+macro_rules! external_string_resource {
+    ($resource:ident, $elem:ty, $data_fn:ident, $length_fn:ident, $dispose_fn:ident,
+     $functions_ty:ident, $functions:ident) => {
+        struct $resource {
+            data: sync::Arc<[$elem]>,
+        }
+
+        unsafe extern "C" fn $data_fn(this: *mut os::raw::c_void) -> *const $elem {
+            (*(this as *const $resource)).data.as_ptr()
+        }
+
+        unsafe extern "C" fn $length_fn(this: *mut os::raw::c_void) -> usize {
+            (*(this as *const $resource)).data.len()
+        }
+
+        unsafe extern "C" fn $dispose_fn(this: *mut os::raw::c_void) {
+            drop(Box::from_raw(this as *mut $resource));
+        }
+
+        const $functions: v8_sys::impls::$functions_ty =
+            v8_sys::impls::$functions_ty {
+                Data: Some($data_fn),
+                Length: Some($length_fn),
+                Dispose: Some($dispose_fn),
+            };
+    }
+}
+
+external_string_resource!(OneByteResource,
+                          u8,
+                          one_byte_resource_data,
+                          one_byte_resource_length,
+                          one_byte_resource_dispose,
+                          ExternalOneByteStringResourceFunctions,
+                          ONE_BYTE_RESOURCE_FUNCTIONS);
+external_string_resource!(TwoByteResource,
+                          u16,
+                          two_byte_resource_data,
+                          two_byte_resource_length,
+                          two_byte_resource_dispose,
+                          ExternalTwoByteStringResourceFunctions,
+                          TWO_BYTE_RESOURCE_FUNCTIONS);
+
 impl Symbol {
     /// Access global symbol registry.
     ///
@@ -909,6 +1670,12 @@ impl Number {
     }
 }
 
+impl<'a> From<(&'a isolate::Isolate, f64)> for Number {
+    fn from((isolate, value): (&'a isolate::Isolate, f64)) -> Number {
+        Number::new(isolate, value)
+    }
+}
+
 impl Integer {
     pub fn new(isolate: &isolate::Isolate, value: i32) -> Integer {
         let raw = unsafe {
@@ -941,6 +1708,18 @@ impl Integer {
     }
 }
 
+impl<'a> From<(&'a isolate::Isolate, i32)> for Integer {
+    fn from((isolate, value): (&'a isolate::Isolate, i32)) -> Integer {
+        Integer::new(isolate, value)
+    }
+}
+
+impl<'a> From<(&'a isolate::Isolate, u32)> for Integer {
+    fn from((isolate, value): (&'a isolate::Isolate, u32)) -> Integer {
+        Integer::new_from_unsigned(isolate, value)
+    }
+}
+
 impl Int32 {
     pub fn value(&self) -> i32 {
         unsafe { util::invoke(&self.0, |c| v8_sys::v8_Int32_Value(c, self.1)).unwrap() }
@@ -973,6 +1752,127 @@ impl Uint32 {
     }
 }
 
+impl BigInt {
+    /// Creates a `BigInt` holding `value`, losslessly.
+    pub fn new_from_i64(isolate: &isolate::Isolate, value: i64) -> BigInt {
+        let raw = unsafe {
+            util::invoke(&isolate, |c| v8_sys::v8_BigInt_New(c, isolate.as_raw(), value)).unwrap()
+        };
+        BigInt(isolate.clone(), raw)
+    }
+
+    /// Creates a `BigInt` holding `value`, losslessly.
+    pub fn new_from_u64(isolate: &isolate::Isolate, value: u64) -> BigInt {
+        let raw = unsafe {
+            util::invoke(&isolate,
+                         |c| v8_sys::v8_BigInt_NewFromUnsigned(c, isolate.as_raw(), value))
+                .unwrap()
+        };
+        BigInt(isolate.clone(), raw)
+    }
+
+    /// This `BigInt`'s value truncated to an `i64`, together with whether the truncation was
+    /// lossless (`false` if the value didn't fit in 64 bits).
+    pub fn to_i64(&self) -> (i64, bool) {
+        let raw = unsafe {
+            util::invoke(&self.0, |c| v8_sys::v8_BigInt_Int64Value(c, self.1)).unwrap()
+        };
+        (raw.value, raw.lossless)
+    }
+
+    /// This `BigInt`'s value truncated to a `u64`, together with whether the truncation was
+    /// lossless (`false` if the value didn't fit in 64 bits, or was negative).
+    pub fn to_u64(&self) -> (u64, bool) {
+        let raw = unsafe {
+            util::invoke(&self.0, |c| v8_sys::v8_BigInt_Uint64Value(c, self.1)).unwrap()
+        };
+        (raw.value, raw.lossless)
+    }
+
+    /// The number of 64-bit words needed to hold this `BigInt`'s magnitude, as returned by
+    /// `to_words`.
+    pub fn word_count(&self) -> usize {
+        unsafe { util::invoke(&self.0, |c| v8_sys::v8_BigInt_WordCount(c, self.1)).unwrap() as usize }
+    }
+
+    /// Decomposes this `BigInt` into a sign bit (`true` for negative) and its magnitude as
+    /// little-endian 64-bit words, mirroring V8's `BigInt::ToWordsArray`.
+    pub fn to_words(&self) -> (bool, Vec<u64>) {
+        let mut words = vec![0u64; self.word_count()];
+        let sign_bit = unsafe {
+            util::invoke(&self.0, |c| {
+                    v8_sys::v8_BigInt_ToWordsArray(c, self.1, words.len(), words.as_mut_ptr())
+                })
+                .unwrap()
+        };
+        (sign_bit, words)
+    }
+
+    /// Creates a `BigInt` of arbitrary precision from a sign bit (`true` for negative) and its
+    /// magnitude as little-endian 64-bit words, mirroring V8's `BigInt::NewFromWords`.
+    ///
+    /// Fails if `words` is longer than V8's maximum `BigInt` length.
+    pub fn new_from_words(isolate: &isolate::Isolate,
+                          context: &context::Context,
+                          sign_bit: bool,
+                          words: &[u64])
+                          -> error::Result<BigInt> {
+        let raw = unsafe {
+            try!(util::invoke_ctx(isolate, context, |c| {
+                v8_sys::v8_BigInt_NewFromWords(c,
+                                               context.as_raw(),
+                                               sign_bit,
+                                               words.len(),
+                                               words.as_ptr())
+            }))
+        };
+        Ok(BigInt(isolate.clone(), raw))
+    }
+
+    /// Creates a BigInt from a set of raw pointers.
+    pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8_sys::BigIntRef) -> BigInt {
+        BigInt(isolate.clone(), raw)
+    }
+
+    /// Returns the underlying raw pointer behind this BigInt.
+    pub fn as_raw(&self) -> v8_sys::BigIntRef {
+        self.1
+    }
+}
+
+/// Flags controlling how a property defined via `Object::define_own_property` or
+/// `Object::set_accessor` behaves, mirroring V8's `v8::PropertyAttribute` bitmask.
+///
+/// Unlike V8's genuinely exhaustive enums, `PropertyAttribute`'s variants are meant to be
+/// combined (e.g. `READ_ONLY | DONT_ENUM`), so this is a plain bit-set newtype rather than a
+/// Rust `enum`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attributes(u32);
+
+impl Attributes {
+    /// No attributes: the property is writable, enumerable and configurable.
+    pub const NONE: Attributes = Attributes(0);
+    /// The property cannot be assigned to.
+    pub const READ_ONLY: Attributes = Attributes(1 << 0);
+    /// The property doesn't show up in `for...in` or `Object.keys`.
+    pub const DONT_ENUM: Attributes = Attributes(1 << 1);
+    /// The property cannot be deleted or reconfigured.
+    pub const DONT_DELETE: Attributes = Attributes(1 << 2);
+
+    /// Returns the underlying raw bitmask behind these attributes.
+    pub fn as_raw(self) -> v8_sys::PropertyAttribute {
+        unsafe { mem::transmute(self.0) }
+    }
+}
+
+impl ops::BitOr for Attributes {
+    type Output = Attributes;
+
+    fn bitor(self, rhs: Attributes) -> Attributes {
+        Attributes(self.0 | rhs.0)
+    }
+}
+
 impl Object {
     pub fn new(isolate: &isolate::Isolate, context: &context::Context) -> Object {
         let _g = context.make_current();
@@ -1046,9 +1946,86 @@ impl Object {
         }
     }
 
-    pub fn get(&self, context: &context::Context, key: &Value) -> Value {
+    /// Defines `key` on this object with `value`, applying `attrs` instead of the all-writable,
+    /// all-enumerable, all-configurable defaults `create_data_property` uses.
+    pub fn define_own_property(&self,
+                               context: &context::Context,
+                               key: &Name,
+                               value: &Value,
+                               attrs: Attributes)
+                               -> bool {
         unsafe {
-            util::invoke_ctx(&self.0,
+            let m = util::invoke_ctx(&self.0, context, |c| {
+                    v8_sys::v8_Object_DefineOwnProperty(c,
+                                                 self.1,
+                                                 context.as_raw(),
+                                                 key.as_raw(),
+                                                 value.as_raw(),
+                                                 attrs.as_raw())
+                })
+                .unwrap();
+
+            assert!( m.is_set);
+             m.value
+        }
+    }
+
+    /// Registers a computed property named `key` on this object, backed by `getter` and (if
+    /// given) `setter`, following the same boxed-callback-in-an-`External` pattern
+    /// `ObjectTemplate::set_accessor` uses to smuggle its callback through V8.
+    pub fn set_accessor(&self,
+                        context: &context::Context,
+                        key: &Name,
+                        getter: Box<AccessorGetter>,
+                        setter: Option<Box<AccessorSetter>>,
+                        attrs: Attributes)
+                        -> bool {
+        unsafe {
+            let has_setter = setter.is_some();
+            let accessor_ptr = Box::into_raw(Box::new(util::Accessor {
+                getter: getter,
+                setter: setter,
+            }));
+            let data = External::new::<util::Accessor>(&self.0, accessor_ptr);
+            let setter_trampoline = if has_setter {
+                Some(util::accessor_setter_callback as _)
+            } else {
+                None
+            };
+
+            let m = util::invoke_ctx(&self.0, context, |c| {
+                    v8_sys::v8_Object_SetAccessor(c,
+                                                  self.1,
+                                                  context.as_raw(),
+                                                  key.as_raw(),
+                                                  Some(util::accessor_getter_callback),
+                                                  setter_trampoline,
+                                                  data.as_raw(),
+                                                  attrs.as_raw())
+                })
+                .unwrap();
+
+            assert!( m.is_set);
+             m.value
+        }
+    }
+
+    /// Returns the property descriptor (an object with `value`/`get`/`set`,
+    /// `writable`/`enumerable`/`configurable` fields, per `Object.getOwnPropertyDescriptor`) for
+    /// `key` on this object, or `undefined` if `key` isn't an own property.
+    pub fn get_own_property_descriptor(&self, context: &context::Context, key: &Name) -> Value {
+        unsafe {
+            util::invoke_ctx(&self.0, context, |c| {
+                    v8_sys::v8_Object_GetOwnPropertyDescriptor(c, self.1, context.as_raw(), key.as_raw())
+                })
+                .map(|p| Value(self.0.clone(), p))
+                .unwrap()
+        }
+    }
+
+    pub fn get(&self, context: &context::Context, key: &Value) -> Value {
+        unsafe {
+            util::invoke_ctx(&self.0,
                              context,
                              |c| v8_sys::v8_Object_Get_Key(c, self.1, context.as_raw(), key.as_raw()))
                 .map(|p| Value(self.0.clone(), p))
@@ -1437,6 +2414,77 @@ impl Object {
     pub fn as_raw(&self) -> v8_sys::ObjectRef {
         self.1
     }
+
+    /// Returns an iterator over this object's own enumerable `(key, value)` pairs, in the same
+    /// order as `get_own_property_names`.
+    pub fn entries<'a>(&'a self, context: &'a context::Context) -> ObjectEntries<'a> {
+        ObjectEntries {
+            object: self,
+            context: context,
+            keys: self.get_own_property_names(context),
+            index: 0,
+        }
+    }
+}
+
+impl PartialEq for Object {
+    fn eq(&self, other: &Object) -> bool {
+        // `get_identity_hash` is a small, collidable hash, not a unique identity (V8 doesn't
+        // promise distinct objects get distinct hashes), so equality can't be based on it the
+        // way `Hash` below is. Route through `Value::strict_equals` instead, the same real
+        // identity/reference comparison `Value`'s own `PartialEq` uses. `Object` and `Value`
+        // share layout (every JS value type in this module is a transparent `(isolate, raw)`
+        // pair), so reinterpreting the reference is the same trick `downcast!`'s `into_*`
+        // conversions use on owned values.
+        let this: &Value = unsafe { mem::transmute(self) };
+        let that: &Value = unsafe { mem::transmute(other) };
+        this.strict_equals(that)
+    }
+}
+
+impl Eq for Object {}
+
+impl hash::Hash for Object {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        // Safe as a `Hash` despite not being a unique identity: two objects that compare equal
+        // above necessarily share the same stored identity hash, so hashing by it can't put
+        // equal objects in different buckets -- it can only make unequal objects collide, which
+        // `HashMap`/`HashSet` already tolerate.
+        self.get_identity_hash().hash(state)
+    }
+}
+
+/// An iterator over an `Object`'s own enumerable property `(key, value)` pairs, returned by
+/// `Object::entries` and the `IntoIterator` impl for `(&Object, &Context)`.
+pub struct ObjectEntries<'a> {
+    object: &'a Object,
+    context: &'a context::Context,
+    keys: Array,
+    index: u32,
+}
+
+impl<'a> Iterator for ObjectEntries<'a> {
+    type Item = (Value, Value);
+
+    fn next(&mut self) -> Option<(Value, Value)> {
+        if self.index >= self.keys.length() {
+            return None;
+        }
+
+        let key = self.keys.get_index(self.context, self.index);
+        let value = self.object.get(self.context, &key);
+        self.index += 1;
+        Some((key, value))
+    }
+}
+
+impl<'a> IntoIterator for (&'a Object, &'a context::Context) {
+    type Item = (Value, Value);
+    type IntoIter = ObjectEntries<'a>;
+
+    fn into_iter(self) -> ObjectEntries<'a> {
+        self.0.entries(self.1)
+    }
 }
 
 impl Array {
@@ -1456,6 +2504,11 @@ impl Array {
         Array(isolate.clone(), raw)
     }
 
+    /// Returns the number of elements in this array.
+    pub fn length(&self) -> u32 {
+        unsafe { util::invoke(&self.0, |c| v8_sys::v8_Array_Length(c, self.1)).unwrap() as u32 }
+    }
+
     /// Returns the underlying raw pointer behind this array.
     pub fn as_raw(&self) -> v8_sys::ArrayRef {
         self.1
@@ -1468,6 +2521,23 @@ impl Map {
         Map(isolate.clone(), raw)
     }
 
+    /// Collects `(key, value)` pairs (e.g. from a `HashMap<Value, Value>`) into a new `Map` owned
+    /// by `isolate`.
+    ///
+    /// Unlike `std::iter::FromIterator`, which has no way to accept an `isolate` parameter, this
+    /// doesn't need to scavenge one from the iterator's first element, so an empty iterator
+    /// produces an empty `Map` instead of panicking.
+    pub fn from_entries<I>(isolate: &isolate::Isolate, iter: I) -> Map
+        where I: IntoIterator<Item = (Value, Value)>
+    {
+        let context = isolate.current_context().unwrap_or_else(|| context::Context::new(isolate));
+        let map = Map::new(isolate);
+        for (key, value) in iter {
+            map.set(&context, &key, &value);
+        }
+        map
+    }
+
     pub fn size(&self) -> usize {
         unsafe { util::invoke(&self.0, |c| v8_sys::v8_Map_Size(c, self.1)).unwrap() as usize }
     }
@@ -1526,6 +2596,33 @@ impl Map {
         Array(self.0.clone(), raw)
     }
 
+    /// Returns an iterator over this map's `(key, value)` pairs, in insertion order.
+    ///
+    /// Walks `as_array`'s flattened `[k0, v0, k1, v1, ...]` result lazily instead of forcing
+    /// callers to juggle even/odd indices themselves.
+    pub fn entries<'a>(&self, context: &'a context::Context) -> MapEntries<'a> {
+        MapEntries {
+            array: self.as_array(),
+            context: context,
+            index: 0,
+        }
+    }
+
+    /// Alias for `entries`.
+    pub fn iter<'a>(&self, context: &'a context::Context) -> MapEntries<'a> {
+        self.entries(context)
+    }
+
+    /// Returns an iterator over this map's keys, in insertion order.
+    pub fn keys<'a>(&self, context: &'a context::Context) -> Box<Iterator<Item = Value> + 'a> {
+        Box::new(self.entries(context).map(|(key, _)| key))
+    }
+
+    /// Returns an iterator over this map's values, in insertion order.
+    pub fn values<'a>(&self, context: &'a context::Context) -> Box<Iterator<Item = Value> + 'a> {
+        Box::new(self.entries(context).map(|(_, value)| value))
+    }
+
     /// Creates a map from a set of raw pointers.
     pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8_sys::MapRef) -> Map {
         Map(isolate.clone(), raw)
@@ -1537,6 +2634,47 @@ impl Map {
     }
 }
 
+/// An iterator over a `Map`'s `(key, value)` pairs, returned by `Map::entries`/`Map::iter` and
+/// the `IntoIterator` impl for `(&Map, &Context)`.
+pub struct MapEntries<'a> {
+    array: Array,
+    context: &'a context::Context,
+    index: u32,
+}
+
+impl<'a> Iterator for MapEntries<'a> {
+    type Item = (Value, Value);
+
+    fn next(&mut self) -> Option<(Value, Value)> {
+        if self.index >= self.array.length() {
+            return None;
+        }
+
+        let key = self.array.get_index(self.context, self.index);
+        let value = self.array.get_index(self.context, self.index + 1);
+        self.index += 2;
+        Some((key, value))
+    }
+}
+
+impl<'a> IntoIterator for (&'a Map, &'a context::Context) {
+    type Item = (Value, Value);
+    type IntoIter = MapEntries<'a>;
+
+    fn into_iter(self) -> MapEntries<'a> {
+        self.0.entries(self.1)
+    }
+}
+
+impl iter::Extend<(Value, Value)> for Map {
+    fn extend<I: IntoIterator<Item = (Value, Value)>>(&mut self, iter: I) {
+        let context = self.0.current_context().unwrap_or_else(|| context::Context::new(&self.0));
+        for (key, value) in iter {
+            self.set(&context, &key, &value);
+        }
+    }
+}
+
 impl Set {
     /// Creates a new empty Set.
     pub fn new(isolate: &isolate::Isolate) -> Set {
@@ -1544,6 +2682,22 @@ impl Set {
         Set(isolate.clone(), raw)
     }
 
+    /// Collects values (e.g. from a `Vec<Value>`) into a new `Set` owned by `isolate`.
+    ///
+    /// Unlike `std::iter::FromIterator`, which has no way to accept an `isolate` parameter, this
+    /// doesn't need to scavenge one from the iterator's first element, so an empty iterator
+    /// produces an empty `Set` instead of panicking.
+    pub fn from_values<I>(isolate: &isolate::Isolate, iter: I) -> Set
+        where I: IntoIterator<Item = Value>
+    {
+        let context = isolate.current_context().unwrap_or_else(|| context::Context::new(isolate));
+        let set = Set::new(isolate);
+        for value in iter {
+            set.add(&context, &value);
+        }
+        set
+    }
+
     pub fn size(&self) -> usize {
         unsafe { util::invoke(&self.0, |c| v8_sys::v8_Set_Size(c, self.1)).unwrap() as usize }
     }
@@ -1591,6 +2745,17 @@ impl Set {
         Array(self.0.clone(), raw)
     }
 
+    /// Returns an iterator over this set's values, in insertion order.
+    ///
+    /// Walks `as_array`'s result lazily instead of forcing callers to index it by hand.
+    pub fn iter<'a>(&self, context: &'a context::Context) -> SetValues<'a> {
+        SetValues {
+            array: self.as_array(),
+            context: context,
+            index: 0,
+        }
+    }
+
     /// Creates a set from a set of raw pointers.
     pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8_sys::SetRef) -> Set {
         Set(isolate.clone(), raw)
@@ -1602,6 +2767,46 @@ impl Set {
     }
 }
 
+/// An iterator over a `Set`'s values, returned by `Set::iter` and the `IntoIterator` impl for
+/// `(&Set, &Context)`.
+pub struct SetValues<'a> {
+    array: Array,
+    context: &'a context::Context,
+    index: u32,
+}
+
+impl<'a> Iterator for SetValues<'a> {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        if self.index >= self.array.length() {
+            return None;
+        }
+
+        let value = self.array.get_index(self.context, self.index);
+        self.index += 1;
+        Some(value)
+    }
+}
+
+impl<'a> IntoIterator for (&'a Set, &'a context::Context) {
+    type Item = Value;
+    type IntoIter = SetValues<'a>;
+
+    fn into_iter(self) -> SetValues<'a> {
+        self.0.iter(self.1)
+    }
+}
+
+impl iter::Extend<Value> for Set {
+    fn extend<I: IntoIterator<Item = Value>>(&mut self, iter: I) {
+        let context = self.0.current_context().unwrap_or_else(|| context::Context::new(&self.0));
+        for value in iter {
+            self.add(&context, &value);
+        }
+    }
+}
+
 impl Function {
     /// Create a function in the current execution context for a given callback.
     pub fn new(isolate: &isolate::Isolate,
@@ -1609,16 +2814,32 @@ impl Function {
                length: usize,
                callback: Box<FunctionCallback>)
                -> Function {
+        Function::new_with_data(isolate, context, length, None, callback)
+    }
+
+    /// Like `new`, but also attaches `data` to the call site, surfaced to the callback as
+    /// `FunctionCallbackInfo::data`.  Lets a native host object (e.g. several methods on the same
+    /// wrapped Rust struct) share state through V8's own `Data` slot instead of each callback
+    /// closure capturing its own copy.
+    pub fn new_with_data(isolate: &isolate::Isolate,
+                         context: &context::Context,
+                         length: usize,
+                         data: Option<External>,
+                         callback: Box<FunctionCallback>)
+                         -> Function {
         unsafe {
             let callback_ptr = Box::into_raw(Box::new(callback));
             let callback_ext =
                 External::new::<Box<FunctionCallback>>(&isolate, callback_ptr);
 
             let template = template::ObjectTemplate::new(isolate);
-            template.set_internal_field_count(1);
+            template.set_internal_field_count(2);
 
             let closure = template.new_instance(context);
             closure.set_internal_field(0, &callback_ext);
+            if let Some(ref data) = data {
+                closure.set_internal_field(1, data);
+            }
 
             let raw = util::invoke_ctx(&isolate, context, |c| {
                     v8_sys::v8_Function_New(c,
@@ -1635,40 +2856,64 @@ impl Function {
 
     /// Call an Object as a function if a callback is set by the
     /// ObjectTemplate::SetCallAsFunctionHandler method.
+    ///
+    /// If the isolate was configured with `Isolate::set_execution_timeout`/`set_heap_limit`, or
+    /// `Isolate::cancel` is called from another thread while this is running, this returns
+    /// `error::ErrorKind::Terminated`/`error::ErrorKind::OutOfMemory` instead.
     pub fn call(&self, context: &context::Context, args: &[&Value]) -> error::Result<Value> {
         let mut arg_ptrs = args.iter().map(|v| v.1).collect::<Vec<_>>();
-        let raw = unsafe {
-            try!(util::invoke_ctx(&self.0, context, |c| {
+        self.0.run_guarded(|| unsafe {
+            let raw = try!(util::invoke_ctx(&self.0, context, |c| {
                 v8_sys::v8_Function_Call(c,
                                   self.1,
                                   context.as_raw(),
                                   ptr::null_mut(),
                                   arg_ptrs.len() as i32,
                                   arg_ptrs.as_mut_ptr())
-            }))
-        };
-        Ok(Value(self.0.clone(), raw))
+            }));
+            Ok(Value(self.0.clone(), raw))
+        })
     }
 
     /// Call an Object as a function if a callback is set by the
     /// ObjectTemplate::SetCallAsFunctionHandler method.
+    ///
+    /// If the isolate was configured with `Isolate::set_execution_timeout`/`set_heap_limit`, or
+    /// `Isolate::cancel` is called from another thread while this is running, this returns
+    /// `error::ErrorKind::Terminated`/`error::ErrorKind::OutOfMemory` instead.
     pub fn call_with_this(&self,
                           context: &context::Context,
                           this: &Value,
                           args: &[&Value])
                           -> error::Result<Value> {
         let mut arg_ptrs = args.iter().map(|v| v.1).collect::<Vec<_>>();
-        let raw = unsafe {
-            try!(util::invoke_ctx(&self.0, context, |c| {
+        self.0.run_guarded(|| unsafe {
+            let raw = try!(util::invoke_ctx(&self.0, context, |c| {
                 v8_sys::v8_Function_Call(c,
                                   self.1,
                                   context.as_raw(),
                                   this.as_raw(),
                                   arg_ptrs.len() as i32,
                                   arg_ptrs.as_mut_ptr())
-            }))
-        };
-        Ok(Value(self.0.clone(), raw))
+            }));
+            Ok(Value(self.0.clone(), raw))
+        })
+    }
+
+    /// Creates a function in the current execution context wrapping a native closure whose
+    /// argument and return types `FromValue`/`ToValue` can marshal automatically, instead of
+    /// hand-decoding `info.args` the way `Function::new`'s raw `FunctionCallback` requires.
+    ///
+    /// `callback`'s `AsCallback::arity` becomes the function's own `length`. Calling it with
+    /// fewer arguments than that, or with an argument `FromValue` can't convert, throws a
+    /// `TypeError` without invoking `callback`.
+    pub fn wrap<F>(isolate: &isolate::Isolate, context: &context::Context, callback: F) -> Function
+        where F: AsCallback
+    {
+        let context = context.clone();
+        Function::new(isolate, &context, F::arity(), Box::new(move |info| {
+            callback.call(&context, &info.args)
+        }))
     }
 
     /// Creates a function from a set of raw pointers.
@@ -1682,6 +2927,136 @@ impl Function {
     }
 }
 
+impl Promise {
+    /// Where this promise is in its lifecycle, together with its fulfillment value or rejection
+    /// reason once it has one.
+    pub fn state(&self) -> PromiseState {
+        let raw = unsafe { util::invoke(&self.0, |c| v8_sys::v8_Promise_State(c, self.1)).unwrap() };
+        match raw {
+            0 => PromiseState::Pending,
+            1 => PromiseState::Fulfilled(self.result()),
+            2 => PromiseState::Rejected(self.result()),
+            s => panic!("unknown v8::Promise::PromiseState {}", s),
+        }
+    }
+
+    /// The promise's fulfillment value or rejection reason.
+    ///
+    /// Only meaningful once `state()` is no longer `Pending`; while pending, this returns
+    /// `undefined`.
+    pub fn result(&self) -> Value {
+        let raw = unsafe { util::invoke(&self.0, |c| v8_sys::v8_Promise_Result(c, self.1)).unwrap() };
+        Value(self.0.clone(), raw)
+    }
+
+    /// Registers `on_fulfilled` to run with this promise's fulfillment value once it resolves,
+    /// returning a new promise that settles with `on_fulfilled`'s return value (or its thrown
+    /// exception as a rejection).
+    pub fn then(&self,
+               context: &context::Context,
+               on_fulfilled: Box<FunctionCallback>)
+               -> error::Result<Promise> {
+        let handler = Function::new(&self.0, context, 1, on_fulfilled);
+        let raw = unsafe {
+            try!(util::invoke_ctx(&self.0, context, |c| {
+                v8_sys::v8_Promise_Then(c, self.1, context.as_raw(), handler.as_raw())
+            }))
+        };
+        Ok(Promise(self.0.clone(), raw))
+    }
+
+    /// Registers `on_rejected` to run with this promise's rejection reason if it rejects,
+    /// returning a new promise that settles with `on_rejected`'s return value. Equivalent to
+    /// `then`ing only a rejection handler.
+    pub fn catch(&self,
+                context: &context::Context,
+                on_rejected: Box<FunctionCallback>)
+                -> error::Result<Promise> {
+        let handler = Function::new(&self.0, context, 1, on_rejected);
+        let raw = unsafe {
+            try!(util::invoke_ctx(&self.0, context, |c| {
+                v8_sys::v8_Promise_Catch(c, self.1, context.as_raw(), handler.as_raw())
+            }))
+        };
+        Ok(Promise(self.0.clone(), raw))
+    }
+
+    /// Adapts this promise into a `Future` resolving to its settled value, so JS async code can be
+    /// `await`ed from Rust instead of polling `state()` by hand. Polling it drains `context`'s
+    /// microtask queue; see `future::PromiseFuture`.
+    pub fn into_future<'c>(self, context: &'c context::Context) -> future::PromiseFuture<'c> {
+        future::PromiseFuture::new(context, self)
+    }
+
+    /// Creates a promise from a set of raw pointers.
+    pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8_sys::PromiseRef) -> Promise {
+        Promise(isolate.clone(), raw)
+    }
+
+    /// Returns the underlying raw pointer behind this promise.
+    pub fn as_raw(&self) -> v8_sys::PromiseRef {
+        self.1
+    }
+}
+
+impl PromiseResolver {
+    /// Creates a new resolver bound to `context`, with a fresh, unsettled `Promise` behind it.
+    pub fn new(isolate: &isolate::Isolate, context: &context::Context) -> error::Result<PromiseResolver> {
+        let raw = unsafe {
+            try!(util::invoke_ctx(isolate,
+                                  context,
+                                  |c| v8_sys::v8_PromiseResolver_New(c, context.as_raw())))
+        };
+        Ok(PromiseResolver(isolate.clone(), raw))
+    }
+
+    /// The promise controlled by this resolver.
+    pub fn get_promise(&self) -> Promise {
+        let raw = unsafe {
+            util::invoke(&self.0, |c| v8_sys::v8_PromiseResolver_GetPromise(c, self.1)).unwrap()
+        };
+        Promise(self.0.clone(), raw)
+    }
+
+    /// Fulfills the controlled promise with `value`, so its `then` reactions run the next time the
+    /// microtask queue is drained. Returns `false` if the promise was already settled.
+    pub fn resolve(&self, context: &context::Context, value: &Value) -> bool {
+        unsafe {
+            let m = util::invoke_ctx(&self.0, context, |c| {
+                    v8_sys::v8_PromiseResolver_Resolve(c, self.1, context.as_raw(), value.as_raw())
+                })
+                .unwrap();
+
+            assert!(m.is_set);
+            m.value
+        }
+    }
+
+    /// Rejects the controlled promise with `value`, so its `catch` reactions run the next time the
+    /// microtask queue is drained. Returns `false` if the promise was already settled.
+    pub fn reject(&self, context: &context::Context, value: &Value) -> bool {
+        unsafe {
+            let m = util::invoke_ctx(&self.0, context, |c| {
+                    v8_sys::v8_PromiseResolver_Reject(c, self.1, context.as_raw(), value.as_raw())
+                })
+                .unwrap();
+
+            assert!(m.is_set);
+            m.value
+        }
+    }
+
+    /// Creates a resolver from a set of raw pointers.
+    pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8_sys::PromiseResolverRef) -> PromiseResolver {
+        PromiseResolver(isolate.clone(), raw)
+    }
+
+    /// Returns the underlying raw pointer behind this resolver.
+    pub fn as_raw(&self) -> v8_sys::PromiseResolverRef {
+        self.1
+    }
+}
+
 impl External {
     pub unsafe fn new<A>(isolate: &isolate::Isolate, value: *mut A) -> External {
         let raw = util::invoke(&isolate, |c| {
@@ -1695,6 +3070,30 @@ impl External {
         util::invoke(&self.0, |c| v8_sys::v8_External_Value(c, self.1)).unwrap() as *mut A
     }
 
+    /// Creates a new external that safely wraps an arbitrary Rust value.
+    ///
+    /// Unlike `new`, this doesn't require `unsafe`: `value` is boxed, type-tagged, and registered
+    /// with `isolate` so that it is dropped when the isolate is disposed rather than leaked.
+    /// Recover it with `downcast`, which checks the tag instead of blindly reinterpreting memory.
+    pub fn new_typed<T: 'static>(isolate: &isolate::Isolate, value: T) -> External {
+        let boxed: Box<any::Any> = Box::new(value);
+        let raw = isolate.register_external(boxed);
+        unsafe { External::new(isolate, raw) }
+    }
+
+    /// Returns the value stashed behind this external by `new_typed`, or `None` if it doesn't
+    /// hold a `T` (including if it wasn't created by `new_typed` at all, e.g. one made via the
+    /// `unsafe fn new`, one belonging to another isolate, or one handed in from JS).
+    pub fn downcast<T: 'static>(&self) -> Option<&T> {
+        unsafe {
+            let boxed = self.value::<Box<any::Any>>();
+            if !self.0.owns_external(boxed) {
+                return None;
+            }
+            (**boxed).downcast_ref::<T>()
+        }
+    }
+
     /// Creates an external from a set of raw pointers.
     pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8_sys::ExternalRef) -> External {
         External(isolate.clone(), raw)
@@ -1706,6 +3105,497 @@ impl External {
     }
 }
 
+/// Byte order, used by `DataView`'s typed accessors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// A zero-copy, borrowed view of an `ArrayBuffer`'s bytes, backed directly by V8's allocation.
+///
+/// Borrowing `&self`/`&mut self` from the `ArrayBuffer` this was obtained from keeps the
+/// underlying buffer reachable (the same way any other handle in this crate keeps its value
+/// alive), so the view never outlives the memory it points into.
+pub struct BackingStore<'a> {
+    data: *mut u8,
+    len: usize,
+    _buffer: marker::PhantomData<&'a ArrayBuffer>,
+}
+
+impl<'a> BackingStore<'a> {
+    /// Borrows the buffer's bytes.
+    pub fn as_slice(&self) -> &'a [u8] {
+        unsafe { slice::from_raw_parts(self.data, self.len) }
+    }
+
+    /// Mutably borrows the buffer's bytes.
+    ///
+    /// # Safety
+    ///
+    /// The returned slice's lifetime `'a` is the backing buffer's lifetime, not this `&mut self`
+    /// borrow, so the borrow checker can't stop two calls on the same `BackingStore` (or on two
+    /// `BackingStore`s obtained from the same `ArrayBuffer`) from handing out aliasing `&mut
+    /// [u8]`s. The caller must ensure no other `&mut`/`&` slice into the same backing store is
+    /// live at the same time.
+    pub unsafe fn as_mut_slice(&mut self) -> &'a mut [u8] {
+        slice::from_raw_parts_mut(self.data, self.len)
+    }
+}
+
+/// A zero-copy, borrowed view of a `SharedArrayBuffer`'s bytes.
+///
+/// Unlike `BackingStore`, this never hands out a `&mut [u8]`: the memory may be concurrently
+/// written from another agent (e.g. another Worker) sharing the same buffer, which would
+/// otherwise violate Rust's aliasing rules.
+pub struct SharedBackingStore<'a> {
+    data: *mut u8,
+    len: usize,
+    _buffer: marker::PhantomData<&'a SharedArrayBuffer>,
+}
+
+impl<'a> SharedBackingStore<'a> {
+    /// Borrows the buffer's bytes.
+    pub fn as_slice(&self) -> &'a [u8] {
+        unsafe { slice::from_raw_parts(self.data, self.len) }
+    }
+}
+
+impl ArrayBuffer {
+    /// Creates a new, zeroed `ArrayBuffer` of `byte_length` bytes.
+    pub fn new(isolate: &isolate::Isolate, byte_length: usize) -> ArrayBuffer {
+        let raw = unsafe {
+            util::invoke(&isolate, |c| {
+                    v8_sys::v8_ArrayBuffer_New(c, isolate.as_raw(), byte_length)
+                })
+                .unwrap()
+        };
+        ArrayBuffer(isolate.clone(), raw)
+    }
+
+    /// Adopts `data` as this buffer's backing store, without copying it: `data` is handed
+    /// directly to V8, which frees it (via the same global allocator path as any other
+    /// `Vec`-backed allocation) once the `ArrayBuffer` is garbage collected.
+    pub fn new_from_bytes(isolate: &isolate::Isolate, data: Vec<u8>) -> ArrayBuffer {
+        let mut data = data;
+        let len = data.len();
+        let cap = data.capacity();
+        let ptr = data.as_mut_ptr();
+        mem::forget(data);
+
+        let deleter_data = Box::into_raw(Box::new(cap)) as *mut os::raw::c_void;
+
+        let raw = unsafe {
+            util::invoke(&isolate, |c| {
+                    v8_sys::v8_ArrayBuffer_New_External(c,
+                                                        isolate.as_raw(),
+                                                        ptr as *mut os::raw::c_void,
+                                                        len,
+                                                        Some(free_external_bytes),
+                                                        deleter_data)
+                })
+                .unwrap()
+        };
+        ArrayBuffer(isolate.clone(), raw)
+    }
+
+    /// Wraps `data` (`len` bytes) as this buffer's backing store, without copying and without
+    /// taking ownership: unlike `new_from_bytes`, V8 will never free this memory, so the caller
+    /// must keep it valid for as long as this `ArrayBuffer` (and any views over it) are
+    /// reachable, and is responsible for freeing it afterwards.
+    ///
+    /// This is the building block for letting external FFI consumers (e.g. a `snappy`-style
+    /// `*const u8`/`size_t` routine) operate directly on memory a JS buffer also sees, with
+    /// neither side copying.
+    pub unsafe fn new_with_backing(isolate: &isolate::Isolate,
+                                   data: *mut u8,
+                                   len: usize)
+                                   -> ArrayBuffer {
+        let raw = util::invoke(&isolate, |c| {
+                v8_sys::v8_ArrayBuffer_New_External(c,
+                                                    isolate.as_raw(),
+                                                    data as *mut os::raw::c_void,
+                                                    len,
+                                                    None,
+                                                    ptr::null_mut())
+            })
+            .unwrap();
+        ArrayBuffer(isolate.clone(), raw)
+    }
+
+    /// The number of bytes in this buffer.
+    pub fn byte_length(&self) -> usize {
+        unsafe {
+            util::invoke(&self.0, |c| v8_sys::v8_ArrayBuffer_ByteLength(c, self.1)).unwrap() as usize
+        }
+    }
+
+    /// Borrows this buffer's bytes directly from V8's allocation, without copying.
+    ///
+    /// Returns `None` if the buffer has been detached (e.g. via a `postMessage` transfer).
+    pub fn get_backing_store(&self) -> Option<BackingStore> {
+        let contents = unsafe {
+            util::invoke(&self.0, |c| v8_sys::v8_ArrayBuffer_GetContents(c, self.1)).unwrap()
+        };
+        if contents.data.is_null() {
+            None
+        } else {
+            Some(BackingStore {
+                data: contents.data as *mut u8,
+                len: contents.length,
+                _buffer: marker::PhantomData,
+            })
+        }
+    }
+
+    /// Creates an array buffer from a set of raw pointers.
+    pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8_sys::ArrayBufferRef) -> ArrayBuffer {
+        ArrayBuffer(isolate.clone(), raw)
+    }
+
+    /// Returns the underlying raw pointer behind this array buffer.
+    pub fn as_raw(&self) -> v8_sys::ArrayBufferRef {
+        self.1
+    }
+}
+
+unsafe extern "C" fn free_external_bytes(data: *mut os::raw::c_void,
+                                         length: usize,
+                                         deleter_data: *mut os::raw::c_void) {
+    let cap = *Box::from_raw(deleter_data as *mut usize);
+    drop(Vec::from_raw_parts(data as *mut u8, length, cap));
+}
+
+impl SharedArrayBuffer {
+    /// The number of bytes in this buffer.
+    pub fn byte_length(&self) -> usize {
+        unsafe {
+            util::invoke(&self.0, |c| v8_sys::v8_SharedArrayBuffer_ByteLength(c, self.1))
+                .unwrap() as usize
+        }
+    }
+
+    /// Borrows this buffer's bytes directly from V8's allocation, without copying.
+    pub fn get_backing_store(&self) -> Option<SharedBackingStore> {
+        let contents = unsafe {
+            util::invoke(&self.0,
+                         |c| v8_sys::v8_SharedArrayBuffer_GetContents(c, self.1))
+                .unwrap()
+        };
+        if contents.data.is_null() {
+            None
+        } else {
+            Some(SharedBackingStore {
+                data: contents.data as *mut u8,
+                len: contents.length,
+                _buffer: marker::PhantomData,
+            })
+        }
+    }
+
+    /// Creates a shared array buffer from a set of raw pointers.
+    pub unsafe fn from_raw(isolate: &isolate::Isolate,
+                           raw: v8_sys::SharedArrayBufferRef)
+                           -> SharedArrayBuffer {
+        SharedArrayBuffer(isolate.clone(), raw)
+    }
+
+    /// Returns the underlying raw pointer behind this shared array buffer.
+    pub fn as_raw(&self) -> v8_sys::SharedArrayBufferRef {
+        self.1
+    }
+}
+
+impl ArrayBufferView {
+    /// The `ArrayBuffer` this view's elements are backed by.
+    pub fn buffer(&self) -> ArrayBuffer {
+        let raw = unsafe {
+            util::invoke(&self.0, |c| v8_sys::v8_ArrayBufferView_Buffer(c, self.1)).unwrap()
+        };
+        ArrayBuffer(self.0.clone(), raw)
+    }
+
+    /// This view's byte offset into `buffer()`.
+    pub fn byte_offset(&self) -> usize {
+        unsafe {
+            util::invoke(&self.0, |c| v8_sys::v8_ArrayBufferView_ByteOffset(c, self.1))
+                .unwrap() as usize
+        }
+    }
+
+    /// This view's length in bytes.
+    pub fn byte_length(&self) -> usize {
+        unsafe {
+            util::invoke(&self.0, |c| v8_sys::v8_ArrayBufferView_ByteLength(c, self.1))
+                .unwrap() as usize
+        }
+    }
+}
+
+/// Generates the `ArrayBufferView` surface (`buffer`/`byte_offset`/`byte_length`) for a concrete
+/// view type.  V8's `ArrayBufferView` is a common C++ base of every typed array and `DataView`,
+/// but since the Rust wrappers aren't actually related by inheritance, each concrete type gets its
+/// own copy, backed by the same `v8_ArrayBufferView_*` glue and a pointer reinterpret (the two
+/// types share layout, since one is just a more specifically-typed `Persistent` than the other).
+macro_rules! array_buffer_view_accessors {
+    ($typ:ident, $raw:ident, $new:ident) => {
+        impl $typ {
+            /// Creates a new view over `length` elements of `buffer`, starting at `byte_offset`.
+            pub fn new(isolate: &isolate::Isolate, buffer: &ArrayBuffer, byte_offset: usize, length: usize) -> $typ {
+                let raw = unsafe {
+                    util::invoke(&isolate,
+                                 |c| v8_sys::$new(c, buffer.as_raw(), byte_offset, length))
+                        .unwrap()
+                };
+                $typ(isolate.clone(), raw)
+            }
+
+            /// The `ArrayBuffer` this view's elements are backed by.
+            pub fn buffer(&self) -> ArrayBuffer {
+                let raw = unsafe {
+                    util::invoke(&self.0,
+                                 |c| {
+                                     v8_sys::v8_ArrayBufferView_Buffer(c,
+                                                                       self.1 as
+                                                                       v8_sys::ArrayBufferViewRef)
+                                 })
+                        .unwrap()
+                };
+                ArrayBuffer(self.0.clone(), raw)
+            }
+
+            /// This view's byte offset into `buffer()`.
+            pub fn byte_offset(&self) -> usize {
+                unsafe {
+                    util::invoke(&self.0,
+                                 |c| {
+                                     v8_sys::v8_ArrayBufferView_ByteOffset(c,
+                                                                           self.1 as
+                                                                           v8_sys::ArrayBufferViewRef)
+                                 })
+                        .unwrap() as usize
+                }
+            }
+
+            /// This view's length in bytes.
+            pub fn byte_length(&self) -> usize {
+                unsafe {
+                    util::invoke(&self.0,
+                                 |c| {
+                                     v8_sys::v8_ArrayBufferView_ByteLength(c,
+                                                                           self.1 as
+                                                                           v8_sys::ArrayBufferViewRef)
+                                 })
+                        .unwrap() as usize
+                }
+            }
+
+            /// Creates a view from a set of raw pointers.
+            pub unsafe fn from_raw(isolate: &isolate::Isolate, raw: v8_sys::$raw) -> $typ {
+                $typ(isolate.clone(), raw)
+            }
+
+            /// Returns the underlying raw pointer behind this view.
+            pub fn as_raw(&self) -> v8_sys::$raw {
+                self.1
+            }
+        }
+    }
+}
+
+array_buffer_view_accessors!(Uint8Array, Uint8ArrayRef, v8_Uint8Array_New);
+array_buffer_view_accessors!(Uint8ClampedArray, Uint8ClampedArrayRef, v8_Uint8ClampedArray_New);
+array_buffer_view_accessors!(Int8Array, Int8ArrayRef, v8_Int8Array_New);
+array_buffer_view_accessors!(Uint16Array, Uint16ArrayRef, v8_Uint16Array_New);
+array_buffer_view_accessors!(Int16Array, Int16ArrayRef, v8_Int16Array_New);
+array_buffer_view_accessors!(Uint32Array, Uint32ArrayRef, v8_Uint32Array_New);
+array_buffer_view_accessors!(Int32Array, Int32ArrayRef, v8_Int32Array_New);
+array_buffer_view_accessors!(Float32Array, Float32ArrayRef, v8_Float32Array_New);
+array_buffer_view_accessors!(Float64Array, Float64ArrayRef, v8_Float64Array_New);
+array_buffer_view_accessors!(DataView, DataViewRef, v8_DataView_New);
+
+/// Generates `as_slice`/`as_mut_slice` for a concrete typed-array type, honoring
+/// `byte_offset`/`byte_length` against its backing `ArrayBuffer`, without copying.
+macro_rules! typed_array_accessors {
+    ($typ:ident, $elem:ty) => {
+        impl $typ {
+            /// Borrows this view's elements directly from its backing `ArrayBuffer`'s
+            /// allocation, without copying.
+            ///
+            /// Returns `None` if the backing buffer has been detached.
+            pub fn as_slice(&self) -> Option<&[$elem]> {
+                let buffer = self.buffer();
+                let store = try_opt!(buffer.get_backing_store());
+                let offset = self.byte_offset();
+                let len = self.byte_length() / mem::size_of::<$elem>();
+                unsafe {
+                    Some(slice::from_raw_parts(store.as_slice()[offset..].as_ptr() as *const $elem,
+                                               len))
+                }
+            }
+
+            /// Mutably borrows this view's elements directly from its backing `ArrayBuffer`'s
+            /// allocation, without copying.
+            ///
+            /// Returns `None` if the backing buffer has been detached.
+            ///
+            /// # Safety
+            ///
+            /// This takes `&self`, not `&mut self`, because `$typ` handles are freely cloned
+            /// throughout this crate and don't carry borrow-checker-visible exclusivity. The
+            /// caller must ensure no other `&mut` slice (from this view or any other view over
+            /// the same backing `ArrayBuffer`, e.g. an aliasing sub-view) is live at the same
+            /// time, and that the backing store itself outlives the returned slice.
+            pub unsafe fn as_mut_slice(&self) -> Option<&mut [$elem]> {
+                let buffer = self.buffer();
+                let mut store = try_opt!(buffer.get_backing_store());
+                let offset = self.byte_offset();
+                let len = self.byte_length() / mem::size_of::<$elem>();
+                unsafe {
+                    Some(slice::from_raw_parts_mut(store.as_mut_slice()[offset..].as_mut_ptr() as
+                                                   *mut $elem,
+                                                   len))
+                }
+            }
+        }
+    }
+}
+
+macro_rules! try_opt {
+    ($e:expr) => {
+        match $e {
+            Some(value) => value,
+            None => return None,
+        }
+    }
+}
+
+typed_array_accessors!(Uint8Array, u8);
+typed_array_accessors!(Uint8ClampedArray, u8);
+typed_array_accessors!(Int8Array, i8);
+typed_array_accessors!(Uint16Array, u16);
+typed_array_accessors!(Int16Array, i16);
+typed_array_accessors!(Uint32Array, u32);
+typed_array_accessors!(Int32Array, i32);
+typed_array_accessors!(Float32Array, f32);
+typed_array_accessors!(Float64Array, f64);
+
+impl TypedArray {
+    /// The number of elements in this view.
+    ///
+    /// Unlike `byte_length` (available via `Deref<Target = ArrayBufferView>`), this is a count of
+    /// elements rather than bytes, which `TypedArray` can report without knowing its concrete
+    /// element kind.
+    pub fn length(&self) -> usize {
+        unsafe {
+            util::invoke(&self.0, |c| v8_sys::v8_TypedArray_Length(c, self.1)).unwrap() as usize
+        }
+    }
+
+    /// Borrows this view's elements as `&[T]`, directly from its backing `ArrayBuffer`'s
+    /// allocation, without copying.
+    ///
+    /// Returns `None` if the backing buffer has been detached.
+    ///
+    /// # Safety
+    ///
+    /// `TypedArray` is the type-erased common supertype of every concrete typed array, so unlike
+    /// those types' own `as_slice`, this has no way to check that `T` actually matches this
+    /// view's element kind (e.g. `u8` for a `Uint8Array`). The caller must ensure it does.
+    pub unsafe fn as_slice<T>(&self) -> Option<&[T]> {
+        let buffer = self.buffer();
+        let store = try_opt!(buffer.get_backing_store());
+        let offset = self.byte_offset();
+        let len = self.byte_length() / mem::size_of::<T>();
+        Some(slice::from_raw_parts(store.as_slice()[offset..].as_ptr() as *const T, len))
+    }
+
+    /// Mutably borrows this view's elements as `&mut [T]`, directly from its backing
+    /// `ArrayBuffer`'s allocation, without copying.
+    ///
+    /// Returns `None` if the backing buffer has been detached.
+    ///
+    /// # Safety
+    ///
+    /// See `as_slice`: the caller must ensure `T` matches this view's actual element kind. In
+    /// addition, because this takes `&self`, the caller must ensure no other `&mut` slice (from
+    /// this view or any other view aliasing the same backing `ArrayBuffer`) is live at the same
+    /// time, and that the backing store outlives the returned slice.
+    pub unsafe fn as_mut_slice<T>(&self) -> Option<&mut [T]> {
+        let buffer = self.buffer();
+        let mut store = try_opt!(buffer.get_backing_store());
+        let offset = self.byte_offset();
+        let len = self.byte_length() / mem::size_of::<T>();
+        Some(slice::from_raw_parts_mut(store.as_mut_slice()[offset..].as_mut_ptr() as *mut T,
+                                       len))
+    }
+}
+
+/// Generates a `DataView` getter/setter pair for `$elem`, each taking an explicit `Endianness`.
+macro_rules! data_view_accessors {
+    ($get:ident, $set:ident, $elem:ty) => {
+        /// Reads a value at `byte_offset` into this view, relative to its backing buffer.
+        ///
+        /// Returns `None` if the read would run past the end of the backing buffer, or the
+        /// buffer has been detached.
+        pub fn $get(&self, byte_offset: usize, endianness: Endianness) -> Option<$elem> {
+            let buffer = self.buffer();
+            let store = try_opt!(buffer.get_backing_store());
+            let start = self.byte_offset() + byte_offset;
+            let size = mem::size_of::<$elem>();
+            let bytes = store.as_slice();
+            if start + size > bytes.len() {
+                return None;
+            }
+
+            let mut array = [0u8; mem::size_of::<$elem>()];
+            array.copy_from_slice(&bytes[start..start + size]);
+            Some(match endianness {
+                Endianness::Little => <$elem>::from_le_bytes(array),
+                Endianness::Big => <$elem>::from_be_bytes(array),
+            })
+        }
+
+        /// Writes `value` at `byte_offset` into this view, relative to its backing buffer.
+        ///
+        /// Returns `false` (without writing anything) if the write would run past the end of
+        /// the backing buffer, or the buffer has been detached.
+        pub fn $set(&self, byte_offset: usize, value: $elem, endianness: Endianness) -> bool {
+            let buffer = self.buffer();
+            let mut store = match buffer.get_backing_store() {
+                Some(store) => store,
+                None => return false,
+            };
+            let start = self.byte_offset() + byte_offset;
+            let size = mem::size_of::<$elem>();
+            // Safety: `store` is a fresh `BackingStore` obtained just above and dropped at the
+            // end of this call, so no other slice into it can be live concurrently.
+            let bytes = unsafe { store.as_mut_slice() };
+            if start + size > bytes.len() {
+                return false;
+            }
+
+            let array = match endianness {
+                Endianness::Little => value.to_le_bytes(),
+                Endianness::Big => value.to_be_bytes(),
+            };
+            bytes[start..start + size].copy_from_slice(&array);
+            true
+        }
+    }
+}
+
+impl DataView {
+    data_view_accessors!(get_uint8, set_uint8, u8);
+    data_view_accessors!(get_int8, set_int8, i8);
+    data_view_accessors!(get_uint16, set_uint16, u16);
+    data_view_accessors!(get_int16, set_int16, i16);
+    data_view_accessors!(get_uint32, set_uint32, u32);
+    data_view_accessors!(get_int32, set_int32, i32);
+    data_view_accessors!(get_float32, set_float32, f32);
+    data_view_accessors!(get_float64, set_float64, f64);
+}
+
 impl Exception {
     pub fn range_error(isolate: &isolate::Isolate, message: &String) -> Value {
         let raw = unsafe {
@@ -1786,6 +3676,9 @@ subtype!(Uint32, Number);
 subtype!(Uint32, Primitive);
 subtype!(Uint32, Value);
 
+inherit!(BigInt, Primitive);
+subtype!(BigInt, Value);
+
 inherit!(Object, Value);
 
 inherit!(Array, Object);
@@ -1803,6 +3696,9 @@ subtype!(Function, Value);
 inherit!(Promise, Object);
 subtype!(Promise, Value);
 
+inherit!(PromiseResolver, Object);
+subtype!(PromiseResolver, Value);
+
 inherit!(Proxy, Object);
 subtype!(Proxy, Value);
 
@@ -1877,6 +3773,9 @@ subtype!(NumberObject, Value);
 inherit!(BooleanObject, Object);
 subtype!(BooleanObject, Value);
 
+inherit!(BigIntObject, Object);
+subtype!(BigIntObject, Value);
+
 inherit!(StringObject, Object);
 subtype!(StringObject, Value);
 
@@ -1902,12 +3801,16 @@ reference!(Number, v8_sys::v8_Number_CloneRef, v8_sys::v8_Number_DestroyRef);
 reference!(Integer, v8_sys::v8_Integer_CloneRef, v8_sys::v8_Integer_DestroyRef);
 reference!(Int32, v8_sys::v8_Int32_CloneRef, v8_sys::v8_Int32_DestroyRef);
 reference!(Uint32, v8_sys::v8_Uint32_CloneRef, v8_sys::v8_Uint32_DestroyRef);
+reference!(BigInt, v8_sys::v8_BigInt_CloneRef, v8_sys::v8_BigInt_DestroyRef);
 reference!(Object, v8_sys::v8_Object_CloneRef, v8_sys::v8_Object_DestroyRef);
 reference!(Array, v8_sys::v8_Array_CloneRef, v8_sys::v8_Array_DestroyRef);
 reference!(Map, v8_sys::v8_Map_CloneRef, v8_sys::v8_Map_DestroyRef);
 reference!(Set, v8_sys::v8_Set_CloneRef, v8_sys::v8_Set_DestroyRef);
 reference!(Function, v8_sys::v8_Function_CloneRef, v8_sys::v8_Function_DestroyRef);
 reference!(Promise, v8_sys::v8_Promise_CloneRef, v8_sys::v8_Promise_DestroyRef);
+reference!(PromiseResolver,
+           v8_sys::v8_PromiseResolver_CloneRef,
+           v8_sys::v8_PromiseResolver_DestroyRef);
 reference!(Proxy, v8_sys::v8_Proxy_CloneRef, v8_sys::v8_Proxy_DestroyRef);
 reference!(ArrayBuffer,
            v8_sys::v8_ArrayBuffer_CloneRef,
@@ -1954,6 +3857,9 @@ reference!(NumberObject,
 reference!(BooleanObject,
            v8_sys::v8_BooleanObject_CloneRef,
            v8_sys::v8_BooleanObject_DestroyRef);
+reference!(BigIntObject,
+           v8_sys::v8_BigIntObject_CloneRef,
+           v8_sys::v8_BigIntObject_DestroyRef);
 reference!(StringObject,
            v8_sys::v8_StringObject_CloneRef,
            v8_sys::v8_StringObject_DestroyRef);
@@ -1963,3 +3869,40 @@ reference!(SymbolObject,
 reference!(RegExp, v8_sys::v8_RegExp_CloneRef, v8_sys::v8_RegExp_DestroyRef);
 reference!(External, v8_sys::v8_External_CloneRef, v8_sys::v8_External_DestroyRef);
 reference!(Exception, v8_sys::v8_Exception_CloneRef, v8_sys::v8_Exception_DestroyRef);
+
+try_from_value!(Name, is_name);
+try_from_value!(String, is_string);
+try_from_value!(Symbol, is_symbol);
+try_from_value!(Function, is_function);
+try_from_value!(Array, is_array);
+try_from_value!(Object, is_object);
+try_from_value!(Boolean, is_boolean);
+try_from_value!(Number, is_number);
+try_from_value!(External, is_external);
+try_from_value!(Int32, is_int32);
+try_from_value!(Uint32, is_uint32);
+try_from_value!(BigInt, is_big_int);
+try_from_value!(Date, is_date);
+try_from_value!(BooleanObject, is_boolean_object);
+try_from_value!(NumberObject, is_number_object);
+try_from_value!(BigIntObject, is_big_int_object);
+try_from_value!(StringObject, is_string_object);
+try_from_value!(RegExp, is_reg_exp);
+try_from_value!(Promise, is_promise);
+try_from_value!(Map, is_map);
+try_from_value!(Set, is_set);
+try_from_value!(ArrayBuffer, is_array_buffer);
+try_from_value!(ArrayBufferView, is_array_buffer_view);
+try_from_value!(TypedArray, is_typed_array);
+try_from_value!(Uint8Array, is_uint8_array);
+try_from_value!(Uint8ClampedArray, is_uint8_clamped_array);
+try_from_value!(Int8Array, is_int8_array);
+try_from_value!(Uint16Array, is_uint16_array);
+try_from_value!(Int16Array, is_int16_array);
+try_from_value!(Uint32Array, is_uint32_array);
+try_from_value!(Int32Array, is_int32_array);
+try_from_value!(Float32Array, is_float32_array);
+try_from_value!(Float64Array, is_float64_array);
+try_from_value!(DataView, is_data_view);
+try_from_value!(SharedArrayBuffer, is_shared_array_buffer);
+try_from_value!(Proxy, is_proxy);
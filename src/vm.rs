@@ -0,0 +1,59 @@
+//! A small subsystem mirroring Node's `vm` module: compile a script once and run it against any
+//! number of freshly created sandbox contexts.
+use context;
+use error;
+use isolate;
+use script;
+use value;
+
+/// A compiled script that can be run against any number of sandboxed contexts.
+///
+/// This mirrors Node's `vm.Script`: the source is compiled once, and the result is retained so it
+/// can be re-run as many times as needed, against as many different `Context`s as needed, without
+/// paying to re-parse it.
+#[derive(Debug)]
+pub struct ContextifiedScript(script::Script);
+
+impl ContextifiedScript {
+    /// Compiles `source`, retaining the compiled script for later runs.
+    pub fn compile(
+        isolate: &isolate::Isolate,
+        context: &context::Context,
+        source: &value::String,
+    ) -> error::Result<ContextifiedScript> {
+        Ok(ContextifiedScript(try!(script::Script::compile(isolate, context, source))))
+    }
+
+    /// Enters `context`, runs this script in it, and restores whatever context was active
+    /// beforehand on the way out.
+    pub fn run_in_context(&self, context: &mut context::Context) -> error::Result<value::Value> {
+        let _scope = context.scope();
+        self.0.run(context)
+    }
+
+    /// Runs this script against `context` without entering it first.
+    ///
+    /// This assumes `context` is already the current context, for example because the caller is
+    /// already inside a `Context::scope` guard.
+    pub fn run_in_this_context(&self, context: &context::Context) -> error::Result<value::Value> {
+        self.0.run(context)
+    }
+}
+
+/// Marks `context`'s global object as a contextified sandbox, so that `is_context` can later
+/// recognize values produced by it.
+pub fn contextify(isolate: &isolate::Isolate, context: &context::Context) {
+    let marker = contextify_marker(isolate);
+    let truthy = value::true_(isolate);
+    context.global().set_private(context, &marker, &truthy);
+}
+
+/// Returns whether `object` is the global object of a sandbox previously marked with
+/// `contextify`.
+pub fn is_context(isolate: &isolate::Isolate, context: &context::Context, object: &value::Object) -> bool {
+    object.has_private(context, &contextify_marker(isolate))
+}
+
+fn contextify_marker(isolate: &isolate::Isolate) -> value::Private {
+    value::Private::for_api_name(isolate, &value::String::from_str(isolate, "v8-rs::vm::contextified"))
+}
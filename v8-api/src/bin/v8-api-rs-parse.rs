@@ -15,5 +15,7 @@ fn main() {
         path::Path::new("/usr/include/v8.h").to_path_buf()
     };
 
-    print!("{}", v8_api::read(&header_file_path, &[] as &[&path::Path]));
+    let api = v8_api::read(&header_file_path, &[] as &[&path::Path])
+        .unwrap_or_else(|err| panic!("could not read V8 API: {}", err));
+    print!("{}", api);
 }
@@ -1,6 +1,13 @@
 extern crate clang;
 #[macro_use]
 extern crate log;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 
 use std::env;
 use std::fmt;
@@ -8,13 +15,19 @@ use std::path;
 
 /// A description of the V8 API.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Api {
     /// The classes that the API consists of.
     pub classes: Vec<Class>,
+    /// Methods that were found in `v8.h` but could not be translated, together with why, so a
+    /// consumer can see exactly which parts of the V8 surface were left out for a given header
+    /// instead of only finding out from the build log.
+    pub skipped: Vec<(String, Error)>,
 }
 
 /// A C++ class,
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Class {
     /// The simple name of the class (without the `v8::` prefix).
     pub name: String,
@@ -24,6 +37,7 @@ pub struct Class {
 
 /// A C++ method
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Method {
     /// Whether the method is static.
     pub is_static: bool,
@@ -40,6 +54,7 @@ pub struct Method {
 
 /// The return type of a method.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RetType {
     /// The type is directly returned.  For primitives `T`, this means
     /// just `T` (e.g. `int`).  For references to `T`, this means
@@ -55,6 +70,7 @@ pub enum RetType {
 
 /// A method argument.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Arg {
     /// The argument name.
     pub name: String,
@@ -64,6 +80,7 @@ pub struct Arg {
 
 /// The types used in V8.
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Type {
     /// The `void` type.
     Void,
@@ -123,6 +140,52 @@ pub enum Type {
     Arr(Box<Type>),
 }
 
+/// Why a piece of the V8 surface couldn't be translated into the `Api` model.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Error {
+    /// Clang itself failed to initialize, or the translation unit failed to parse.
+    Clang(String),
+    /// A clang type that `build_type`/`build_ret_type` has no mapping for, e.g. a typedef or
+    /// unexposed type that isn't in the hand-written exception tables.
+    UnmappedType {
+        /// Clang's spelling of the type, e.g. `"v8::Eternal<v8::String>"`.
+        display_name: String,
+        /// Clang's `TypeKind` for the type, e.g. `"Unexposed"`.
+        kind: String,
+    },
+    /// A class or enum type qualified by a namespace/class other than `v8::`, e.g.
+    /// `Platform::StackTracePrinter`, which this crate has no way to model yet.
+    NestedType(String),
+    /// An entity that was expected to have a name, type, or argument list (per its `EntityKind`)
+    /// but clang didn't report one.
+    Missing(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Clang(ref message) => write!(f, "clang error: {}", message),
+            Error::UnmappedType { ref display_name, ref kind } => {
+                write!(f, "unmapped type {:?} of kind {:?}", display_name, kind)
+            }
+            Error::NestedType(ref name) => write!(f, "no support for nested type {:?}", name),
+            Error::Missing(ref what) => write!(f, "entity is missing its {}", what),
+        }
+    }
+}
+
+impl ::std::error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Clang(..) => "clang error",
+            Error::UnmappedType { .. } => "unmapped type",
+            Error::NestedType(..) => "nested type",
+            Error::Missing(..) => "entity is missing an expected attribute",
+        }
+    }
+}
+
 /// A method mangle rule.
 struct MethodMangle {
     /// The exact name of the method to mangle.
@@ -227,16 +290,11 @@ const METHOD_MANGLES: &'static [MethodMangle] = &[
 /// Reads the V8 API from the given file path pointing to a `v8.h`
 /// file (or a file that includes `v8.h`), using the specified extra
 /// includes if necessary.
-///
-/// # Panics
-///
-/// Since this library is supposed to be used in a build script,
-/// panics if anything goes wrong whatsoever.
-pub fn read<P1, P2>(file_path: P1, extra_includes: &[P2]) -> Api
+pub fn read<P1, P2>(file_path: P1, extra_includes: &[P2]) -> Result<Api, Error>
     where P1: AsRef<path::Path>,
           P2: AsRef<path::Path>
 {
-    let clang = clang::Clang::new().unwrap();
+    let clang = try!(clang::Clang::new().map_err(Error::Clang));
     let index = clang::Index::new(&clang, false, true);
 
     let mut args = vec!["-x".to_owned(),
@@ -261,77 +319,88 @@ pub fn read<P1, P2>(file_path: P1, extra_includes: &[P2]) -> Api
         }
     }
 
-    let translation_unit = index.parser(file_path.as_ref())
+    let translation_unit = try!(index.parser(file_path.as_ref())
         .arguments(&args)
         .parse()
-        .unwrap();
+        .map_err(|err| Error::Clang(format!("{:?}", err))));
 
-    build_api(&translation_unit.get_entity())
+    Ok(build_api(&translation_unit.get_entity()))
 }
 
 fn build_api(entity: &clang::Entity) -> Api {
     let namespaces = entity.get_children()
         .into_iter()
         .filter(|e| e.get_name().map(|n| n == "v8").unwrap_or(false));
-    let classes = namespaces.flat_map(|n| build_classes(&n).into_iter()).collect();
-    Api { classes: classes }
+
+    let mut classes = Vec::new();
+    let mut skipped = Vec::new();
+    for namespace in namespaces {
+        build_classes(&namespace, &mut classes, &mut skipped);
+    }
+
+    Api { classes: classes, skipped: skipped }
 }
 
-fn build_classes(entity: &clang::Entity) -> Vec<Class> {
-    entity.get_children()
-        .into_iter()
+fn build_classes(entity: &clang::Entity, classes: &mut Vec<Class>, skipped: &mut Vec<(String, Error)>) {
+    for e in entity.get_children() {
         // Is a class
-        .filter(|e| e.get_kind() == clang::EntityKind::ClassDecl)
+        if e.get_kind() != clang::EntityKind::ClassDecl {
+            continue;
+        }
         // Is not just a declaration
-        .filter(|e| !e.get_children().is_empty())
+        if e.get_children().is_empty() {
+            continue;
+        }
         // Is not nameless or special
+        let name = match e.get_name() {
+            Some(ref n) if !SPECIAL_CLASSES.contains(&n.as_str()) => n.clone(),
+            _ => continue,
+        };
+        classes.push(build_class(&e, name, skipped));
+    }
+}
+
+fn build_class(entity: &clang::Entity, name: String, skipped: &mut Vec<(String, Error)>) -> Class {
+    let methods = entity.get_children()
+        .into_iter()
+        // Is a method
+        .filter(|e| e.get_kind() == clang::EntityKind::Method)
+        // Is not deprecated
+        .filter(|e| e.get_availability() == clang::Availability::Available)
+        // Is public
+        .filter(|e| e.get_accessibility() == Some(clang::Accessibility::Public))
+        // Is not an operator or special
         .filter(|e| {
-            e.get_name().map(|ref n| !SPECIAL_CLASSES.contains(&n.as_str())).unwrap_or(false)
+            e.get_name()
+                .map(|ref n| {
+                    !n.starts_with("operator") &&
+                        !SPECIAL_METHODS.iter()
+                        .any(|m| m.0 == name &&  m.1 == n)
+                })
+                .unwrap_or(false)
         })
-        .map(|e| build_class(&e))
-        .collect::<Vec<_>>()
-}
+        .flat_map(|e| {
+            build_method(&e)
+                .map_err(|err| {
+                    let display_name = e.get_display_name().unwrap_or_else(|| "(unnamed)".to_owned());
+                    warn!("Could not translate method {}: {}", display_name, err);
+                    skipped.push((format!("{}::{}", name, display_name), err));
+                })
+        })
+        .collect();
 
-fn build_class(entity: &clang::Entity) -> Class {
-    let name = entity.get_name().unwrap();
-    Class {
-        methods: entity.get_children()
-            .into_iter()
-            // Is a method
-            .filter(|e| e.get_kind() == clang::EntityKind::Method)
-            // Is not deprecated
-            .filter(|e| e.get_availability() == clang::Availability::Available)
-            // Is public
-            .filter(|e| e.get_accessibility() == Some(clang::Accessibility::Public))
-            // Is not an operator or special
-            .filter(|e| {
-                e.get_name()
-                    .map(|ref n| {
-                        !n.starts_with("operator") &&
-                            !SPECIAL_METHODS.iter()
-                            .any(|m| m.0 == name &&  m.1 == n)
-                    })
-                    .unwrap_or(false)
-            })
-            .flat_map(|e| build_method(&e)
-                      .map_err(|err| {
-                          warn!("Could not translate method {}", e.get_display_name().unwrap_or_else(||"(unnamed)".to_owned()));
-                          err
-                      }))
-            .collect(),
-        name: name,
-    }
+    Class { methods: methods, name: name }
 }
 
-fn build_method(entity: &clang::Entity) -> Result<Method, ()> {
-    let display_name = try!(entity.get_display_name().ok_or(()));
-    let name = try!(entity.get_name().ok_or(()));
-    let args = try!(entity.get_arguments().ok_or(()));
+fn build_method(entity: &clang::Entity) -> Result<Method, Error> {
+    let display_name = try!(entity.get_display_name().ok_or(Error::Missing("display name".to_owned())));
+    let name = try!(entity.get_name().ok_or(Error::Missing("name".to_owned())));
+    let args = try!(entity.get_arguments().ok_or(Error::Missing("arguments".to_owned())));
     let args: Vec<Arg> = try!(args.iter().map(|e| build_arg(&e)).collect());
 
-    let method_type = try!(entity.get_type().ok_or(()));
+    let method_type = try!(entity.get_type().ok_or(Error::Missing("type".to_owned())));
     let method_type_display_name = method_type.get_display_name();
-    let ret_type = try!(method_type.get_result_type().ok_or(()));
+    let ret_type = try!(method_type.get_result_type().ok_or(Error::Missing("result type".to_owned())));
     let ret_type = try!(build_ret_type(&ret_type));
 
     let mangled_name = METHOD_MANGLES.iter()
@@ -351,14 +420,14 @@ fn build_method(entity: &clang::Entity) -> Result<Method, ()> {
     })
 }
 
-fn build_arg(entity: &clang::Entity) -> Result<Arg, ()> {
+fn build_arg(entity: &clang::Entity) -> Result<Arg, Error> {
     Ok(Arg {
-        name: try!(entity.get_name().ok_or(())),
+        name: try!(entity.get_name().ok_or(Error::Missing("name".to_owned()))),
         arg_type: try!(build_type(&entity.get_type().unwrap())),
     })
 }
 
-fn build_ret_type(typ: &clang::Type) -> Result<RetType, ()> {
+fn build_ret_type(typ: &clang::Type) -> Result<RetType, Error> {
     if typ.get_kind() == clang::TypeKind::Unexposed {
         let name = typ.get_display_name();
 
@@ -376,7 +445,7 @@ fn build_ret_type(typ: &clang::Type) -> Result<RetType, ()> {
     }
 }
 
-fn build_type(typ: &clang::Type) -> Result<Type, ()> {
+fn build_type(typ: &clang::Type) -> Result<Type, Error> {
     match typ.get_kind() {
         clang::TypeKind::Void => Ok(Type::Void),
         clang::TypeKind::Bool => Ok(Type::Bool),
@@ -395,12 +464,12 @@ fn build_type(typ: &clang::Type) -> Result<Type, ()> {
         clang::TypeKind::LongLong => Ok(Type::I64),
         clang::TypeKind::ULongLong => Ok(Type::U64),
         clang::TypeKind::Pointer => {
-            let inner = try!(typ.get_pointee_type().ok_or(()));
+            let inner = try!(typ.get_pointee_type().ok_or(Error::Missing("pointee type".to_owned())));
             let inner = try!(build_type(&inner));
             Ok(Type::Ptr(Box::new(inner)))
         }
         clang::TypeKind::IncompleteArray => {
-            let inner = try!(typ.get_element_type().ok_or(()));
+            let inner = try!(typ.get_element_type().ok_or(Error::Missing("element type".to_owned())));
             let inner = try!(build_type(&inner));
             Ok(Type::Arr(Box::new(inner)))
         }
@@ -409,7 +478,7 @@ fn build_type(typ: &clang::Type) -> Result<Type, ()> {
             let name = typ.get_display_name().replace("v8::", "");
             if name.contains("::") {
                 warn!("No support for nested type {:?}", name);
-                Err(())
+                Err(Error::NestedType(name))
             } else {
                 Ok(Type::Class(name))
             }
@@ -419,7 +488,7 @@ fn build_type(typ: &clang::Type) -> Result<Type, ()> {
             let name = typ.get_display_name().replace("v8::", "");
             if name.contains("::") {
                 warn!("No support for nested type {:?}", name);
-                Err(())
+                Err(Error::NestedType(name))
             } else {
                 Ok(Type::Enum(name))
             }
@@ -440,7 +509,7 @@ fn build_type(typ: &clang::Type) -> Result<Type, ()> {
                 s if s.ends_with("Callback") => Ok(Type::Callback(s.to_owned())),
                 s => {
                     warn!("Unmapped type {:?} (a typedef)", s);
-                    Err(())
+                    Err(Error::UnmappedType { display_name: s.to_owned(), kind: "Typedef".to_owned() })
                 }
             }
         }
@@ -464,7 +533,10 @@ fn build_type(typ: &clang::Type) -> Result<Type, ()> {
                         warn!("Unmapped type {:?} of kind {:?} (in unexposed exception table)",
                               n,
                               typ.get_kind());
-                        Err(())
+                        Err(Error::UnmappedType {
+                            display_name: n.to_owned(),
+                            kind: format!("{:?}", typ.get_kind()),
+                        })
                     }
                 }
             }
@@ -481,7 +553,10 @@ fn build_type(typ: &clang::Type) -> Result<Type, ()> {
                     warn!("Unmapped type {:?} of kind {:?} (in lvalue reference exception table)",
                           n,
                           typ.get_kind());
-                    Err(())
+                    Err(Error::UnmappedType {
+                        display_name: n.to_owned(),
+                        kind: format!("{:?}", typ.get_kind()),
+                    })
                 }
             }
         }
@@ -489,7 +564,10 @@ fn build_type(typ: &clang::Type) -> Result<Type, ()> {
             warn!("Unmapped type {:?} of kind {:?} (in kind dispatch table)",
                   typ.get_display_name(),
                   typ.get_kind());
-            Err(())
+            Err(Error::UnmappedType {
+                display_name: typ.get_display_name(),
+                kind: format!("{:?}", typ.get_kind()),
+            })
         }
     }
 }
@@ -499,6 +577,21 @@ fn get_first_tpl_arg<'a>(typ: &clang::Type<'a>) -> clang::Type<'a> {
     tpl_args[0].unwrap()
 }
 
+#[cfg(feature = "serde")]
+impl Api {
+    /// Serializes the model to JSON, so a build can cache it (e.g. alongside the `v8.h` it was
+    /// read from) instead of re-running clang on every build that doesn't change V8 versions.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// The inverse of `to_json`, for a code-generation back end that wants to consume a cached
+    /// model without linking against clang at all.
+    pub fn from_json(json: &str) -> serde_json::Result<Api> {
+        serde_json::from_str(json)
+    }
+}
+
 impl fmt::Display for Api {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for class in self.classes.iter() {
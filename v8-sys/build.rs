@@ -2,19 +2,230 @@ extern crate bindgen;
 extern crate cc;
 extern crate env_logger;
 extern crate pkg_config;
+extern crate shlex;
 
 use std::env;
+use std::fs;
 use std::path;
 
+/// If `TARGET` and `HOST` differ, returns the clang arguments needed to point clang (and,
+/// transitively, bindgen) at the right triple and sysroot for the cross target.
+///
+/// The sysroot is taken from `<TARGET>_SYSROOT` (e.g. `AARCH64_UNKNOWN_LINUX_GNU_SYSROOT`) if
+/// set, falling back to the generic `SYSROOT` env var understood by other `-sys` crates.
+fn cross_compile_clang_args() -> Vec<String> {
+    let target = env::var("TARGET").expect("TARGET env var not set");
+    let host = env::var("HOST").expect("HOST env var not set");
+
+    if target == host {
+        return Vec::new();
+    }
+
+    let mut args = vec!["-target".to_string(), target.clone()];
+
+    let target_specific_sysroot = format!("{}_SYSROOT", target.to_uppercase().replace('-', "_"));
+    if let Ok(sysroot) = env::var(&target_specific_sysroot) {
+        args.push(format!("--sysroot={}", sysroot));
+    } else if let Ok(sysroot) = env::var("SYSROOT") {
+        args.push(format!("--sysroot={}", sysroot));
+    }
+
+    args
+}
+
+/// Splits `BINDGEN_EXTRA_CLANG_ARGS`, if set, into shell-quoted arguments to splice onto the
+/// bindgen clang command line, letting downstream users extend it without patching this crate.
+fn extra_clang_args() -> Vec<String> {
+    match env::var("BINDGEN_EXTRA_CLANG_ARGS") {
+        Ok(extra) => shlex::split(&extra).expect("malformed BINDGEN_EXTRA_CLANG_ARGS"),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Everything the C++ compilation and bindgen need in order to see the same V8 headers,
+/// regardless of whether they came from a system install (via pkg-config) or the `vendored`
+/// fallback build.
+struct V8 {
+    include_paths: Vec<path::PathBuf>,
+    defines: Vec<(String, Option<String>)>,
+    /// The dotted V8 version (e.g. `"6.8.275.32"`), used to pick a version-appropriate binding
+    /// surface; see `major_version` and `configure_version_specific_lists`.
+    version: ::std::string::String,
+}
+
+impl From<pkg_config::Library> for V8 {
+    fn from(lib: pkg_config::Library) -> V8 {
+        V8 {
+            include_paths: lib.include_paths,
+            defines: lib.defines.into_iter().collect(),
+            version: lib.version,
+        }
+    }
+}
+
+/// Returns the major component of a dotted V8 version string (e.g. `6` for `"6.8.275.32"`).
+fn major_version(version: &str) -> u32 {
+    version
+        .split('.')
+        .next()
+        .and_then(|major| major.parse().ok())
+        .expect("unable to parse V8 major version")
+}
+
+/// V8 renames and removes types across major releases (the inline RAII scope guards blacklisted
+/// below, in particular, have shuffled names more than once), so the allow/deny lists that keep
+/// bindgen from choking have to be chosen per `major_version` rather than hardcoded for one era.
+fn configure_version_specific_lists(builder: bindgen::Builder, major_version: u32) -> bindgen::Builder {
+    let builder = builder
+        // For some reason bindgen output is corrupt (syntax errors) for this type in every
+        // version we support.
+        .blacklist_type("v8::JitCodeEvent__bindgen.*");
+
+    if major_version < 7 {
+        builder
+            .blacklist_type(".*DisallowJavascriptExecutionScope.*")
+            .blacklist_type(".*SuppressMicrotaskExecutionScope.*")
+    } else {
+        // V8 7.x folds those two scope guards into nested types under v8::Isolate that bindgen
+        // handles fine, but introduces v8::EmbedderHeapTracer with the same unparseable
+        // inline-class shape.
+        builder.blacklist_type(".*EmbedderHeapTracer.*")
+    }
+}
+
+/// Discovers the exact `-I`/`-D` flags V8 was compiled with, so the C++ glue and the generated
+/// bindings agree with V8's actual object layout (pointer compression, the sandbox, etc. are all
+/// controlled by defines, and a mismatch between what V8 was built with and what we compile
+/// against silently corrupts memory).
+///
+/// An explicit `V8_COMPILE_COMMANDS` env var pointing at a `compile_commands.json` takes
+/// precedence, since it reflects the exact translation unit V8 itself was built with. Absent
+/// that, falls back to the cflags pkg-config reported for the library.
+fn discover_abi_flags(v8: &V8) -> (Vec<path::PathBuf>, Vec<(String, Option<String>)>) {
+    match env::var("V8_COMPILE_COMMANDS") {
+        Ok(path) => parse_compile_commands(path::Path::new(&path)),
+        Err(_) => (v8.include_paths.clone(), v8.defines.clone()),
+    }
+}
+
+/// Pulls the `-I`/`-D` arguments out of the first entry's `command` in a `compile_commands.json`.
+///
+/// This is a deliberately narrow scan for the one field we need, rather than a full JSON parser:
+/// the `command` value is shell-quoted, so once it's pulled out of its `"command": "..."` line,
+/// `shlex` (already a dependency for `BINDGEN_EXTRA_CLANG_ARGS`) tokenizes it like a shell would.
+fn parse_compile_commands(path: &path::Path) -> (Vec<path::PathBuf>, Vec<(String, Option<String>)>) {
+    let contents = fs::read_to_string(path).expect("unable to read V8_COMPILE_COMMANDS file");
+    let command_line = contents
+        .lines()
+        .find(|line| line.trim_start().starts_with("\"command\""))
+        .expect("no \"command\" entry found in V8_COMPILE_COMMANDS file");
+    let raw_command = command_line
+        .splitn(2, ':')
+        .nth(1)
+        .unwrap()
+        .trim()
+        .trim_end_matches(',')
+        .trim();
+    let command = raw_command[1..raw_command.len() - 1].replace("\\\"", "\"");
+
+    let mut include_paths = Vec::new();
+    let mut defines = Vec::new();
+    for token in shlex::split(&command).expect("malformed compile_commands.json command") {
+        if token.starts_with("-I") {
+            include_paths.push(path::PathBuf::from(&token[2..]));
+        } else if token.starts_with("-D") {
+            let define = &token[2..];
+            match define.find('=') {
+                Some(i) => defines.push((define[..i].to_string(), Some(define[i + 1..].to_string()))),
+                None => defines.push((define.to_string(), None)),
+            }
+        }
+    }
+    (include_paths, defines)
+}
+
+/// Locates a usable V8: first via pkg-config, falling back to fetching and building a
+/// known-good version into `OUT_DIR` when pkg-config can't find one or the `vendored` feature
+/// is enabled.
+fn locate_v8() -> V8 {
+    if !cfg!(feature = "vendored") {
+        match pkg_config::Config::new().atleast_version("6.0.0.0").probe("v8") {
+            Ok(lib) => return lib.into(),
+            Err(err) => {
+                println!("cargo:warning=unable to locate V8 via pkg-config ({}), falling back \
+                           to the vendored build",
+                         err);
+            }
+        }
+    }
+
+    vendored::build()
+}
+
+/// The `vendored` feature's fetch-and-build fallback, used when no system V8 can be found via
+/// pkg-config.
+mod vendored {
+    use std::env;
+    use std::path;
+    use std::process;
+
+    /// The V8 checkout this crate knows how to build when vendoring.
+    const V8_TAG: &'static str = "7.0.276.3";
+
+    /// Fetches and builds `V8_TAG` into `OUT_DIR/v8`, then returns its include path and the
+    /// link directives callers need, mirroring what a turnkey self-contained `-sys` crate (e.g.
+    /// `openssl-sys` with `vendored`) does instead of requiring a hand-built system V8.
+    pub fn build() -> super::V8 {
+        let out_dir = path::PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR env var not set"));
+        let v8_dir = out_dir.join("v8");
+        let include_dir = v8_dir.join("include");
+        let lib_dir = v8_dir.join("out.gn").join("obj");
+
+        if !include_dir.join("v8.h").is_file() {
+            fetch_and_build(&v8_dir);
+        }
+
+        println!("cargo:rustc-link-search=native={}", lib_dir.display());
+        println!("cargo:rustc-link-lib=static=v8_monolith");
+
+        super::V8 {
+            include_paths: vec![include_dir],
+            defines: Vec::new(),
+            version: V8_TAG.to_string(),
+        }
+    }
+
+    /// Runs `depot_tools`' `fetch`/`gn`/`ninja` pipeline to produce a monolithic `libv8_monolith.a`
+    /// and its headers under `dest`.
+    fn fetch_and_build(dest: &path::Path) {
+        run(process::Command::new("fetch")
+                .args(&["v8"])
+                .arg(format!("--checkout={}", V8_TAG))
+                .current_dir(dest.parent().unwrap()));
+        run(process::Command::new("gn")
+                .args(&["gen", "out.gn", "--args=is_debug=false v8_monolithic=true \
+                         v8_use_external_startup_data=false is_component_build=false"])
+                .current_dir(dest));
+        run(process::Command::new("ninja")
+                .args(&["-C", "out.gn", "v8_monolith"])
+                .current_dir(dest));
+    }
+
+    fn run(command: &mut process::Command) {
+        let status = command.status().expect("failed to spawn vendored V8 build step");
+        assert!(status.success(), "vendored V8 build step failed: {:?}", command);
+    }
+}
+
 fn main() {
     env_logger::init().unwrap();
 
-    pkg_config::Config::new()
-        .atleast_version("6.0.0.0")
-        .probe("v8")
-        .expect("unable to locate V8 via pkg-config");
+    let v8 = locate_v8();
+
+    let cross_args = cross_compile_clang_args();
 
-    cc::Build::new()
+    let mut cc_build = cc::Build::new();
+    cc_build
         .cpp(true)
         .warnings(true)
         .flag("--std=c++11")
@@ -22,13 +233,46 @@ fn main() {
         .flag("-fkeep-inline-functions")
         .file("src/allocator.cpp")
         .file("src/isolate.cpp")
-        .file("src/platform.cpp")
-        .compile("librust-v8-impls.a");
+        .file("src/platform.cpp");
+    for arg in &cross_args {
+        cc_build.flag(arg);
+    }
 
-    let bindings = bindgen::Builder::default()
+    let mut bindgen_builder = bindgen::Builder::default()
         .header("src/wrapper.hpp")
         .rust_target(bindgen::RustTarget::Nightly)
-        .clang_arg("--std=c++11")
+        .clang_arg("--std=c++11");
+    for arg in cross_args.iter().chain(extra_clang_args().iter()) {
+        bindgen_builder = bindgen_builder.clang_arg(arg);
+    }
+
+    // Feed the include directories and defines V8 was actually built with into both the C++
+    // compiler and bindgen, so headers outside the default search path (and macros that affect
+    // struct layout) are seen identically by both.
+    let (abi_include_paths, abi_defines) = discover_abi_flags(&v8);
+    for include_path in &abi_include_paths {
+        cc_build.include(include_path);
+        bindgen_builder = bindgen_builder.clang_arg(format!("-I{}", include_path.display()));
+    }
+    for (name, value) in &abi_defines {
+        match *value {
+            Some(ref value) => {
+                cc_build.define(name, value.as_str());
+                bindgen_builder = bindgen_builder.clang_arg(format!("-D{}={}", name, value));
+            }
+            None => {
+                cc_build.define(name, None);
+                bindgen_builder = bindgen_builder.clang_arg(format!("-D{}", name));
+            }
+        }
+    }
+
+    cc_build.compile("librust-v8-impls.a");
+
+    let major_version = major_version(&v8.version);
+    println!("cargo:rustc-cfg=v8_version_major=\"{}\"", major_version);
+
+    let bindgen_builder = bindgen_builder
         .whitelist_type("v8::.*")
         .whitelist_type("rust_v8_impls::.*")
         .whitelist_function("v8::.*")
@@ -36,11 +280,10 @@ fn main() {
         .whitelist_var("v8::.*")
         .whitelist_var("rust_v8_impls::.*")
         // Because there are some layout problems with these
-        .opaque_type("std::.*")
-        // For some reason bindgen output is corrupt (syntax errors) for these types
-        .blacklist_type("v8::JitCodeEvent__bindgen.*")
-        .blacklist_type(".*DisallowJavascriptExecutionScope.*")
-        .blacklist_type(".*SuppressMicrotaskExecutionScope.*")
+        .opaque_type("std::.*");
+    let bindgen_builder = configure_version_specific_lists(bindgen_builder, major_version);
+
+    let bindings = bindgen_builder
         // We want to re-structure the modules a bit and hide the "root" module
         .raw_line("#[doc(hidden)]")
         .generate_inline_functions(true)
@@ -1,20 +1,226 @@
 use clang;
-use std::iter;
+use regex::Regex;
+use std::collections::HashSet;
 use std::path;
+use std::path::PathBuf;
 
 #[derive(Debug)]
-pub struct Api(pub Vec<Class>);
+pub struct Api {
+    pub classes: Vec<Class>,
+    pub callbacks: Vec<Callback>,
+    pub enums: Vec<Enum>,
+}
 
 #[derive(Debug)]
 pub struct Class(pub &'static str, pub &'static [Method]);
 
+/// The full signature of a C++ function-pointer typedef, e.g. `FunctionCallback` or
+/// `AccessorNameGetterCallback`. Unlike `Type::Ptr`, which only carries the target class name for
+/// a handle, this keeps enough information (parameter and return types) for a consumer to
+/// generate a correct `extern "C" fn(...)` type rather than hand-rolling one per callback.
+#[derive(Debug)]
+pub struct Callback {
+    pub name: String,
+    pub args: Vec<Arg>,
+    pub ret_type: RetType,
+}
+
+/// A parsed `enum`/`enum class` from `v8.h`: its underlying integer type and every enumerator's
+/// name and value, so a binding generator can emit the real Rust `enum` (or constants, see
+/// `suggested_style`) instead of duplicating V8's definitions by hand.
+#[derive(Debug)]
+pub struct Enum {
+    pub name: String,
+    pub repr: Type,
+    pub variants: Vec<(String, i64)>,
+}
+
+/// Which Rust shape `Enum` is best modeled as, mirroring bindgen's own choice between a genuine
+/// `enum` and a set of integer constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnumStyle {
+    /// A real Rust `enum`: every V8-side value is a distinct, exhaustive variant.
+    Rust,
+    /// A set of plain integer constants, for enums V8 uses as a bitmask (so callers may legally
+    /// combine or extend values beyond the named ones), like `PropertyAttribute`.
+    Constants,
+}
+
+impl Enum {
+    /// A bitmask-shaped enum (every non-zero variant a distinct power of two) can't roundtrip
+    /// through an exhaustive Rust `enum`, since combinations of its values are legal inputs;
+    /// everything else maps to a genuine `enum`.
+    pub fn suggested_style(&self) -> EnumStyle {
+        let nonzero: Vec<i64> = self.variants.iter().map(|&(_, v)| v).filter(|&v| v != 0).collect();
+        if !nonzero.is_empty() && nonzero.iter().all(|&v| v > 0 && (v & (v - 1)) == 0) {
+            EnumStyle::Constants
+        } else {
+            EnumStyle::Rust
+        }
+    }
+}
+
 #[derive(Debug)]
-pub struct Method(pub &'static str, pub &'static [Arg], pub RetType);
+pub enum Method {
+    /// An ordinary method, invoked as `self->Get(c.isolate)->Method(args...)`.
+    Instance(&'static str, &'static [Arg], RetType),
+    /// A static factory method, invoked as `v8::Class::Method(args...)` with no `self` argument at
+    /// all.  This is how values actually get minted (`String::NewFromUtf8`, `Number::New`, ...);
+    /// without it the bindings could only operate on handles that already existed.
+    Static(&'static str, &'static [Arg], RetType),
+    /// A static factory method that mints a value backed by a native Rust callback, e.g.
+    /// `FunctionTemplate::New`.  Instead of ordinary `Arg`s, the generated glue takes a
+    /// `RustCallback` function pointer plus an opaque `void *` and wires up a dedicated C++
+    /// trampoline (of the shape V8 expects, e.g. `v8::FunctionCallback`) that bridges back into
+    /// them.  See `write_callback_glue` for what actually gets emitted.
+    Callback(&'static str, RetType),
+    /// A method that reads a `String`'s contents out as an owned, heap-allocated byte buffer
+    /// instead of handing back another V8 handle, e.g. reading UTF-8 or Latin-1 bytes out via
+    /// `String::WriteUtf8`/`String::WriteOneByte`.  The `bool` selects the encoding (`true` means
+    /// one-byte/Latin-1).  Always takes just `self`, no extra `Arg`s.  See
+    /// `write_owned_string_glue` for what actually gets emitted.
+    OwnedString(&'static str, bool),
+    /// A method that hands back a *borrowed* view over memory V8 already owns, e.g.
+    /// `ArrayBuffer::GetContents`.  Unlike `OwnedString`, there's nothing to free: the backing
+    /// store lives as long as the V8 object it came from.  Always takes just `self`, no extra
+    /// `Arg`s.  See `write_borrowed_bytes_glue` for what actually gets emitted.
+    BorrowedBytes(&'static str),
+    /// Like `Callback`, but also registers a V8 "fast API" callback: a plain C function that
+    /// TurboFan-optimized code can call directly with unboxed primitive arguments, bypassing the
+    /// `RustCallback`/`FunctionCallbackInfo` trampoline entirely.  V8 falls back to the slow path
+    /// on its own whenever the optimizer can't use the fast one (e.g. on a type mismatch), so the
+    /// slow `RustCallback` must still be supplied and must remain correct on its own.  See
+    /// `write_callback_glue` for what actually gets emitted.
+    CallbackWithFastApi(&'static str, RetType, FastSignature),
+}
+
+impl Method {
+    pub fn is_static(&self) -> bool {
+        match *self {
+            Method::Instance(..) => false,
+            Method::Static(..) => true,
+            Method::Callback(..) => true,
+            Method::OwnedString(..) => false,
+            Method::BorrowedBytes(..) => false,
+            Method::CallbackWithFastApi(..) => true,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Method::Instance(name, _, _) => name,
+            Method::Static(name, _, _) => name,
+            Method::Callback(name, _) => name,
+            Method::OwnedString(name, _) => name,
+            Method::BorrowedBytes(name) => name,
+            Method::CallbackWithFastApi(name, _, _) => name,
+        }
+    }
+
+    pub fn args(&self) -> &'static [Arg] {
+        match *self {
+            Method::Instance(_, args, _) => args,
+            Method::Static(_, args, _) => args,
+            Method::Callback(..) => &[],
+            Method::OwnedString(..) => &[],
+            Method::BorrowedBytes(..) => &[],
+            Method::CallbackWithFastApi(..) => &[],
+        }
+    }
+
+    pub fn ret_type(&self) -> &RetType {
+        match *self {
+            Method::Instance(_, _, ref ret_type) => ret_type,
+            Method::Static(_, _, ref ret_type) => ret_type,
+            Method::Callback(_, ref ret_type) => ret_type,
+            Method::OwnedString(_, false) => &UTF8_RET_TYPE,
+            Method::OwnedString(_, true) => &ONE_BYTE_RET_TYPE,
+            Method::BorrowedBytes(..) => &BORROWED_BYTES_RET_TYPE,
+            Method::CallbackWithFastApi(_, ref ret_type, _) => ret_type,
+        }
+    }
+}
+
+/// One C++ type a V8 fast-API callback's parameter or return value can take.  Mirrors a useful
+/// subset of `v8::CTypeInfo::Type`.
+#[derive(Debug, Clone, Copy)]
+pub enum FastType {
+    Int32,
+    Uint32,
+    Float64,
+    Int64,
+    Bool,
+    /// A handle into the V8 heap, passed as our usual `Value *` rather than unboxed.
+    V8Value,
+}
+
+/// Whether a 64-bit integer fast-API argument/return value is surfaced to JS as a lossy `Number`
+/// or as a `BigInt`.  Mirrors `v8::CFunctionInfo::Int64Representation`.
+#[derive(Debug, Clone, Copy)]
+pub enum Int64Representation {
+    Number,
+    BigInt,
+}
+
+/// The signature of a fast-API C function: the type of each argument (the receiver is implicit
+/// and always the first parameter V8 passes, ahead of these) and of the return value, plus how
+/// 64-bit integers should be represented.  The generated C function pointer's parameter list must
+/// match `args` exactly, or V8 will crash at call time.
+#[derive(Debug)]
+pub struct FastSignature {
+    pub args: &'static [FastType],
+    pub ret: FastType,
+    pub int64_representation: Int64Representation,
+}
+
+impl FastType {
+    /// The `v8::CTypeInfo::Type` enumerator this type lowers to.
+    pub fn ctype_info_type(&self) -> &'static str {
+        match *self {
+            FastType::Int32 => "kInt32",
+            FastType::Uint32 => "kUint32",
+            FastType::Float64 => "kFloat64",
+            FastType::Int64 => "kInt64",
+            FastType::Bool => "kBool",
+            FastType::V8Value => "kV8Value",
+        }
+    }
+}
+
+impl Int64Representation {
+    /// The `v8::CFunctionInfo::Int64Representation` enumerator this selects.
+    pub fn cpp_name(&self) -> &'static str {
+        match *self {
+            Int64Representation::Number => "kNumber",
+            Int64Representation::BigInt => "kBigInt",
+        }
+    }
+}
+
+const UTF8_RET_TYPE: RetType = RetType::OwnedString(false);
+const ONE_BYTE_RET_TYPE: RetType = RetType::OwnedString(true);
+const BORROWED_BYTES_RET_TYPE: RetType = RetType::BorrowedBytes;
 
 #[derive(Debug)]
 pub enum RetType {
     Direct(Type),
+    /// The fallible counterpart to `Direct`, for methods that return V8's `MaybeLocal<T>` (or a
+    /// plain bool/numeric "did this succeed" pair) and set a pending exception on failure instead
+    /// of a C++ exception.  The generated glue still checks the result via `handle_exception`
+    /// before returning, so on the Rust side these lower to the crate's `Result<_, Error>`, with
+    /// the `Error` built from whatever `Exception` factory the pending exception came from.
     Maybe(Type),
+    /// An owned, heap-allocated string buffer (as opposed to a `Local`/`MaybeLocal` handle into
+    /// the V8 heap), for methods that the caller wants to read directly into a Rust
+    /// `String`/`Vec<u8>` without going through a `String` handle at all.  Lowers to `struct
+    /// Utf8Value { char *data; int length; }`; the caller must free the buffer via the generated
+    /// `v8_String_FreeUtf8`/`v8_String_FreeOneByte` destructor.  The `bool` is the same encoding
+    /// selector as `Method::OwnedString`.
+    OwnedString(bool),
+    /// A borrowed view (pointer + length) over memory V8 itself keeps alive, as opposed to a
+    /// handle or an owned buffer the caller must free.  Lowers to `struct Bytes { void *data;
+    /// size_t length; }`.
+    BorrowedBytes,
 }
 
 #[derive(Debug)]
@@ -33,29 +239,451 @@ pub enum Type {
     Ptr(&'static str),
 }
 
+/// Hooks a downstream caller can implement to override codegen decisions `read`/`Builder` would
+/// otherwise make from the built-in tables above, without forking the parser. Mirrors bindgen's
+/// own `ParseCallbacks` trait. Every hook has a permissive/no-op default, so callers only need to
+/// override the ones they care about.
+pub trait ParseCallbacks {
+    /// Whether to emit glue for the class at all. Defaults to `true`.
+    fn allow_class(&self, _class: &str) -> bool {
+        true
+    }
+
+    /// Whether to emit glue for this method of this class. Defaults to `true`.
+    fn allow_method(&self, _class: &str, _method: &str) -> bool {
+        true
+    }
+
+    /// Override the glue function name an overload mangles to, instead of `mangle_overloads`'s
+    /// built-in argument-type suffixing (see `signature_mangle`). Returning `None` falls back to
+    /// that default.
+    fn mangle_method(&self, _class: &str, _method: &Method) -> Option<String> {
+        None
+    }
+
+    /// Resolve a C++ type clang couldn't classify (an `Unexposed` type or an unrecognized
+    /// `Typedef`) before the method that uses it gets dropped. `display_name` is clang's
+    /// spelling of the type, e.g. `"Local<Context>"` or `"MaybeLocal<Promise>"`.
+    fn map_unknown_type(&self, _display_name: &str) -> Option<Type> {
+        None
+    }
+}
+
+/// The `ParseCallbacks` used when a caller doesn't supply their own: every hook keeps its
+/// permissive default, so parsing behaves exactly as it did before callbacks existed.
+struct NoCallbacks;
+
+impl ParseCallbacks for NoCallbacks {}
+
+/// A compiled allowlist/blocklist pair, e.g. for class or method names. An empty allowlist means
+/// "allow everything"; the blocklist is always applied on top of that, so it can carve out
+/// exceptions (like "every method starting with `Internal`") without needing a matching allowlist.
+#[derive(Default)]
+struct Filter {
+    allowlist: Vec<Regex>,
+    blocklist: Vec<Regex>,
+}
+
+impl Filter {
+    fn allows(&self, name: &str) -> bool {
+        (self.allowlist.is_empty() || self.allowlist.iter().any(|r| r.is_match(name))) &&
+        !self.blocklist.iter().any(|r| r.is_match(name))
+    }
+}
+
+/// Everything a parse needs that isn't the clang entity tree itself: the caller's
+/// `ParseCallbacks` plus the compiled class/method/type filters, bundled so the plumbing functions
+/// below only need to thread one extra argument instead of four.
+struct Context<'a> {
+    callbacks: &'a ParseCallbacks,
+    classes: Filter,
+    methods: Filter,
+    types: Filter,
+}
+
+/// Configures and runs a clang parse of `v8.h`, in the spirit of `bindgen::Builder`.
+#[derive(Default)]
+pub struct Builder {
+    header: PathBuf,
+    callbacks: Option<Box<ParseCallbacks>>,
+    classes: Filter,
+    methods: Filter,
+    types: Filter,
+}
+
+impl Builder {
+    pub fn new<P: Into<PathBuf>>(v8_header_path: P) -> Builder {
+        Builder { header: v8_header_path.into(), ..Builder::default() }
+    }
+
+    /// Supplies hooks to resolve skip/mangle/type decisions the built-in tables can't, e.g. for a
+    /// V8 version where a method was renamed or a new overload appeared.
+    pub fn parse_callbacks(mut self, callbacks: Box<ParseCallbacks>) -> Builder {
+        self.callbacks = Some(callbacks);
+        self
+    }
+
+    /// Only emit glue for classes whose name matches `pattern`. Can be called more than once;
+    /// a class matching any allowlist pattern is kept. Leaving the allowlist empty allows every
+    /// class, subject to `blocklist_class`.
+    pub fn allowlist_class(mut self, pattern: &str) -> Builder {
+        self.classes.allowlist.push(Regex::new(pattern).unwrap());
+        self
+    }
+
+    /// Never emit glue for classes whose name matches `pattern`, even if `allowlist_class` would
+    /// otherwise keep them.
+    pub fn blocklist_class(mut self, pattern: &str) -> Builder {
+        self.classes.blocklist.push(Regex::new(pattern).unwrap());
+        self
+    }
+
+    /// Only emit glue for methods whose (bare, unqualified) name matches `pattern`.
+    pub fn allowlist_method(mut self, pattern: &str) -> Builder {
+        self.methods.allowlist.push(Regex::new(pattern).unwrap());
+        self
+    }
+
+    /// Never emit glue for methods whose name matches `pattern`, e.g. `"^Internal"`.
+    pub fn blocklist_method(mut self, pattern: &str) -> Builder {
+        self.methods.blocklist.push(Regex::new(pattern).unwrap());
+        self
+    }
+
+    /// Only emit glue for a method/arg whose referenced handle type (e.g. `Context` in
+    /// `Local<Context>`) matches `pattern`; a method referencing a disallowed type is dropped
+    /// entirely, the same as an unmappable clang type.
+    pub fn allowlist_type(mut self, pattern: &str) -> Builder {
+        self.types.allowlist.push(Regex::new(pattern).unwrap());
+        self
+    }
+
+    /// Never emit glue for a method/arg referencing a handle type matching `pattern`.
+    pub fn blocklist_type(mut self, pattern: &str) -> Builder {
+        self.types.blocklist.push(Regex::new(pattern).unwrap());
+        self
+    }
+
+    pub fn parse(self) -> Api {
+        let clang = clang::Clang::new().unwrap();
+        let index = clang::Index::new(&clang, false, true);
+
+        let translation_unit = index.parser(&self.header)
+            .arguments(&["-x", "c++", "--std=c++11"])
+            .parse()
+            .unwrap();
+
+        let ctx = Context {
+            callbacks: self.callbacks.as_ref().map(|c| &**c).unwrap_or(&NoCallbacks),
+            classes: self.classes,
+            methods: self.methods,
+            types: self.types,
+        };
+
+        build_api(&translation_unit.get_entity(), &ctx)
+    }
+}
+
 pub fn read(v8_header_path: &path::Path) -> Api {
-    let clang = clang::Clang::new().unwrap();
-    let index = clang::Index::new(&clang, false, true);
+    Builder::new(v8_header_path).parse()
+}
+
+fn build_api(entity: &clang::Entity, ctx: &Context) -> Api {
+    // v8.h reopens `namespace v8 { ... }` more than once, so collect every block rather than
+    // assuming there's exactly one.
+    let namespaces: Vec<clang::Entity> = entity.get_children().into_iter()
+        .filter(|e| e.get_name().map(|n| n == "v8").unwrap_or(false))
+        .collect();
+
+    Api {
+        classes: namespaces.iter().flat_map(|n| build_classes(n, ctx).into_iter()).collect(),
+        callbacks: namespaces.iter().flat_map(|n| build_callbacks(n, ctx).into_iter()).collect(),
+        enums: namespaces.iter().flat_map(|n| build_enums(n, ctx).into_iter()).collect(),
+    }
+}
 
-    let translation_unit = index.parser(v8_header_path)
-        .arguments(&["-x", "c++", "--std=c++11"])
-        .parse()
-        .unwrap();
+/// `build_classes`' sibling for top-level `enum`/`enum class` declarations in the `v8` namespace.
+fn build_enums(entity: &clang::Entity, ctx: &Context) -> Vec<Enum> {
+    entity.get_children().into_iter()
+        .filter(|e| e.get_kind() == clang::EntityKind::EnumDecl)
+        .filter(|e| e.get_name().is_some())
+        .flat_map(|e| {
+            let name = e.get_name().unwrap();
+            build_enum(&e, ctx).map_err(|_| {
+                println!("cargo:warning=skipping unsupported enum {}", name);
+            })
+        })
+        .collect()
+}
+
+fn build_enum(entity: &clang::Entity, ctx: &Context) -> Result<Enum, ()> {
+    let name = try!(entity.get_name().ok_or(()));
+    let underlying = try!(entity.get_enum_underlying_type().ok_or(()));
+    let repr = try!(build_type(&underlying, ctx));
 
-    build_api(&translation_unit.get_entity())
+    let variants: Vec<(String, i64)> = entity.get_children().into_iter()
+        .filter(|e| e.get_kind() == clang::EntityKind::EnumConstantDecl)
+        .flat_map(|e| {
+            let variant_name = e.get_name().unwrap_or_else(|| "(unnamed)".to_owned());
+            build_enum_variant(&e).map_err(|_| {
+                println!("cargo:warning=skipping unsupported enum constant {}::{}",
+                         name, variant_name);
+            })
+        })
+        .collect();
+
+    Ok(Enum { name: name, repr: repr, variants: variants })
 }
 
-fn build_api(entity: &clang::Entity) -> Api {
-    let namespaces = entity.get_children().into_iter()
-        .filter(|e| e.get_name().map(|n| n == "v8").unwrap_or(false));
-    Api(namespaces.flat_map(|n| build_classes(&n).into_iter()).collect())
+fn build_enum_variant(entity: &clang::Entity) -> Result<(String, i64), ()> {
+    let name = try!(entity.get_name().ok_or(()));
+    let (value, _unsigned_value) = try!(entity.get_enum_constant_value().ok_or(()));
+    Ok((name, value))
 }
 
-fn build_classes(entity: &clang::Entity) -> Vec<Class> {
+fn build_classes(entity: &clang::Entity, ctx: &Context) -> Vec<Class> {
     entity.get_children().into_iter()
+        // Is a class, not just a forward-declaration
         .filter(|e| e.get_kind() == clang::EntityKind::ClassDecl)
+        .filter(|e| !e.get_children().is_empty())
         .filter(|e| e.get_name().is_some())
-        .collect::<Vec<_>>();
+        .filter(|e| ctx.callbacks.allow_class(e.get_name().unwrap().as_str()))
+        .filter(|e| ctx.classes.allows(e.get_name().unwrap().as_str()))
+        .map(|e| build_class(&e, ctx))
+        .collect::<Vec<_>>()
+}
+
+fn build_class(entity: &clang::Entity, ctx: &Context) -> Class {
+    let name = entity.get_name().unwrap();
+
+    let methods: Vec<Method> = entity.get_children().into_iter()
+        // Is a method
+        .filter(|e| e.get_kind() == clang::EntityKind::Method)
+        // Is not deprecated (V8_DEPRECATED / V8_DEPRECATE_SOON)
+        .filter(|e| e.get_availability() == clang::Availability::Available)
+        // Is public
+        .filter(|e| e.get_accessibility() == Some(clang::Accessibility::Public))
+        // Skip operators; they don't have a sensible glue name
+        .filter(|e| e.get_name().map(|n| !n.starts_with("operator")).unwrap_or(false))
+        .filter(|e| ctx.callbacks.allow_method(&name, e.get_name().unwrap().as_str()))
+        .filter(|e| ctx.methods.allows(e.get_name().unwrap().as_str()))
+        .flat_map(|e| {
+            let display_name = e.get_display_name().unwrap_or_else(|| "(unnamed)".to_owned());
+            build_method(&e, ctx).map_err(|_| {
+                println!("cargo:warning=skipping unsupported method {}::{}", name, display_name);
+            })
+        })
+        .collect();
+
+    Class(leak_str(name.clone()), leak_vec(mangle_overloads(&name, methods, ctx)))
+}
+
+/// Function-pointer typedefs all follow V8's naming convention of ending in `Callback`, e.g.
+/// `FunctionCallback` or `AccessorNameGetterCallback`.
+fn build_callbacks(entity: &clang::Entity, ctx: &Context) -> Vec<Callback> {
+    entity.get_children().into_iter()
+        .filter(|e| e.get_kind() == clang::EntityKind::TypedefDecl)
+        .filter(|e| e.get_name().map(|n| n.ends_with("Callback")).unwrap_or(false))
+        .flat_map(|e| {
+            let name = e.get_name().unwrap();
+            build_callback(&e, ctx).map_err(|_| {
+                println!("cargo:warning=skipping unsupported callback typedef {}", name);
+            })
+        })
+        .collect()
+}
+
+/// Follows a `Callback` typedef to its underlying `Pointer`-to-`FunctionProto` and walks its
+/// parameter/return types through the same `build_type`/`build_ret_type` machinery ordinary
+/// methods use, so the full ABI (not just the typedef name) is available to a consumer.
+fn build_callback(entity: &clang::Entity, ctx: &Context) -> Result<Callback, ()> {
+    let name = try!(entity.get_name().ok_or(()));
+    let underlying = try!(entity.get_typedef_underlying_type().ok_or(()));
+    let fn_type = try!(underlying.get_pointee_type().ok_or(()));
+
+    let arg_types = try!(fn_type.get_argument_types().ok_or(()));
+    let args: Vec<Arg> = try!(arg_types.iter().enumerate().map(|(i, t)| {
+        build_type(t, ctx).map(|ty| Arg(leak_str(format!("arg{}", i)), ty))
+    }).collect());
+
+    let ret_type = try!(fn_type.get_result_type().ok_or(()));
+    let ret_type = try!(build_ret_type(&ret_type, ctx));
+
+    Ok(Callback { name: name, args: args, ret_type: ret_type })
+}
+
+/// Disambiguates methods that share a name (C++ overloads), since the generated glue functions
+/// live in a single flat C namespace and can't be overloaded the way the C++ methods they wrap
+/// are. A `ParseCallbacks::mangle_method` override takes priority; failing that, every occurrence
+/// after the first gets a suffix abbreviating its argument types (see `signature_mangle`) rather
+/// than a plain counter, so the mangled name is stable across reordered/added overloads instead of
+/// depending on declaration order — the same overload-disambiguation problem bindgen solves when
+/// C++ symbols collide.
+fn mangle_overloads(class: &str, methods: Vec<Method>, ctx: &Context) -> Vec<Method> {
+    let mut seen_names: HashSet<String> = HashSet::new();
+    let mut used_names: HashSet<String> = HashSet::new();
+
+    methods.into_iter()
+        .map(|m| {
+            if let Some(name) = ctx.callbacks.mangle_method(class, &m) {
+                used_names.insert(name.clone());
+                return with_name(m, leak_str(name));
+            }
+
+            if seen_names.insert(m.name().to_owned()) {
+                used_names.insert(m.name().to_owned());
+                return m;
+            }
+
+            let signature = signature_mangle(m.args());
+            let mut mangled = format!("{}_{}", m.name(), signature);
+            let mut disambiguator = 1;
+            while used_names.contains(&mangled) {
+                disambiguator += 1;
+                mangled = format!("{}_{}_{}", m.name(), signature, disambiguator);
+            }
+            used_names.insert(mangled.clone());
+            with_name(m, leak_str(mangled))
+        })
+        .collect()
+}
+
+/// Abbreviates an argument list into a short, human-readable token: each `Type` contributes a
+/// one-or-few-character tag (`Int` -> `i`, `Ptr("String")` -> `pString`, ...), concatenated in
+/// argument order. Two overloads with the same argument types still collide deterministically
+/// here, which `mangle_overloads` breaks with a numeric tie-breaker as a last resort.
+fn signature_mangle(args: &[Arg]) -> String {
+    args.iter().map(|a| type_abbrev(&a.1)).collect::<Vec<_>>().join("")
+}
+
+fn type_abbrev(ty: &Type) -> String {
+    match *ty {
+        Type::ValBool => "b".to_owned(),
+        Type::ValInt => "i".to_owned(),
+        Type::ValF64 => "d".to_owned(),
+        Type::ValU32 => "u32".to_owned(),
+        Type::ValI32 => "i32".to_owned(),
+        Type::ValU64 => "u64".to_owned(),
+        Type::ValI64 => "i64".to_owned(),
+        Type::Ptr(name) => format!("p{}", name),
+    }
+}
+
+fn with_name(method: Method, name: &'static str) -> Method {
+    match method {
+        Method::Instance(_, args, ret_type) => Method::Instance(name, args, ret_type),
+        Method::Static(_, args, ret_type) => Method::Static(name, args, ret_type),
+    }
+}
+
+fn build_method(entity: &clang::Entity, ctx: &Context) -> Result<Method, ()> {
+    let name = try!(entity.get_name().ok_or(()));
+    let args = try!(entity.get_arguments().ok_or(()));
+    let args: Vec<Arg> = try!(args.iter().map(|e| build_arg(&e, ctx)).collect());
+
+    let method_type = try!(entity.get_type().ok_or(()));
+    let ret_type = try!(method_type.get_result_type().ok_or(()));
+    let ret_type = try!(build_ret_type(&ret_type, ctx));
+
+    let name = leak_str(name);
+    let args = leak_vec(args);
+    if entity.is_static_method() {
+        Ok(Method::Static(name, args, ret_type))
+    } else {
+        Ok(Method::Instance(name, args, ret_type))
+    }
+}
+
+fn build_arg(entity: &clang::Entity, ctx: &Context) -> Result<Arg, ()> {
+    let name = try!(entity.get_name().ok_or(()));
+    let arg_type = try!(build_type(&entity.get_type().unwrap(), ctx));
+    Ok(Arg(leak_str(name), arg_type))
+}
+
+/// Builds a `Type::Ptr` for a handle type name parsed out of e.g. `Local<Context>`, subject to
+/// the `Context`'s type filter; a disallowed type fails the same way an unmappable one does, so
+/// the method referencing it gets dropped rather than generating glue for a type nobody asked for.
+fn ptr_type(ctx: &Context, name: String) -> Result<Type, ()> {
+    if ctx.types.allows(&name) {
+        Ok(Type::Ptr(leak_str(name)))
+    } else {
+        Err(())
+    }
+}
+
+fn build_ret_type(typ: &clang::Type, ctx: &Context) -> Result<RetType, ()> {
+    if typ.get_kind() == clang::TypeKind::Unexposed {
+        let name = typ.get_display_name();
+
+        if name.starts_with("MaybeLocal<") {
+            Ok(RetType::Maybe(try!(ptr_type(ctx, get_first_tpl_arg_name(typ)))))
+        } else if name.starts_with("Maybe<") {
+            let inner = get_first_tpl_arg_name(typ);
+            match inner.as_str() {
+                "bool" => Ok(RetType::Maybe(Type::ValBool)),
+                "double" => Ok(RetType::Maybe(Type::ValF64)),
+                "uint32_t" => Ok(RetType::Maybe(Type::ValU32)),
+                "int32_t" => Ok(RetType::Maybe(Type::ValI32)),
+                "uint64_t" => Ok(RetType::Maybe(Type::ValU64)),
+                "int64_t" => Ok(RetType::Maybe(Type::ValI64)),
+                "int" => Ok(RetType::Maybe(Type::ValInt)),
+                _ => Err(()),
+            }
+        } else if name.starts_with("Local<") {
+            Ok(RetType::Direct(try!(ptr_type(ctx, get_first_tpl_arg_name(typ)))))
+        } else if let Some(mapped) = ctx.callbacks.map_unknown_type(&name) {
+            Ok(RetType::Direct(mapped))
+        } else {
+            Err(())
+        }
+    } else {
+        Ok(RetType::Direct(try!(build_type(typ, ctx))))
+    }
+}
+
+fn build_type(typ: &clang::Type, ctx: &Context) -> Result<Type, ()> {
+    match typ.get_kind() {
+        clang::TypeKind::Bool => Ok(Type::ValBool),
+        clang::TypeKind::Int => Ok(Type::ValInt),
+        clang::TypeKind::Double => Ok(Type::ValF64),
+        clang::TypeKind::UInt => Ok(Type::ValU32),
+        clang::TypeKind::LongLong => Ok(Type::ValI64),
+        clang::TypeKind::ULongLong => Ok(Type::ValU64),
+        clang::TypeKind::Pointer => {
+            let inner = try!(typ.get_pointee_type().ok_or(()));
+            let name = inner.get_display_name().replace("v8::", "").replace("const ", "");
+            ptr_type(ctx, name)
+        }
+        clang::TypeKind::Typedef => {
+            match typ.get_display_name().as_str() {
+                "uint32_t" | "const uint32_t" => Ok(Type::ValU32),
+                "int32_t" | "const int32_t" => Ok(Type::ValI32),
+                "uint64_t" | "const uint64_t" => Ok(Type::ValU64),
+                "int64_t" | "const int64_t" => Ok(Type::ValI64),
+                other => ctx.callbacks.map_unknown_type(other).ok_or(()),
+            }
+        }
+        clang::TypeKind::Unexposed => {
+            let name = typ.get_display_name();
+            if name.starts_with("Local<") {
+                ptr_type(ctx, get_first_tpl_arg_name(typ))
+            } else {
+                ctx.callbacks.map_unknown_type(&name).ok_or(())
+            }
+        }
+        _ => Err(()),
+    }
+}
+
+fn get_first_tpl_arg_name(typ: &clang::Type) -> String {
+    let tpl_args = typ.get_template_argument_types().unwrap();
+    tpl_args[0].unwrap().get_display_name().replace("v8::", "")
+}
+
+fn leak_str(s: String) -> &'static str {
+    Box::leak(s.into_boxed_str())
+}
 
-    vec![]
+fn leak_vec<T>(v: Vec<T>) -> &'static [T] {
+    Box::leak(v.into_boxed_slice())
 }
@@ -1,6 +1,7 @@
 extern crate bindgen;
 extern crate clang;
 extern crate gcc;
+extern crate regex;
 
 mod api;
 
@@ -13,6 +14,8 @@ use std::path;
 
 const NS: &'static str = "v8";
 
+/// The static archives a classic make/gyp or ninja/gyp build produces; GN builds don't use this
+/// layout at all (see `MONOLITH_LIB` below).
 const LIBS: [&'static str; 6] = ["v8_base",
                                  "v8_libbase",
                                  "v8_libsampler",
@@ -20,11 +23,23 @@ const LIBS: [&'static str; 6] = ["v8_base",
                                  "icui18n",
                                  "icuuc"];
 
+/// Modern GN builds collapse every component (and, with `use_custom_libcxx = false`, ICU) into a
+/// single `libv8_monolith.a`.
+const MONOLITH_LIB: &'static str = "v8_monolith";
+
 fn main() {
     let out_dir_str = env::var_os("OUT_DIR").unwrap();
     let out_dir_path = path::Path::new(&out_dir_str);
 
-    println!("cargo:warning={:?}", parse_api());
+    let parsed_api = parse_api();
+    println!(
+        "cargo:warning=clang parse of v8.h found {} classes ({} methods total), {} callback \
+         typedefs, and {} enums; still generating glue from the hand-maintained API table",
+        parsed_api.classes.len(),
+        parsed_api.classes.iter().map(|c| c.1.len()).sum::<usize>(),
+        parsed_api.callbacks.len(),
+        parsed_api.enums.len()
+    );
 
     link_v8();
 
@@ -51,46 +66,58 @@ fn parse_api() -> api::Api {
 }
 
 fn link_v8() {
+    let mut lib_dirs: Vec<path::PathBuf> = Vec::new();
+
     if let Some(dir_str) = env::var_os("V8_BUILD") {
         println!("V8_BUILD={:?}", dir_str);
         let dir = path::Path::new(&dir_str);
 
-        maybe_search(dir);
+        lib_dirs.push(dir.to_owned());
 
         // make+gyp-based build tree
-        maybe_search(dir.join("lib"));
-        maybe_search(dir.join("obj.target/src"));
-        maybe_search(dir.join("obj.target/third_party/icu"));
+        lib_dirs.push(dir.join("lib"));
+        lib_dirs.push(dir.join("obj.target/src"));
+        lib_dirs.push(dir.join("obj.target/third_party/icu"));
 
         // ninja+gyp-based build tree
-        maybe_search(dir.join("lib"));
-        maybe_search(dir.join("obj/src"));
-        maybe_search(dir.join("obj/third_party/icu"));
+        lib_dirs.push(dir.join("obj/src"));
+        lib_dirs.push(dir.join("obj/third_party/icu"));
 
-        // TODO: for GN-based builds it doesn't seem like the build
-        // produces static archives; maybe run ar here?
+        // GN-based build tree: `obj` holds `libv8_monolith.a` for the default monolithic build,
+        // while `lib.unstripped` holds the per-component `.so`/`.dylib` outputs of an
+        // `is_component_build = true` build.
+        lib_dirs.push(dir.join("obj"));
+        lib_dirs.push(dir.join("lib.unstripped"));
     } else {
         println!("V8_BUILD not set, searching system paths");
-        maybe_search("/usr/lib");
-        maybe_search("/usr/local/lib");
+        lib_dirs.push(path::PathBuf::from("/usr/lib"));
+        lib_dirs.push(path::PathBuf::from("/usr/local/lib"));
         // TODO: hack: lazy way to fix the Travis build
-        maybe_search("/usr/lib/x86_64-linux-gnu");
-        maybe_search("/usr/local/lib/x86_64-linux-gnu");
-        maybe_search("/usr/lib/v8");
-        maybe_search("/usr/local/lib/v8");
+        lib_dirs.push(path::PathBuf::from("/usr/lib/x86_64-linux-gnu"));
+        lib_dirs.push(path::PathBuf::from("/usr/local/lib/x86_64-linux-gnu"));
+        lib_dirs.push(path::PathBuf::from("/usr/lib/v8"));
+        lib_dirs.push(path::PathBuf::from("/usr/local/lib/v8"));
+    }
+
+    for dir in &lib_dirs {
+        maybe_search(dir);
     }
 
     if cfg!(feature = "shared") {
         println!("cargo:rustc-link-lib=dylib=v8");
         println!("cargo:rustc-link-lib=dylib=icui18n");
         println!("cargo:rustc-link-lib=dylib=icuuc");
+    } else if lib_dirs.iter().any(|dir| has_static_lib(dir, MONOLITH_LIB)) {
+        // A GN monolith build: one archive instead of the classic `LIBS` set.
+        println!("cargo:rustc-link-lib=static={}", MONOLITH_LIB);
     } else {
         for lib in LIBS.iter() {
             println!("cargo:rustc-link-lib=static={}", lib);
         }
-        if fs::metadata("/usr/lib/x86_64-linux-gnu/libicudata.a").map(|m| m.is_file()).unwrap_or(false) {
-            println!("cargo:rustc-link-lib=static=icudata");
-        }
+    }
+
+    if lib_dirs.iter().any(|dir| has_static_lib(dir, "icudata")) {
+        println!("cargo:rustc-link-lib=static=icudata");
     }
 }
 
@@ -101,6 +128,15 @@ fn maybe_search<P>(dir: P) where P: AsRef<path::Path> {
     }
 }
 
+/// Whether `dir` contains a static archive for `name`, i.e. `lib{name}.a`.  Used to pick a link
+/// mode (classic component archives vs. a GN monolith) based on what's actually on disk instead
+/// of assuming a particular build tree layout.
+fn has_static_lib<P>(dir: P, name: &str) -> bool
+    where P: AsRef<path::Path>
+{
+    fs::metadata(dir.as_ref().join(format!("lib{}.a", name))).map(|m| m.is_file()).unwrap_or(false)
+}
+
 fn gen_bindings(out_dir_path: &path::Path, bindings_path: &path::Path) {
     use std::io::Write;
 
@@ -143,6 +179,16 @@ fn build_glue(out_dir_path: &path::Path) {
     config.compile("libv8sysglue.a");
 }
 
+/// The table's flat class names double as C identifiers for the generated glue functions
+/// (`v8_PromiseResolver_New`), but a few of them name a nested V8 type rather than a top-level
+/// one. Translate to the real, possibly-qualified C++ path when building a `v8::` type reference.
+fn cpp_class_path(class: &str) -> &str {
+    match class {
+        "PromiseResolver" => "Promise::Resolver",
+        other => other,
+    }
+}
+
 fn write_header<W>(mut out: W) -> io::Result<()>
     where W: io::Write
 {
@@ -152,28 +198,76 @@ fn write_header<W>(mut out: W) -> io::Result<()>
         try!(writeln!(out, ""));
         try!(writeln!(out, "#if defined __cplusplus"));
         try!(writeln!(out,
-                      "typedef v8::Persistent<v8::{class}> {class};",
+                      "typedef v8::Persistent<v8::{cpp_class}> {class};",
+                      cpp_class = cpp_class_path(class.0),
                       class = class.0));
         try!(writeln!(out, "#else"));
         try!(writeln!(out, "typedef void {class};", class = class.0));
         try!(writeln!(out, "#endif /* defined __cplusplus */"));
     }
 
+    try!(writeln!(out, ""));
+    try!(writeln!(out,
+                  "/* A native Rust function exposed to JS, e.g. as a FunctionTemplate's \
+                   callback.  `this_` is the receiver V8 invoked the call on. */"));
+    try!(writeln!(out,
+                  "typedef Value *(*RustCallback)(RustContext c, int argc, Value **argv, \
+                   Value *this_);"));
+
     for class in API.iter() {
         try!(writeln!(out, ""));
 
         for method in class.1.iter() {
+            if let Method::Callback(method_name, ref ret_type) = *method {
+                try!(writeln!(out,
+                              "{retty} {ns}_{class}_{method}(RustContext c, RustCallback \
+                               callback, void *data);",
+                              ns = NS,
+                              retty = ret_type,
+                              class = class.0,
+                              method = method_name));
+                continue;
+            }
+
+            if let Method::CallbackWithFastApi(method_name, ref ret_type, ref sig) = *method {
+                try!(write!(out,
+                            "{retty} {ns}_{class}_{method}(RustContext c, RustCallback \
+                             callback, void *data, {fastretty} (*fast_callback)(void *receiver",
+                            ns = NS,
+                            retty = ret_type,
+                            fastretty = sig.ret,
+                            class = class.0,
+                            method = method_name));
+                for (i, arg) in sig.args.iter().enumerate() {
+                    try!(write!(out, ", {arg} a{i}", arg = arg, i = i));
+                }
+                try!(writeln!(out, "));"));
+                continue;
+            }
+
             try!(write!(out,
-                        "{retty} {ns}_{class}_{method}(RustContext c, {class} *self",
+                        "{retty} {ns}_{class}_{method}(RustContext c",
                         ns = NS,
-                        retty = method.2,
+                        retty = method.ret_type(),
                         class = class.0,
-                        method = method.0));
+                        method = method.name()));
+
+            if !method.is_static() {
+                try!(write!(out, ", {class} *self", class = class.0));
+            }
 
-            for arg in method.1.iter() {
+            for arg in method.args().iter() {
                 try!(write!(out, ", {arg}", arg = arg));
             }
             try!(writeln!(out, ");"));
+
+            if let Method::OwnedString(_, one_byte) = *method {
+                try!(writeln!(out,
+                              "void {ns}_{class}_{free}(struct Utf8Value value);",
+                              ns = NS,
+                              class = class.0,
+                              free = owned_string_free_name(one_byte)));
+            }
         }
         try!(writeln!(out,
                       "void {ns}_{class}_Destroy({class} *self);",
@@ -187,30 +281,83 @@ fn write_header<W>(mut out: W) -> io::Result<()>
 fn write_cc_file<W>(mut out: W) -> io::Result<()>
     where W: io::Write
 {
+    try!(writeln!(out, ""));
+    try!(writeln!(out, "namespace {{"));
+    try!(writeln!(out, "struct CallbackData {{"));
+    try!(writeln!(out, "  RustCallback callback;"));
+    try!(writeln!(out, "  void *data;"));
+    try!(writeln!(out,
+                  "  // Kept alive so the weak callback below can clear it once the External \
+                   itself is collected."));
+    try!(writeln!(out, "  v8::Persistent<v8::External> external;"));
+    try!(writeln!(out, "}};"));
+    try!(writeln!(out,
+                  "// Ties `callback_data`'s lifetime to the External that smuggles it into the \
+                   callback trampoline's FunctionCallbackInfo::Data(), so it's freed when V8 GCs \
+                   that External instead of leaking for the isolate's whole lifetime."));
+    try!(writeln!(out,
+                  "void FreeCallbackData(const v8::WeakCallbackInfo<CallbackData> &info) {{"));
+    try!(writeln!(out, "  CallbackData *callback_data = info.GetParameter();"));
+    try!(writeln!(out, "  callback_data->external.Reset();"));
+    try!(writeln!(out, "  delete callback_data;"));
+    try!(writeln!(out, "}}"));
+    try!(writeln!(out, "}}  // namespace"));
+
     for class in API.iter() {
         for method in class.1.iter() {
+            if let Method::Callback(method_name, ref ret_type) = *method {
+                try!(write_callback_glue(&mut out, class.0, method_name, ret_type));
+                continue;
+            }
+
+            if let Method::CallbackWithFastApi(method_name, ref ret_type, ref sig) = *method {
+                try!(write_fast_callback_glue(&mut out, class.0, method_name, ret_type, sig));
+                continue;
+            }
+
+            if let Method::OwnedString(method_name, one_byte) = *method {
+                try!(write_owned_string_glue(&mut out, class.0, method_name, one_byte));
+                continue;
+            }
+
+            if let Method::BorrowedBytes(method_name) = *method {
+                try!(write_borrowed_bytes_glue(&mut out, class.0, method_name));
+                continue;
+            }
+
             try!(writeln!(out, ""));
             try!(write!(out,
-                        "{retty} {ns}_{class}_{method}(RustContext c, {class} *self",
+                        "{retty} {ns}_{class}_{method}(RustContext c",
                         ns = NS,
-                        retty = method.2,
+                        retty = method.ret_type(),
                         class = class.0,
-                        method = method.0));
+                        method = method.name()));
+
+            if !method.is_static() {
+                try!(write!(out, ", {class} *self", class = class.0));
+            }
 
-            for arg in method.1.iter() {
+            for arg in method.args().iter() {
                 try!(write!(out, ", {arg}", arg = arg));
             }
             try!(writeln!(out, ") {{"));
             try!(writeln!(out, "  v8::HandleScope scope(c.isolate);"));
             try!(writeln!(out, "  v8::TryCatch try_catch(c.isolate);"));
-            if let Some(&Arg(ctx, Type::Ptr("Context"))) = method.1.iter().next() {
+            if let Some(&Arg(ctx, Type::Ptr("Context"))) = method.args().iter().next() {
                 try!(writeln!(out, "  v8::Context::Scope {ctx}_scope(wrap(c.isolate, {ctx}));", ctx=ctx));
             }
-            try!(write!(out,
-                        "  auto result = self->Get(c.isolate)->{method}(",
-                        method = method.0));
+            if method.is_static() {
+                try!(write!(out,
+                            "  auto result = v8::{class}::{method}(",
+                            class = cpp_class_path(class.0),
+                            method = method.name()));
+            } else {
+                try!(write!(out,
+                            "  auto result = self->Get(c.isolate)->{method}(",
+                            method = method.name()));
+            }
             let mut needs_sep = false;
-            for arg in method.1.iter() {
+            for arg in method.args().iter() {
                 if needs_sep {
                     try!(write!(out, ", "));
                 }
@@ -219,7 +366,7 @@ fn write_cc_file<W>(mut out: W) -> io::Result<()>
             }
             try!(writeln!(out, ");"));
             try!(writeln!(out, "  handle_exception(c, try_catch);"));
-            try!(writeln!(out, "  return {retunwrap}(c.isolate, result);", retunwrap = method.2.unwrap_fun()));
+            try!(writeln!(out, "  return {retunwrap}(c.isolate, result);", retunwrap = method.ret_type().unwrap_fun()));
             try!(writeln!(out, "}}"));
         }
 
@@ -235,6 +382,260 @@ fn write_cc_file<W>(mut out: W) -> io::Result<()>
     Ok(())
 }
 
+/// The name of the generated destructor that frees the buffer behind a `struct Utf8Value`
+/// returned by a `Method::OwnedString` entry with the given encoding.
+fn owned_string_free_name(one_byte: bool) -> &'static str {
+    if one_byte { "FreeOneByte" } else { "FreeUtf8" }
+}
+
+/// Emits the glue for a `Method::OwnedString` entry.  Reads the `String`'s bytes directly into a
+/// `malloc`'d buffer via `String::WriteUtf8`/`String::WriteOneByte` instead of minting another
+/// `String` handle, and emits a matching `v8_String_Free{Utf8,OneByte}` that frees it.
+fn write_owned_string_glue<W>(mut out: W, class: &str, method: &str, one_byte: bool) -> io::Result<()>
+    where W: io::Write
+{
+    let write_method = if one_byte { "WriteOneByte" } else { "WriteUtf8" };
+    let length_method = if one_byte { "Length" } else { "Utf8Length" };
+    let cast = if one_byte { "reinterpret_cast<uint8_t *>(data)" } else { "data" };
+
+    try!(writeln!(out, ""));
+    try!(writeln!(out,
+                  "struct Utf8Value {ns}_{class}_{method}(RustContext c, {class} *self) {{",
+                  ns = NS,
+                  class = class,
+                  method = method));
+    try!(writeln!(out, "  v8::HandleScope scope(c.isolate);"));
+    try!(writeln!(out, "  v8::TryCatch try_catch(c.isolate);"));
+    try!(writeln!(out, "  auto str = self->Get(c.isolate);"));
+    try!(writeln!(out, "  int length = str->{length_method}();", length_method = length_method));
+    try!(writeln!(out, "  char *data = static_cast<char *>(malloc(length));"));
+    try!(writeln!(out, "  str->{write_method}({cast}, 0, length);", write_method = write_method, cast = cast));
+    try!(writeln!(out, "  handle_exception(c, try_catch);"));
+    try!(writeln!(out, "  return Utf8Value {{ data, length }};"));
+    try!(writeln!(out, "}}"));
+
+    try!(writeln!(out, ""));
+    try!(writeln!(out,
+                  "void {ns}_{class}_{free}(struct Utf8Value value) {{",
+                  ns = NS,
+                  class = class,
+                  free = owned_string_free_name(one_byte)));
+    try!(writeln!(out, "  free(value.data);"));
+    try!(writeln!(out, "}}"));
+
+    Ok(())
+}
+
+/// Emits the glue for a `Method::BorrowedBytes` entry, e.g. `ArrayBuffer::GetContents`.
+/// Externalizes the buffer first (if it isn't already) so the ownership transfer out of V8's
+/// internal heap management is explicit, then hands back a view over the backing store; there's
+/// no matching destructor to call since V8, not Rust, still owns the memory.
+fn write_borrowed_bytes_glue<W>(mut out: W, class: &str, method: &str) -> io::Result<()>
+    where W: io::Write
+{
+    try!(writeln!(out, ""));
+    try!(writeln!(out,
+                  "struct Bytes {ns}_{class}_{method}(RustContext c, {class} *self) {{",
+                  ns = NS,
+                  class = class,
+                  method = method));
+    try!(writeln!(out, "  v8::HandleScope scope(c.isolate);"));
+    try!(writeln!(out, "  v8::TryCatch try_catch(c.isolate);"));
+    try!(writeln!(out, "  auto buffer = self->Get(c.isolate);"));
+    try!(writeln!(out, "  if (!buffer->IsExternal()) {{"));
+    try!(writeln!(out, "    buffer->Externalize(buffer->GetContents());"));
+    try!(writeln!(out, "  }}"));
+    try!(writeln!(out, "  auto contents = buffer->GetContents();"));
+    try!(writeln!(out, "  handle_exception(c, try_catch);"));
+    try!(writeln!(out,
+                  "  return Bytes {{ contents.Data(), \
+                   static_cast<size_t>(contents.ByteLength()) }};"));
+    try!(writeln!(out, "}}"));
+
+    Ok(())
+}
+
+/// Emits the trampoline and constructor glue for a `Method::Callback` entry, e.g.
+/// `FunctionTemplate::New`.  The trampoline has the shape V8 itself expects
+/// (`void(const v8::FunctionCallbackInfo<v8::Value> &)`); it unpacks the `CallbackData` stashed
+/// in `info.Data()`, copies `info[i]`/`This` into the plain pointers the Rust callback signature
+/// takes, invokes it, funnels any Rust-side error through `handle_exception` just like the
+/// ordinary glue does, and writes the result back via `GetReturnValue().Set(...)`.
+fn write_callback_glue<W>(mut out: W,
+                          class: &str,
+                          method: &str,
+                          ret_type: &RetType)
+                          -> io::Result<()>
+    where W: io::Write
+{
+    try!(write_callback_trampoline(&mut out, class, method));
+
+    try!(writeln!(out, ""));
+    try!(writeln!(out,
+                  "{retty} {ns}_{class}_{method}(RustContext c, RustCallback callback, void \
+                   *data) {{",
+                  ns = NS,
+                  retty = ret_type,
+                  class = class,
+                  method = method));
+    try!(writeln!(out, "  v8::HandleScope scope(c.isolate);"));
+    try!(write_callback_data_setup(&mut out));
+    try!(writeln!(out,
+                  "  auto result = v8::{class}::{method}(c.isolate, \
+                   {ns}_{class}_{method}_Trampoline, external);",
+                  ns = NS,
+                  class = class,
+                  method = method));
+    try!(writeln!(out,
+                  "  return {retunwrap}(c.isolate, result);",
+                  retunwrap = ret_type.unwrap_fun()));
+    try!(writeln!(out, "}}"));
+
+    Ok(())
+}
+
+/// Emits the `callback`/`data` pair's `CallbackData` allocation and wires it to be freed once the
+/// `External` that carries it into the trampoline is garbage collected, so a closure's lifetime is
+/// tied to the isolate's heap instead of leaking for as long as the process runs.  Leaves the
+/// `external` it declares ready to pass into whichever `v8::{class}::New`-family call follows.
+fn write_callback_data_setup<W>(mut out: W) -> io::Result<()>
+    where W: io::Write
+{
+    try!(writeln!(out,
+                  "  auto *callback_data = new CallbackData {{ callback, data }};"));
+    try!(writeln!(out,
+                  "  auto external = v8::External::New(c.isolate, callback_data);"));
+    try!(writeln!(out,
+                  "  callback_data->external.Reset(c.isolate, external);"));
+    try!(writeln!(out,
+                  "  callback_data->external.SetWeak(callback_data, FreeCallbackData, \
+                   v8::WeakCallbackType::kParameter);"));
+    Ok(())
+}
+
+/// Emits the `v8::FunctionCallback`-shaped trampoline shared by `Method::Callback` and
+/// `Method::CallbackWithFastApi`: it unpacks the `CallbackData` stashed in `info.Data()`, copies
+/// `info[i]`/`This` into the plain pointers the Rust callback signature takes, invokes it, funnels
+/// any Rust-side error through `handle_exception` just like the ordinary glue does, and writes the
+/// result back via `GetReturnValue().Set(...)`.
+fn write_callback_trampoline<W>(mut out: W, class: &str, method: &str) -> io::Result<()>
+    where W: io::Write
+{
+    try!(writeln!(out, ""));
+    try!(writeln!(out,
+                  "static void {ns}_{class}_{method}_Trampoline(const \
+                   v8::FunctionCallbackInfo<v8::Value> &info) {{",
+                  ns = NS,
+                  class = class,
+                  method = method));
+    try!(writeln!(out, "  v8::Isolate *isolate = info.GetIsolate();"));
+    try!(writeln!(out, "  RustContext c {{ isolate }};"));
+    try!(writeln!(out, "  v8::HandleScope scope(isolate);"));
+    try!(writeln!(out, "  v8::TryCatch try_catch(isolate);"));
+    try!(writeln!(out,
+                  "  auto *callback_data = static_cast<CallbackData \
+                   *>(v8::External::Cast(*info.Data())->Value());"));
+    try!(writeln!(out, "  int argc = info.Length();"));
+    try!(writeln!(out,
+                  "  std::vector<v8::Local<v8::Value>> arg_handles(info.Length());"));
+    try!(writeln!(out, "  std::vector<Value *> argv(info.Length());"));
+    try!(writeln!(out, "  for (int i = 0; i < argc; ++i) {{"));
+    try!(writeln!(out, "    arg_handles[i] = info[i];"));
+    try!(writeln!(out, "    argv[i] = wrap(isolate, arg_handles[i]);"));
+    try!(writeln!(out, "  }}"));
+    try!(writeln!(out, "  Value *this_ = wrap(isolate, info.This());"));
+    try!(writeln!(out,
+                  "  Value *result = callback_data->callback(c, argc, argv.data(), this_);"));
+    try!(writeln!(out, "  handle_exception(c, try_catch);"));
+    try!(writeln!(out, "  if (result != nullptr) {{"));
+    try!(writeln!(out,
+                  "    info.GetReturnValue().Set(unwrap(isolate, result));"));
+    try!(writeln!(out, "  }}"));
+    try!(writeln!(out, "}}"));
+
+    Ok(())
+}
+
+/// Emits the trampoline and constructor glue for a `Method::CallbackWithFastApi` entry, e.g.
+/// `FunctionTemplate::NewWithFastApi`.  Builds on top of `write_callback_trampoline` for the slow
+/// path V8 falls back to whenever the optimizer can't use the fast one, and additionally describes
+/// the fast C function's signature to V8 via a `v8::CFunctionInfo` (one `v8::CTypeInfo` per
+/// argument, with the implicit receiver as element 0) so TurboFan-generated code can call it
+/// directly with unboxed primitives, bypassing `FunctionCallbackInfo` entirely.
+fn write_fast_callback_glue<W>(mut out: W,
+                               class: &str,
+                               method: &str,
+                               ret_type: &RetType,
+                               sig: &FastSignature)
+                               -> io::Result<()>
+    where W: io::Write
+{
+    try!(write_callback_trampoline(&mut out, class, method));
+
+    try!(writeln!(out, ""));
+    try!(writeln!(out, "namespace {{"));
+    try!(write!(out,
+                "static const v8::CTypeInfo {ns}_{class}_{method}_FastArgInfo[] = {{",
+                ns = NS,
+                class = class,
+                method = method));
+    try!(write!(out, "v8::CTypeInfo(v8::CTypeInfo::Type::kV8Value)"));
+    for arg in sig.args.iter() {
+        try!(write!(out,
+                    ", v8::CTypeInfo(v8::CTypeInfo::Type::{ctype})",
+                    ctype = arg.ctype_info_type()));
+    }
+    try!(writeln!(out, "}};"));
+    try!(writeln!(out,
+                  "static const v8::CFunctionInfo {ns}_{class}_{method}_FastInfo(\n    \
+                   v8::CTypeInfo(v8::CTypeInfo::Type::{retctype}),\n    {argc},\n    \
+                   {ns}_{class}_{method}_FastArgInfo,\n    \
+                   v8::CFunctionInfo::Int64Representation::{int64_repr});",
+                  ns = NS,
+                  class = class,
+                  method = method,
+                  retctype = sig.ret.ctype_info_type(),
+                  argc = sig.args.len() + 1,
+                  int64_repr = sig.int64_representation.cpp_name()));
+    try!(writeln!(out, "}}  // namespace"));
+
+    try!(writeln!(out, ""));
+    try!(write!(out,
+                "{retty} {ns}_{class}_{method}(RustContext c, RustCallback callback, void \
+                 *data, {fastretty} (*fast_callback)(void *receiver",
+                ns = NS,
+                retty = ret_type,
+                fastretty = sig.ret,
+                class = class,
+                method = method));
+    for (i, arg) in sig.args.iter().enumerate() {
+        try!(write!(out, ", {arg} a{i}", arg = arg, i = i));
+    }
+    try!(writeln!(out, ")) {{"));
+    try!(writeln!(out, "  v8::HandleScope scope(c.isolate);"));
+    try!(write_callback_data_setup(&mut out));
+    try!(writeln!(out,
+                  "  v8::CFunction fast_function(reinterpret_cast<void *>(fast_callback), \
+                   &{ns}_{class}_{method}_FastInfo);",
+                  ns = NS,
+                  class = class,
+                  method = method));
+    try!(writeln!(out,
+                  "  auto result = v8::{class}::New(c.isolate, \
+                   {ns}_{class}_{method}_Trampoline, external, v8::Local<v8::Signature>(), 0, \
+                   v8::ConstructorBehavior::kAllow, v8::SideEffectType::kHasSideEffect, \
+                   &fast_function);",
+                  ns = NS,
+                  class = class,
+                  method = method));
+    try!(writeln!(out,
+                  "  return {retunwrap}(c.isolate, result);",
+                  retunwrap = ret_type.unwrap_fun()));
+    try!(writeln!(out, "}}"));
+
+    Ok(())
+}
+
 impl fmt::Display for Arg {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{type} {name}", name=self.0, type=self.1)
@@ -253,6 +654,9 @@ impl RetType {
                 RetType::Maybe(Type::ValI64) => "unwrap_int64_t",
                 RetType::Maybe(Type::ValInt) => "unwrap_int",
                 RetType::Maybe(Type::Ptr(_)) => "unwrap",
+                RetType::OwnedString(false) => "unwrap_utf8",
+                RetType::OwnedString(true) => "unwrap_one_byte",
+                RetType::BorrowedBytes => "unwrap_bytes",
             }
     }
 }
@@ -269,6 +673,21 @@ impl fmt::Display for RetType {
             RetType::Maybe(Type::ValI64) => write!(f, "struct MaybeI64"),
             RetType::Maybe(Type::ValInt) => write!(f, "struct MaybeInt"),
             RetType::Maybe(Type::Ptr(target)) => write!(f, "{} *", target),
+            RetType::OwnedString(_) => write!(f, "struct Utf8Value"),
+            RetType::BorrowedBytes => write!(f, "struct Bytes"),
+        }
+    }
+}
+
+impl fmt::Display for FastType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FastType::Int32 => write!(f, "int32_t"),
+            FastType::Uint32 => write!(f, "uint32_t"),
+            FastType::Float64 => write!(f, "double"),
+            FastType::Int64 => write!(f, "int64_t"),
+            FastType::Bool => write!(f, "bool"),
+            FastType::V8Value => write!(f, "Value *"),
         }
     }
 }
@@ -290,247 +709,320 @@ impl fmt::Display for Type {
 
 const API: &'static [Class] =
     &[Class("ScriptOrigin",
-            &[Method("ResourceName", &[], RetType::Direct(Type::Ptr("Value"))),
-              Method("ResourceLineOffset", &[], RetType::Direct(Type::Ptr("Integer"))),
-              Method("ResourceColumnOffset", &[], RetType::Direct(Type::Ptr("Integer"))),
-              Method("ScriptID", &[], RetType::Direct(Type::Ptr("Integer"))),
-              Method("SourceMapUrl", &[], RetType::Direct(Type::Ptr("Value")))
+            &[Method::Instance("ResourceName", &[], RetType::Direct(Type::Ptr("Value"))),
+              Method::Instance("ResourceLineOffset", &[], RetType::Direct(Type::Ptr("Integer"))),
+              Method::Instance("ResourceColumnOffset", &[], RetType::Direct(Type::Ptr("Integer"))),
+              Method::Instance("ScriptID", &[], RetType::Direct(Type::Ptr("Integer"))),
+              Method::Instance("SourceMapUrl", &[], RetType::Direct(Type::Ptr("Value")))
               // TODO: add Options
             ]),
       Class("UnboundScript",
-            &[Method("GetId", &[], RetType::Direct(Type::ValInt)),
-              Method("GetScriptName", &[], RetType::Direct(Type::Ptr("Value"))),
-              Method("GetSourceURL", &[], RetType::Direct(Type::Ptr("Value"))),
-              Method("GetSourceMappingURL", &[], RetType::Direct(Type::Ptr("Value"))),
-            Method("GetLineNumber", &[Arg("code_pos", Type::ValInt)], RetType::Direct(Type::ValInt))]),
+            &[Method::Instance("GetId", &[], RetType::Direct(Type::ValInt)),
+              Method::Instance("GetScriptName", &[], RetType::Direct(Type::Ptr("Value"))),
+              Method::Instance("GetSourceURL", &[], RetType::Direct(Type::Ptr("Value"))),
+              Method::Instance("GetSourceMappingURL", &[], RetType::Direct(Type::Ptr("Value"))),
+            Method::Instance("GetLineNumber", &[Arg("code_pos", Type::ValInt)], RetType::Direct(Type::ValInt))]),
       Class("Script",
-            &[Method("Run",
+            &[Method::Instance("Run",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::Ptr("Value"))),
-              Method("GetUnboundScript", &[], RetType::Maybe(Type::Ptr("UnboundScript")))]),
+              Method::Instance("GetUnboundScript", &[], RetType::Maybe(Type::Ptr("UnboundScript")))]),
       Class("ScriptCompiler", &[
           // TODO: methods
       ]),
       Class("Message", &[
-          Method("Get", &[], RetType::Direct(Type::Ptr("String"))),
-          Method("GetSourceLine", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::Ptr("String"))),
-          // Method("GetScriptOrigin", &[], RetType::Direct(Type::Ptr("ScriptOrigin"))),
-          Method("GetScriptResourceName", &[], RetType::Direct(Type::Ptr("Value"))),
-          Method("GetStackTrace", &[], RetType::Direct(Type::Ptr("StackTrace"))),
-          Method("GetLineNumber", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::ValInt)),
-          Method("GetStartPosition", &[], RetType::Direct(Type::ValInt)),
-          Method("GetEndPosition", &[], RetType::Direct(Type::ValInt)),
-          Method("GetStartColumn", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::ValInt)),
-          Method("GetEndColumn", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::ValInt)),
-          Method("IsSharedCrossOrigin", &[], RetType::Direct(Type::ValBool)),
-          Method("IsOpaque", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("Get", &[], RetType::Direct(Type::Ptr("String"))),
+          Method::Instance("GetSourceLine", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::Ptr("String"))),
+          // Method::Instance("GetScriptOrigin", &[], RetType::Direct(Type::Ptr("ScriptOrigin"))),
+          Method::Instance("GetScriptResourceName", &[], RetType::Direct(Type::Ptr("Value"))),
+          Method::Instance("GetStackTrace", &[], RetType::Direct(Type::Ptr("StackTrace"))),
+          Method::Instance("GetLineNumber", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::ValInt)),
+          Method::Instance("GetStartPosition", &[], RetType::Direct(Type::ValInt)),
+          Method::Instance("GetEndPosition", &[], RetType::Direct(Type::ValInt)),
+          Method::Instance("GetStartColumn", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::ValInt)),
+          Method::Instance("GetEndColumn", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::ValInt)),
+          Method::Instance("IsSharedCrossOrigin", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("IsOpaque", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("ErrorLevel", &[], RetType::Direct(Type::ValInt)),
       ]),
       Class("StackTrace", &[
-          Method("GetFrame", &[Arg("index", Type::ValU32)], RetType::Direct(Type::Ptr("StackFrame"))),
-          Method("GetFrameCount", &[], RetType::Direct(Type::ValInt)),
-          Method("AsArray", &[], RetType::Direct(Type::Ptr("Array"))),
+          Method::Instance("GetFrame", &[Arg("index", Type::ValU32)], RetType::Direct(Type::Ptr("StackFrame"))),
+          Method::Instance("GetFrameCount", &[], RetType::Direct(Type::ValInt)),
+          Method::Instance("AsArray", &[], RetType::Direct(Type::Ptr("Array"))),
       ]),
       Class("StackFrame", &[
-          Method("GetLineNumber", &[], RetType::Direct(Type::ValInt)),
-          Method("GetColumn", &[], RetType::Direct(Type::ValInt)),
-          Method("GetScriptId", &[], RetType::Direct(Type::ValInt)),
-          Method("GetScriptName", &[], RetType::Direct(Type::Ptr("String"))),
-          Method("GetScriptNameOrSourceURL", &[], RetType::Direct(Type::Ptr("String"))),
-          Method("GetFunctionName", &[], RetType::Direct(Type::Ptr("String"))),
-          Method("IsEval", &[], RetType::Direct(Type::ValBool)),
-          Method("IsConstructor", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("GetLineNumber", &[], RetType::Direct(Type::ValInt)),
+          Method::Instance("GetColumn", &[], RetType::Direct(Type::ValInt)),
+          Method::Instance("GetScriptId", &[], RetType::Direct(Type::ValInt)),
+          Method::Instance("GetScriptName", &[], RetType::Direct(Type::Ptr("String"))),
+          Method::Instance("GetScriptNameOrSourceURL", &[], RetType::Direct(Type::Ptr("String"))),
+          Method::Instance("GetFunctionName", &[], RetType::Direct(Type::Ptr("String"))),
+          Method::Instance("IsEval", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("IsConstructor", &[], RetType::Direct(Type::ValBool)),
       ]),
       Class("JSON", &[
-          Method("Parse", &[Arg("context", Type::Ptr("Context")), Arg("json_string", Type::Ptr("String"))], RetType::Maybe(Type::Ptr("Value"))),
-          Method("Stringify", &[Arg("context", Type::Ptr("Context")), Arg("json_object", Type::Ptr("Object"))], RetType::Maybe(Type::Ptr("String"))),
+          Method::Instance("Parse", &[Arg("context", Type::Ptr("Context")), Arg("json_string", Type::Ptr("String"))], RetType::Maybe(Type::Ptr("Value"))),
+          Method::Instance("Stringify", &[Arg("context", Type::Ptr("Context")), Arg("json_object", Type::Ptr("Object"))], RetType::Maybe(Type::Ptr("String"))),
       ]),
       Class("NativeWeakMap", &[
           // TODO: methods
       ]),
       // Values
       Class("Value",
-            &[Method("IsUndefined", &[], RetType::Direct(Type::ValBool)),
-              Method("IsNull", &[], RetType::Direct(Type::ValBool)),
-              Method("IsTrue", &[], RetType::Direct(Type::ValBool)),
-              Method("IsFalse", &[], RetType::Direct(Type::ValBool)),
-              Method("IsName", &[], RetType::Direct(Type::ValBool)),
-              Method("IsString", &[], RetType::Direct(Type::ValBool)),
-              Method("IsSymbol", &[], RetType::Direct(Type::ValBool)),
-              Method("IsFunction", &[], RetType::Direct(Type::ValBool)),
-              Method("IsArray", &[], RetType::Direct(Type::ValBool)),
-              Method("IsObject", &[], RetType::Direct(Type::ValBool)),
-              Method("IsBoolean", &[], RetType::Direct(Type::ValBool)),
-              Method("IsNumber", &[], RetType::Direct(Type::ValBool)),
-              Method("IsExternal", &[], RetType::Direct(Type::ValBool)),
-              Method("IsInt32", &[], RetType::Direct(Type::ValBool)),
-              Method("IsUint32", &[], RetType::Direct(Type::ValBool)),
-              Method("IsDate", &[], RetType::Direct(Type::ValBool)),
-              Method("IsArgumentsObject", &[], RetType::Direct(Type::ValBool)),
-              Method("IsBooleanObject", &[], RetType::Direct(Type::ValBool)),
-              Method("IsNumberObject", &[], RetType::Direct(Type::ValBool)),
-              Method("IsStringObject", &[], RetType::Direct(Type::ValBool)),
-              Method("IsSymbolObject", &[], RetType::Direct(Type::ValBool)),
-              Method("IsNativeError", &[], RetType::Direct(Type::ValBool)),
-              Method("IsRegExp", &[], RetType::Direct(Type::ValBool)),
-              Method("IsGeneratorFunction", &[], RetType::Direct(Type::ValBool)),
-              Method("IsGeneratorObject", &[], RetType::Direct(Type::ValBool)),
-              Method("IsPromise", &[], RetType::Direct(Type::ValBool)),
-              Method("IsMap", &[], RetType::Direct(Type::ValBool)),
-              Method("IsSet", &[], RetType::Direct(Type::ValBool)),
-              Method("IsMapIterator", &[], RetType::Direct(Type::ValBool)),
-              Method("IsSetIterator", &[], RetType::Direct(Type::ValBool)),
-              Method("IsWeakMap", &[], RetType::Direct(Type::ValBool)),
-              Method("IsWeakSet", &[], RetType::Direct(Type::ValBool)),
-              Method("IsArrayBuffer", &[], RetType::Direct(Type::ValBool)),
-              Method("IsArrayBufferView", &[], RetType::Direct(Type::ValBool)),
-              Method("IsTypedArray", &[], RetType::Direct(Type::ValBool)),
-              Method("IsUint8Array", &[], RetType::Direct(Type::ValBool)),
-              Method("IsUint8ClampedArray", &[], RetType::Direct(Type::ValBool)),
-              Method("IsInt8Array", &[], RetType::Direct(Type::ValBool)),
-              Method("IsUint16Array", &[], RetType::Direct(Type::ValBool)),
-              Method("IsInt16Array", &[], RetType::Direct(Type::ValBool)),
-              Method("IsUint32Array", &[], RetType::Direct(Type::ValBool)),
-              Method("IsInt32Array", &[], RetType::Direct(Type::ValBool)),
-              Method("IsFloat32Array", &[], RetType::Direct(Type::ValBool)),
-              Method("IsFloat64Array", &[], RetType::Direct(Type::ValBool)),
-              Method("IsDataView", &[], RetType::Direct(Type::ValBool)),
-              Method("IsSharedArrayBuffer", &[], RetType::Direct(Type::ValBool)),
-              Method("IsProxy", &[], RetType::Direct(Type::ValBool)),
-              Method("IsWebAssemblyCompiledModule",
+            &[Method::Instance("IsUndefined", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsNull", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsTrue", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsFalse", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsName", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsString", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsSymbol", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsFunction", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsArray", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsObject", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsBoolean", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsNumber", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsExternal", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsInt32", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsUint32", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsDate", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsArgumentsObject", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsBooleanObject", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsNumberObject", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsStringObject", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsSymbolObject", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsNativeError", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsRegExp", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsGeneratorFunction", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsGeneratorObject", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsPromise", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsMap", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsSet", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsMapIterator", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsSetIterator", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsWeakMap", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsWeakSet", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsArrayBuffer", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsArrayBufferView", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsTypedArray", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsUint8Array", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsUint8ClampedArray", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsInt8Array", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsUint16Array", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsInt16Array", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsUint32Array", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsInt32Array", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsFloat32Array", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsFloat64Array", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsDataView", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsSharedArrayBuffer", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsProxy", &[], RetType::Direct(Type::ValBool)),
+              Method::Instance("IsWebAssemblyCompiledModule",
                      &[],
                      RetType::Direct(Type::ValBool)),
-              Method("ToBoolean",
+              Method::Instance("ToBoolean",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::Ptr("Boolean"))),
-              Method("ToNumber",
+              Method::Instance("ToNumber",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::Ptr("Number"))),
-              Method("ToString",
+              Method::Instance("ToString",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::Ptr("String"))),
-              Method("ToDetailString",
+              Method::Instance("ToDetailString",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::Ptr("String"))),
-              Method("ToObject",
+              Method::Instance("ToObject",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::Ptr("Object"))),
-              Method("ToInteger",
+              Method::Instance("ToInteger",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::Ptr("Integer"))),
-              Method("ToUint32",
+              Method::Instance("ToUint32",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::Ptr("Uint32"))),
-              Method("ToInt32",
+              Method::Instance("ToInt32",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::Ptr("Int32"))),
-              Method("ToArrayIndex",
+              Method::Instance("ToArrayIndex",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::Ptr("Uint32"))),
-              Method("BooleanValue",
+              Method::Instance("BooleanValue",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::ValBool)),
-              Method("NumberValue",
+              Method::Instance("NumberValue",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::ValF64)),
-              Method("IntegerValue",
+              Method::Instance("IntegerValue",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::ValI64)),
-              Method("Uint32Value",
+              Method::Instance("Uint32Value",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::ValU32)),
-              Method("Int32Value",
+              Method::Instance("Int32Value",
                      &[Arg("context", Type::Ptr("Context"))],
                      RetType::Maybe(Type::ValI32)),
-              Method("Equals",
+              Method::Instance("Equals",
                      &[Arg("context", Type::Ptr("Context")), Arg("that", Type::Ptr("Value"))],
                      RetType::Maybe(Type::ValBool)),
-              Method("StrictEquals",
+              Method::Instance("StrictEquals",
                      &[Arg("that", Type::Ptr("Value"))],
                      RetType::Direct(Type::ValBool)),
-              Method("SameValue",
+              Method::Instance("SameValue",
                      &[Arg("that", Type::Ptr("Value"))],
                      RetType::Direct(Type::ValBool)),
             ]),
       Class("Primitive", &[]),
       Class("Boolean", &[
-          Method("Value", &[], RetType::Direct(Type::ValBool))
+          Method::Static("New", &[Arg("isolate", Type::Ptr("Isolate")), Arg("value", Type::ValBool)], RetType::Direct(Type::Ptr("Boolean"))),
+          Method::Instance("Value", &[], RetType::Direct(Type::ValBool))
       ]),
       Class("Name", &[
-          Method("GetIdentityHash", &[], RetType::Direct(Type::ValInt))
+          Method::Instance("GetIdentityHash", &[], RetType::Direct(Type::ValInt))
       ]),
       Class("String", &[
-          Method("Length", &[], RetType::Direct(Type::ValInt)),
-          Method("Utf8Length", &[], RetType::Direct(Type::ValInt)),
-          Method("IsOneByte", &[], RetType::Direct(Type::ValBool)),
-          Method("ContainsOnlyOneByte", &[], RetType::Direct(Type::ValBool)),
-          Method("IsExternal", &[], RetType::Direct(Type::ValBool)),
-          Method("IsExternalOneByte", &[], RetType::Direct(Type::ValBool)),
-          Method("Concat", &[Arg("left", Type::Ptr("String")), Arg("right", Type::Ptr("String"))], RetType::Direct(Type::Ptr("String"))),
+          // `NewFromUtf8` isn't representable here yet: it takes a raw `const char *` + length,
+          // and `Type` has no variant for that.  The hand-written glue in src/value.rs covers
+          // string construction directly until the codegen `Type` enum grows one.
+          Method::Instance("Length", &[], RetType::Direct(Type::ValInt)),
+          Method::Instance("Utf8Length", &[], RetType::Direct(Type::ValInt)),
+          Method::Instance("IsOneByte", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("ContainsOnlyOneByte", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("IsExternal", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("IsExternalOneByte", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("Concat", &[Arg("left", Type::Ptr("String")), Arg("right", Type::Ptr("String"))], RetType::Direct(Type::Ptr("String"))),
+          // Read the bytes straight into an owned buffer instead of minting another `String`
+          // handle just to read it back out.
+          Method::OwnedString("ReadUtf8", false),
+          Method::OwnedString("ReadOneByte", true),
       ]),
       Class("Symbol", &[
       ]),
       Class("Private", &[
-          Method("Name", &[], RetType::Direct(Type::Ptr("Value")))
+          Method::Instance("Name", &[], RetType::Direct(Type::Ptr("Value")))
       ]),
       Class("Number", &[
-          Method("Value", &[], RetType::Direct(Type::ValF64))
+          Method::Static("New", &[Arg("isolate", Type::Ptr("Isolate")), Arg("value", Type::ValF64)], RetType::Direct(Type::Ptr("Number"))),
+          Method::Instance("Value", &[], RetType::Direct(Type::ValF64))
       ]),
       Class("Integer", &[
-          Method("Value", &[], RetType::Direct(Type::ValI64))
+          Method::Static("New", &[Arg("isolate", Type::Ptr("Isolate")), Arg("value", Type::ValI32)], RetType::Direct(Type::Ptr("Integer"))),
+          Method::Instance("Value", &[], RetType::Direct(Type::ValI64))
       ]),
       Class("Int32", &[
-          Method("Value", &[], RetType::Direct(Type::ValI32))
+          Method::Instance("Value", &[], RetType::Direct(Type::ValI32))
       ]),
       Class("Uint32", &[
-          Method("Value", &[], RetType::Direct(Type::ValU32))
+          Method::Instance("Value", &[], RetType::Direct(Type::ValU32))
       ]),
       Class("Object", &[
+          Method::Static("New", &[Arg("isolate", Type::Ptr("Isolate"))], RetType::Direct(Type::Ptr("Object"))),
           // TODO: add index things
-          Method("Set", &[Arg("context", Type::Ptr("Context")),
+          Method::Instance("Set", &[Arg("context", Type::Ptr("Context")),
                           Arg("key", Type::Ptr("Value")),
                           Arg("value", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
-          Method("CreateDataProperty", &[Arg("context", Type::Ptr("Context")),
+          Method::Instance("CreateDataProperty", &[Arg("context", Type::Ptr("Context")),
                                          Arg("key", Type::Ptr("Name")),
                                          Arg("value", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
-          Method("Get", &[Arg("context", Type::Ptr("Context")),
+          Method::Instance("Get", &[Arg("context", Type::Ptr("Context")),
                           Arg("key", Type::Ptr("Value"))], RetType::Maybe(Type::Ptr("Value"))),
-          Method("GetOwnPropertyDescriptor", &[Arg("context", Type::Ptr("Context")),
+          Method::Instance("GetOwnPropertyDescriptor", &[Arg("context", Type::Ptr("Context")),
                                                Arg("key", Type::Ptr("String"))], RetType::Maybe(Type::Ptr("Value"))),
-          Method("Has", &[Arg("context", Type::Ptr("Context")),
+          Method::Instance("Has", &[Arg("context", Type::Ptr("Context")),
                           Arg("key", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
-          Method("Delete", &[Arg("context", Type::Ptr("Context")),
+          Method::Instance("Delete", &[Arg("context", Type::Ptr("Context")),
                              Arg("key", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
-          Method("GetPropertyNames", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::Ptr("Array"))),
-          Method("GetOwnPropertyNames", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::Ptr("Array"))),
-          Method("GetPrototype", &[], RetType::Direct(Type::Ptr("Value"))),
-          Method("SetPrototype", &[Arg("context", Type::Ptr("Context")),
+          Method::Instance("GetPropertyNames", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::Ptr("Array"))),
+          Method::Instance("GetOwnPropertyNames", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::Ptr("Array"))),
+          Method::Instance("GetPrototype", &[], RetType::Direct(Type::Ptr("Value"))),
+          Method::Instance("SetPrototype", &[Arg("context", Type::Ptr("Context")),
                                    Arg("value", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
-          Method("ObjectProtoToString", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::Ptr("String"))),
-          Method("GetConstructorName", &[], RetType::Direct(Type::Ptr("String"))),
-          Method("HasOwnProperty", &[Arg("context", Type::Ptr("Context")),
+          Method::Instance("ObjectProtoToString", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::Ptr("String"))),
+          Method::Instance("GetConstructorName", &[], RetType::Direct(Type::Ptr("String"))),
+          Method::Instance("HasOwnProperty", &[Arg("context", Type::Ptr("Context")),
                                      Arg("key", Type::Ptr("Name"))], RetType::Maybe(Type::ValBool)),
-          Method("HasRealNamedProperty", &[Arg("context", Type::Ptr("Context")),
+          Method::Instance("HasRealNamedProperty", &[Arg("context", Type::Ptr("Context")),
                                            Arg("key", Type::Ptr("Name"))], RetType::Maybe(Type::ValBool)),
-          Method("HasRealIndexedProperty", &[Arg("context", Type::Ptr("Context")),
+          Method::Instance("HasRealIndexedProperty", &[Arg("context", Type::Ptr("Context")),
                                              Arg("key", Type::ValU32)], RetType::Maybe(Type::ValBool)),
-          Method("GetIdentityHash", &[], RetType::Direct(Type::ValInt)),
-          Method("Clone", &[], RetType::Direct(Type::Ptr("Object"))),
-          Method("CreationContext", &[], RetType::Direct(Type::Ptr("Context"))),
-          Method("IsCallable", &[], RetType::Direct(Type::ValBool)),
-          Method("IsConstructor", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("GetIdentityHash", &[], RetType::Direct(Type::ValInt)),
+          Method::Instance("Clone", &[], RetType::Direct(Type::Ptr("Object"))),
+          Method::Instance("CreationContext", &[], RetType::Direct(Type::Ptr("Context"))),
+          Method::Instance("IsCallable", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("IsConstructor", &[], RetType::Direct(Type::ValBool)),
       ]),
       Class("Array", &[
-          Method("Length", &[], RetType::Direct(Type::ValU32))
+          // Note: the real `Array::New` also takes an initial length, but the codegen `Type` enum
+          // has no way to express the defaulted `int length = 0` parameter yet, so only the
+          // always-required overload is represented here.
+          Method::Static("New", &[Arg("isolate", Type::Ptr("Isolate"))], RetType::Direct(Type::Ptr("Array"))),
+          Method::Instance("Length", &[], RetType::Direct(Type::ValU32))
+      ]),
+      Class("Map", &[
+          Method::Static("New", &[Arg("isolate", Type::Ptr("Isolate"))], RetType::Direct(Type::Ptr("Map"))),
+          Method::Instance("Get", &[Arg("context", Type::Ptr("Context")),
+                          Arg("key", Type::Ptr("Value"))], RetType::Maybe(Type::Ptr("Value"))),
+          Method::Instance("Set", &[Arg("context", Type::Ptr("Context")),
+                          Arg("key", Type::Ptr("Value")),
+                          Arg("value", Type::Ptr("Value"))], RetType::Maybe(Type::Ptr("Map"))),
+          Method::Instance("Has", &[Arg("context", Type::Ptr("Context")),
+                          Arg("key", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
+          Method::Instance("Delete", &[Arg("context", Type::Ptr("Context")),
+                             Arg("key", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
+          Method::Instance("Size", &[], RetType::Direct(Type::ValU64)),
+          // Flattens to `[k0, v0, k1, v1, ...]`, the same layout `Map::AsArray` itself produces.
+          Method::Instance("AsArray", &[], RetType::Direct(Type::Ptr("Array"))),
+      ]),
+      Class("Set", &[
+          Method::Static("New", &[Arg("isolate", Type::Ptr("Isolate"))], RetType::Direct(Type::Ptr("Set"))),
+          Method::Instance("Add", &[Arg("context", Type::Ptr("Context")),
+                          Arg("key", Type::Ptr("Value"))], RetType::Maybe(Type::Ptr("Set"))),
+          Method::Instance("Has", &[Arg("context", Type::Ptr("Context")),
+                          Arg("key", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
+          Method::Instance("Delete", &[Arg("context", Type::Ptr("Context")),
+                             Arg("key", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
+          Method::Instance("Size", &[], RetType::Direct(Type::ValU64)),
+          // Flattens to `[v0, v1, ...]`, the same layout `Set::AsArray` itself produces.
+          Method::Instance("AsArray", &[], RetType::Direct(Type::Ptr("Array"))),
       ]),
-      Class("Map", &[]),
-      Class("Set", &[]),
       Class("Function", &[]),
-      Class("Promise", &[]),
+      Class("Promise", &[
+          Method::Instance("State", &[], RetType::Direct(Type::ValInt)),
+          Method::Instance("Result", &[], RetType::Direct(Type::Ptr("Value"))),
+          Method::Instance("Then", &[Arg("context", Type::Ptr("Context")),
+                          Arg("handler", Type::Ptr("Function"))], RetType::Maybe(Type::Ptr("Promise"))),
+          Method::Instance("Catch", &[Arg("context", Type::Ptr("Context")),
+                           Arg("handler", Type::Ptr("Function"))], RetType::Maybe(Type::Ptr("Promise"))),
+      ]),
+      Class("PromiseResolver", &[
+          Method::Static("New", &[Arg("context", Type::Ptr("Context"))], RetType::Maybe(Type::Ptr("PromiseResolver"))),
+          Method::Instance("GetPromise", &[], RetType::Direct(Type::Ptr("Promise"))),
+          Method::Instance("Resolve", &[Arg("context", Type::Ptr("Context")),
+                              Arg("value", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
+          Method::Instance("Reject", &[Arg("context", Type::Ptr("Context")),
+                             Arg("value", Type::Ptr("Value"))], RetType::Maybe(Type::ValBool)),
+      ]),
       Class("Proxy", &[]),
       Class("WasmCompiledModule", &[]),
-      Class("ArrayBuffer", &[]),
-      Class("ArrayBufferView", &[]),
-      Class("TypedArray", &[]),
+      Class("ArrayBuffer", &[
+          Method::Instance("IsExternal", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("IsNeuterable", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("ByteLength", &[], RetType::Direct(Type::ValU64)),
+          // Zero-copy access to the backing store; see `write_borrowed_bytes_glue`.
+          Method::BorrowedBytes("GetContents"),
+      ]),
+      Class("ArrayBufferView", &[
+          Method::Instance("Buffer", &[], RetType::Direct(Type::Ptr("ArrayBuffer"))),
+          Method::Instance("ByteOffset", &[], RetType::Direct(Type::ValU64)),
+          Method::Instance("ByteLength", &[], RetType::Direct(Type::ValU64)),
+      ]),
+      Class("TypedArray", &[
+          Method::Instance("Length", &[], RetType::Direct(Type::ValU64)),
+      ]),
+      // `Uint8Array::New(buffer, byte_offset, length)` and friends aren't representable here yet
+      // either (see the `String` class above for the same `Type`-enum limitation), so the
+      // concrete typed-array classes only inherit what `ArrayBufferView`/`TypedArray` already
+      // give them; minting one, or viewing its backing store as a typed `&[f64]`/etc. slice
+      // instead of raw bytes, still has to go through the hand-written glue in src/value.rs.
       Class("Uint8Array", &[]),
       Class("Uint8ClampedArray", &[]),
       Class("Int8Array", &[]),
@@ -541,7 +1033,15 @@ const API: &'static [Class] =
       Class("Float32Array", &[]),
       Class("Float64Array", &[]),
       Class("DataView", &[]),
-      Class("SharedArrayBuffer", &[]),
+      // Unlike `ArrayBuffer`, a `SharedArrayBuffer`'s backing store may be mapped into more than
+      // one isolate at once, so `GetContents` here hands back the same raw, racy view: callers
+      // must synchronize their own reads/writes (e.g. via the `Atomics` JS surface or an external
+      // lock) instead of relying on handle-scope borrowing alone for safety.
+      Class("SharedArrayBuffer", &[
+          Method::Instance("IsExternal", &[], RetType::Direct(Type::ValBool)),
+          Method::Instance("ByteLength", &[], RetType::Direct(Type::ValU64)),
+          Method::BorrowedBytes("GetContents"),
+      ]),
       Class("Date", &[]),
       Class("NumberObject", &[]),
       Class("BooleanObject", &[]),
@@ -551,10 +1051,29 @@ const API: &'static [Class] =
       Class("External", &[]),
       // Templates
       Class("Template", &[]),
-      Class("FunctionTemplate", &[]),
+      Class("FunctionTemplate",
+            &[Method::Callback("New", RetType::Direct(Type::Ptr("FunctionTemplate"))),
+              // Registers a fast C function alongside the ordinary slow callback; see
+              // `Method::CallbackWithFastApi` for the invariants this relies on.
+              Method::CallbackWithFastApi("NewWithFastApi",
+                     RetType::Direct(Type::Ptr("FunctionTemplate")),
+                     FastSignature {
+                         args: &[],
+                         ret: FastType::V8Value,
+                         int64_representation: Int64Representation::Number,
+                     })]),
       Class("Signature", &[]),
       Class("AccessorSignature", &[]),
       // Tracing
-      Class("Exception", &[]),
+      Class("Exception", &[
+          // Each mints an Error-subclass object carrying `message`; these are what the
+          // hand-written `Result<Local<T>, Error>` wrappers in src/ construct their `Error` from
+          // when a `RetType::Maybe` method comes back empty (see `handle_exception`).
+          Method::Static("RangeError", &[Arg("message", Type::Ptr("String"))], RetType::Direct(Type::Ptr("Value"))),
+          Method::Static("ReferenceError", &[Arg("message", Type::Ptr("String"))], RetType::Direct(Type::Ptr("Value"))),
+          Method::Static("SyntaxError", &[Arg("message", Type::Ptr("String"))], RetType::Direct(Type::Ptr("Value"))),
+          Method::Static("TypeError", &[Arg("message", Type::Ptr("String"))], RetType::Direct(Type::Ptr("Value"))),
+          Method::Static("Error", &[Arg("message", Type::Ptr("String"))], RetType::Direct(Type::Ptr("Value"))),
+      ]),
       // Context
       Class("Context", &[])];